@@ -4,7 +4,7 @@ layout (location = 0) in vec3 position;
 layout (location = 1) in vec2 tex_coord;
 layout (location = 2) in vec3 normal;
 layout (location = 3) in vec3 offset;
-layout (location = 4) in uint texture;
+layout (location = 4) in uvec3 textures;
 
 out VS_OUTPUT 
 {
@@ -20,11 +20,19 @@ uniform mat4 model;
 void main() 
 {
     mat4 translation = mat4(model);
-    translation[3][0] = offset.x;
-    translation[3][1] = offset.y;
-    translation[3][2] = offset.z;
+    translation[3][0] += offset.x;
+    translation[3][1] += offset.y;
+    translation[3][2] += offset.z;
     OUT.TexCoord = tex_coord;
     OUT.v_normal = transpose(inverse(mat3(translation))) * normal; 
-    OUT.texture = texture;
+
+    // Top, bottom or side texture
+    if (normal.y > 0.5)
+        OUT.texture = textures.x;
+    else if (normal.y < -0.5)
+        OUT.texture = textures.y;
+    else
+        OUT.texture = textures.z;
+
     gl_Position = perspective * view * translation * vec4(position, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+#version 330   
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec2 tex_coord;
+layout (location = 2) in vec3 normal;
+layout (location = 3) in uint texture;
+
+out VS_OUTPUT 
+{
+    vec2 TexCoord;
+    vec3 v_normal;
+    flat uint texture;
+} OUT;
+
+uniform mat4 perspective;  
+uniform mat4 view;
+uniform mat4 model;
+
+void main() 
+{
+    OUT.TexCoord = tex_coord;
+    OUT.v_normal = transpose(inverse(mat3(model))) * normal; 
+    OUT.texture = texture;
+    gl_Position = perspective * view * model * vec4(position, 1.0);
+}
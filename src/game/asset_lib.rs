@@ -1,7 +1,8 @@
 
 use std::{ collections::HashMap, rc::Rc };
-use crate::{ Gl, Mesh, Texture, Program, Flip };
+use crate::{ Gl, Mesh, Texture, Program, Flip, graphics::TextureArray };
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum AssetType
 {
     MESH,
@@ -14,6 +15,7 @@ pub struct AssetLib
     gl: Gl,
     meshes: HashMap<String, Rc<Mesh>>,
     textures: HashMap<String, Rc<Texture>>,
+    texture_arrays: HashMap<String, Rc<TextureArray>>,
     programs: HashMap<String, Rc<Program>>
 }
 
@@ -21,7 +23,7 @@ impl AssetLib
 {
     pub fn new(gl: &Gl) -> AssetLib
     {
-        AssetLib { gl: gl.clone(), meshes: HashMap::new(), textures: HashMap::new(), programs: HashMap::new() }
+        AssetLib { gl: gl.clone(), meshes: HashMap::new(), textures: HashMap::new(), texture_arrays: HashMap::new(), programs: HashMap::new() }
     }
 
     pub fn get_mesh(self: &mut AssetLib, filename: &str) -> Result<Rc<Mesh>, String>
@@ -46,6 +48,19 @@ impl AssetLib
         Ok(Rc::clone(&self.textures[filename]))
     }
 
+    /// Texture arrays are looked up by name like programs, the files are only
+    /// loaded the first time the name is used
+    pub fn get_texture_array(self: &mut AssetLib, array_name: &str, filenames: &[String], flip: Flip) -> Result<Rc<TextureArray>, String>
+    {
+        if !self.texture_arrays.contains_key(array_name)
+        {
+            let texture_array = TextureArray::new(&self.gl, filenames, flip)?;
+            self.texture_arrays.insert(String::from(array_name), Rc::new(texture_array));
+        }
+
+        Ok(Rc::clone(&self.texture_arrays[array_name]))
+    }
+
     pub fn get_program(self: &mut AssetLib, program_name: &str, vert_source: &str, frag_source: &str) -> Result<Rc<Program>, String>
     {
         if !self.programs.contains_key(program_name)
@@ -0,0 +1,453 @@
+
+use std::{ fs, collections::HashMap };
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockFace
+{
+    TOP,
+    BOTTOM,
+    SIDE,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockTextures
+{
+    pub top: String,
+    pub bottom: String,
+    pub side: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct BlockType
+{
+    pub name: String,
+    pub id: u16,
+    pub textures: BlockTextures,
+
+    /// Solid blocks can be targeted by the block raycast
+    pub solid: bool,
+
+    /// Transparent blocks don't hide the faces of the blocks next to them
+    pub transparent: bool,
+
+    /// Light emitted by the block, 0 - 15
+    pub light: u8,
+}
+
+/// Every block type the game knows about, loaded from a data file (see assets/blocks.cfg).
+/// Id 0 is always air. Ids that aren't in the registry are treated as solid, opaque
+/// blocks with no texture so chunks saved with removed block types still load.
+pub struct BlockRegistry
+{
+    blocks: HashMap<u16, BlockType>,
+    by_name: HashMap<String, u16>,
+
+    // Every texture used by a block, each one only once
+    textures: Vec<String>,
+
+    // Indexed by id for the mesher's inner loops
+    opaque: Vec<bool>,
+    texture_slots: Vec<[u32; 3]>,
+}
+
+impl BlockRegistry
+{
+    pub fn load(filename: &str) -> Result<BlockRegistry, String>
+    {
+        match fs::read_to_string(filename)
+        {
+            Ok(text) => match BlockRegistry::parse(&text)
+            {
+                Ok(registry) => Ok(registry),
+                Err(e) => Err(format!("Error Loading Blocks! File: {}, Error: {}", filename, e))
+            },
+            Err(e) => Err(format!("Error Loading Blocks! File: {}, Error: {:?}", filename, e))
+        }
+    }
+
+    /// Parses the block file format:
+    ///
+    ///     # comment
+    ///     [name]
+    ///     id = 1
+    ///     texture = path      (all faces, top/bottom/side override it)
+    ///     solid = true
+    ///     transparent = false
+    ///     light = 0
+    pub fn parse(text: &str) -> Result<BlockRegistry, String>
+    {
+        let mut blocks: Vec<BlockType> = vec![];
+        let mut current: Option<(String, HashMap<String, String>, usize)> = None;
+
+        for (i, line) in text.lines().enumerate()
+        {
+            let line_num = i + 1;
+            let line = match line.find('#')
+            {
+                Some(pos) => &line[..pos],
+                None => line
+            }.trim();
+
+            if line.is_empty()
+            {
+                continue;
+            }
+
+            if line.starts_with('[')
+            {
+                if !line.ends_with(']') || line.len() < 3
+                {
+                    return Err(format!("Line {}: Bad block name {}", line_num, line));
+                }
+
+                if let Some((name, values, start)) = current.take()
+                {
+                    blocks.push(BlockRegistry::make_block(name, &values, start)?);
+                }
+
+                current = Some((String::from(line[1..line.len() - 1].trim()), HashMap::new(), line_num));
+                continue;
+            }
+
+            let (key, value) = match line.find('=')
+            {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => return Err(format!("Line {}: Expected key = value, found {}", line_num, line))
+            };
+
+            match &mut current
+            {
+                Some((_, values, _)) =>
+                {
+                    if values.insert(String::from(key), String::from(value)).is_some()
+                    {
+                        return Err(format!("Line {}: {} is set twice", line_num, key));
+                    }
+                },
+                None => return Err(format!("Line {}: {} is not inside a [block]", line_num, key))
+            }
+        }
+
+        if let Some((name, values, start)) = current.take()
+        {
+            blocks.push(BlockRegistry::make_block(name, &values, start)?);
+        }
+
+        BlockRegistry::from_blocks(blocks)
+    }
+
+    fn make_block(name: String, values: &HashMap<String, String>, line_num: usize) -> Result<BlockType, String>
+    {
+        for key in values.keys()
+        {
+            match key.as_str()
+            {
+                "id" | "texture" | "top" | "bottom" | "side" | "solid" | "transparent" | "light" => (),
+                _ => return Err(format!("Block {} (line {}): Unknown key {}", name, line_num, key))
+            }
+        }
+
+        let id = match values.get("id").map(|v| v.parse::<u16>())
+        {
+            Some(Ok(0)) => return Err(format!("Block {} (line {}): Id 0 is air", name, line_num)),
+            Some(Ok(id)) => id,
+            Some(Err(_)) => return Err(format!("Block {} (line {}): Bad id {}", name, line_num, values["id"])),
+            None => return Err(format!("Block {} (line {}): Missing id", name, line_num))
+        };
+
+        let face_texture = |face: &str| -> Result<String, String>
+        {
+            match values.get(face).or_else(|| values.get("texture"))
+            {
+                Some(t) => Ok(t.clone()),
+                None => Err(format!("Block {} (line {}): No texture for the {} face", name, line_num, face))
+            }
+        };
+
+        let textures = BlockTextures { top: face_texture("top")?, bottom: face_texture("bottom")?, side: face_texture("side")? };
+
+        let flag = |key: &str, default: bool| -> Result<bool, String>
+        {
+            match values.get(key).map(|v| v.as_str())
+            {
+                Some("true") => Ok(true),
+                Some("false") => Ok(false),
+                Some(v) => Err(format!("Block {} (line {}): {} must be true or false, found {}", name, line_num, key, v)),
+                None => Ok(default)
+            }
+        };
+
+        let solid = flag("solid", true)?;
+        let transparent = flag("transparent", false)?;
+
+        let light = match values.get("light").map(|v| v.parse::<u8>())
+        {
+            Some(Ok(l)) if l <= 15 => l,
+            Some(_) => return Err(format!("Block {} (line {}): light must be 0 - 15", name, line_num)),
+            None => 0
+        };
+
+        Ok(BlockType { name, id, textures, solid, transparent, light })
+    }
+
+    pub fn from_blocks(block_list: Vec<BlockType>) -> Result<BlockRegistry, String>
+    {
+        let mut registry = BlockRegistry { blocks: HashMap::new(), by_name: HashMap::new(), textures: vec![],
+                                            opaque: vec![false], texture_slots: vec![[0; 3]] };
+
+        for block in block_list
+        {
+            if registry.blocks.contains_key(&block.id)
+            {
+                return Err(format!("Block {} uses id {} which is already taken", block.name, block.id));
+            }
+
+            if registry.by_name.contains_key(&block.name)
+            {
+                return Err(format!("Block {} is defined twice", block.name));
+            }
+
+            let id = block.id as usize;
+            if registry.opaque.len() <= id
+            {
+                registry.opaque.resize(id + 1, true);
+                registry.texture_slots.resize(id + 1, [0; 3]);
+            }
+
+            registry.opaque[id] = !block.transparent;
+            registry.texture_slots[id] = [registry.texture_slot(&block.textures.top),
+                                            registry.texture_slot(&block.textures.bottom),
+                                            registry.texture_slot(&block.textures.side)];
+
+            registry.by_name.insert(block.name.clone(), block.id);
+            registry.blocks.insert(block.id, block);
+        }
+
+        Ok(registry)
+    }
+
+    fn texture_slot(self: &mut BlockRegistry, texture: &str) -> u32
+    {
+        match self.textures.iter().position(|t| t == texture)
+        {
+            Some(slot) => slot as u32,
+            None =>
+            {
+                self.textures.push(String::from(texture));
+                (self.textures.len() - 1) as u32
+            }
+        }
+    }
+
+    pub fn get(self: &BlockRegistry, id: u16) -> Option<&BlockType>
+    {
+        self.blocks.get(&id)
+    }
+
+    pub fn id_of(self: &BlockRegistry, name: &str) -> Option<u16>
+    {
+        self.by_name.get(name).cloned()
+    }
+
+    /// Block ids in ascending order
+    pub fn ids(self: &BlockRegistry) -> Vec<u16>
+    {
+        let mut ids: Vec<u16> = self.blocks.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// The id after this one (wrapping around), for cycling through the blocks
+    pub fn next_id(self: &BlockRegistry, id: u16) -> u16
+    {
+        let ids = self.ids();
+        match ids.iter().find(|&&i| i > id)
+        {
+            Some(next) => *next,
+            None => ids.first().cloned().unwrap_or(0)
+        }
+    }
+
+    /// Every texture a block uses, the index is the texture slot passed to the shaders
+    pub fn textures(self: &BlockRegistry) -> &[String]
+    {
+        &self.textures
+    }
+
+    pub fn texture_index(self: &BlockRegistry, id: u16, face: BlockFace) -> u32
+    {
+        let slots = match self.texture_slots.get(id as usize)
+        {
+            Some(s) => s,
+            None => return 0
+        };
+
+        match face
+        {
+            BlockFace::TOP => slots[0],
+            BlockFace::BOTTOM => slots[1],
+            BlockFace::SIDE => slots[2],
+        }
+    }
+
+    /// Texture slots for the top, bottom and side faces
+    pub fn face_textures(self: &BlockRegistry, id: u16) -> [u32; 3]
+    {
+        match self.texture_slots.get(id as usize)
+        {
+            Some(s) => *s,
+            None => [0; 3]
+        }
+    }
+
+    pub fn is_opaque(self: &BlockRegistry, id: u16) -> bool
+    {
+        match self.opaque.get(id as usize)
+        {
+            Some(o) => *o,
+            None => true
+        }
+    }
+
+    pub fn is_solid(self: &BlockRegistry, id: u16) -> bool
+    {
+        if id == 0
+        {
+            return false;
+        }
+
+        match self.blocks.get(&id)
+        {
+            Some(block) => block.solid,
+            None => true
+        }
+    }
+
+    /// True if the face of block id that touches neighbor can't be seen. Transparent
+    /// blocks only hide faces of the same block type (so glass next to glass has no
+    /// faces in between).
+    pub fn hides_face(self: &BlockRegistry, id: u16, neighbor: u16) -> bool
+    {
+        neighbor != 0 && (neighbor == id || self.is_opaque(neighbor))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const TEST_BLOCKS: &str = "
+        # Test blocks
+        [stone]
+        id = 3
+        texture = stone.png
+
+        [grass]   # grass on top
+        id = 1
+        texture = dirt.png
+        top = grass.png
+
+        [glass]
+        id = 10
+        texture = glass.png
+        transparent = true
+        solid = true
+
+        [lamp]
+        id = 11
+        texture = stone.png
+        light = 15
+        solid = false
+    ";
+
+    #[test]
+    fn parses_blocks()
+    {
+        let blocks = BlockRegistry::parse(TEST_BLOCKS).unwrap();
+
+        assert_eq!(blocks.ids(), vec![1, 3, 10, 11]);
+        assert_eq!(blocks.id_of("grass"), Some(1));
+        assert_eq!(blocks.id_of("dirt"), None);
+
+        let grass = blocks.get(1).unwrap();
+        assert_eq!(grass.textures, BlockTextures { top: String::from("grass.png"), bottom: String::from("dirt.png"),
+                                                    side: String::from("dirt.png") });
+        assert_eq!(blocks.get(11).unwrap().light, 15);
+        assert!(!blocks.is_solid(11));
+        assert!(!blocks.is_solid(0));
+        assert!(blocks.is_solid(500));
+    }
+
+    #[test]
+    fn textures_are_shared()
+    {
+        let blocks = BlockRegistry::parse(TEST_BLOCKS).unwrap();
+
+        assert_eq!(blocks.textures().len(), 4);
+        assert_eq!(blocks.texture_index(3, BlockFace::SIDE), blocks.texture_index(11, BlockFace::TOP));
+        assert_ne!(blocks.texture_index(1, BlockFace::TOP), blocks.texture_index(1, BlockFace::SIDE));
+        assert_eq!(blocks.texture_index(1, BlockFace::BOTTOM), blocks.texture_index(1, BlockFace::SIDE));
+        assert_eq!(blocks.face_textures(1), [blocks.texture_index(1, BlockFace::TOP), blocks.texture_index(1, BlockFace::BOTTOM),
+                                                blocks.texture_index(1, BlockFace::SIDE)]);
+    }
+
+    #[test]
+    fn transparent_blocks_hide_only_their_own_faces()
+    {
+        let blocks = BlockRegistry::parse(TEST_BLOCKS).unwrap();
+
+        assert!(blocks.hides_face(1, 3));
+        assert!(!blocks.hides_face(1, 0));
+        assert!(!blocks.hides_face(1, 10));
+        assert!(blocks.hides_face(10, 10));
+        assert!(blocks.hides_face(10, 3));
+    }
+
+    #[test]
+    fn next_id_wraps()
+    {
+        let blocks = BlockRegistry::parse(TEST_BLOCKS).unwrap();
+
+        assert_eq!(blocks.next_id(1), 3);
+        assert_eq!(blocks.next_id(3), 10);
+        assert_eq!(blocks.next_id(11), 1);
+        assert_eq!(blocks.next_id(0), 1);
+    }
+
+    #[test]
+    fn rejects_bad_files()
+    {
+        let bad = [
+            "id = 1",
+            "[a]\ntexture = a.png",
+            "[a]\nid = 0\ntexture = a.png",
+            "[a]\nid = x\ntexture = a.png",
+            "[a]\nid = 1",
+            "[a]\nid = 1\ntexture = a.png\ncolour = red",
+            "[a]\nid = 1\ntexture = a.png\nsolid = yes",
+            "[a]\nid = 1\ntexture = a.png\nlight = 16",
+            "[a]\nid = 1\nid = 2\ntexture = a.png",
+            "[a]\nid = 1\ntexture = a.png\n[b]\nid = 1\ntexture = b.png",
+            "[a]\nid = 1\ntexture = a.png\n[a]\nid = 2\ntexture = b.png",
+            "[a\nid = 1",
+            "[a]\nid 1",
+        ];
+
+        for text in bad.iter()
+        {
+            assert!(BlockRegistry::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn loads_the_game_blocks()
+    {
+        let blocks = BlockRegistry::load("assets/blocks.cfg").unwrap();
+
+        assert!(blocks.id_of("grass").is_some());
+        assert!(blocks.id_of("dirt").is_some());
+        assert!(blocks.id_of("stone").is_some());
+    }
+}
@@ -6,67 +6,105 @@
 *	Description	-	This is the main demo scene
 ******************************************************************************/
 
-use std::rc::Rc;
-use rand::{ /* prelude::*, */ Rng, rngs::StdRng, SeedableRng};
 use glium_glyph::glyph_brush::{rusttype::Font, Section, rusttype::Scale};
 use glium_glyph::GlyphBrush;
-use crate::{ graphics::Gl, utils::mat4_to_array, GridPlane, AssetLib, Flip, graphics::WindowInfo,
-                WorldChunk, game::world_chunk::Voxel, game::world_chunk::Attr, game::GameData, game::game_data::NoiseType, utils::OlcNoise, utils::SimplexNoise };
+use crate::{ graphics::Gl, utils::mat4_to_array, GridPlane, graphics::BlockOutline, graphics::Crosshair, AssetLib, Flip, graphics::WindowInfo, graphics::CameraFPS, graphics::Frustum,
+                WorldChunk, game::World, game::ChunkCoord, game::world::ChunkBuffers, game::GameData, game::game_data::NoiseType, 
+                game::generators::GeneratorRegistry, game::RayHit, game::BlockRegistry, game::chunk_storage::SECTION_HEIGHT };
 
+/// How many chunks and sections the last frame drew or skipped
+#[derive(Clone, Copy, Default)]
+struct DrawStats
+{
+    chunks_drawn: u32,
+    chunks_culled: u32,
+    sections_drawn: u32,
+    sections_culled: u32,
+
+    // In view but hidden behind other sections
+    sections_occluded: u32,
+}
 
 pub struct ChunkDemoScene<'font, 'a>
 {
     gl: Gl,
     grid: GridPlane,
-    chunk: WorldChunk,
+    outline: BlockOutline,
+    section_outline: BlockOutline,
+    crosshair: Crosshair,
+    world: World,
+    camera_chunk: ChunkCoord,
+    camera_position: glm::Vec3,
+
+    // The block the camera is looking at, if it's within reach
+    target: Option<RayHit>,
+    draw_stats: DrawStats,
     perspective: glm::Mat4,
-    chunk_instance: Option<Rc<glium::VertexBuffer<Attr>>>,
-    force_chunk_regen: bool,
+
+    // Elapsed time (seconds) when animated noise was last moved forward
+    last_animation: f64,
     glyph_brush: GlyphBrush<'font, 'a>
 }
 
 impl<'font, 'a> ChunkDemoScene<'font, 'a>
 {
-    pub fn new(assets: &mut AssetLib, display: Gl, perspective: &glm::Mat4) 
+    pub fn new(assets: &mut AssetLib, display: Gl, perspective: &glm::Mat4, game_data: &GameData) 
         -> Result<ChunkDemoScene<'font, 'a>, String>
     {
+        let blocks = BlockRegistry::load("assets/blocks.cfg")?;
+
         // Pre Load assets
         assets.get_mesh("assets/Cube/BasicCube.obj")?;
-        assets.get_texture("assets/textures/Grass.png", Flip::NONE)?;
-        assets.get_texture("assets/textures/Dirt.png", Flip::NONE)?;
-        assets.get_texture("assets/textures/Stone.png", Flip::NONE)?;
+        assets.get_texture_array("Block_textures", blocks.textures(), Flip::NONE)?;
         assets.get_program("Blocks_instanced", "assets/shaders/block_instanced.vert", "assets/shaders/block.frag")?;
+        assets.get_program("Blocks_meshed", "assets/shaders/block_mesh.vert", "assets/shaders/block.frag")?;
 
         let mut grid = GridPlane::new(&display, [0.75, 0.75, 0.75], 10.0, 100, 100).unwrap();
         grid.projection = *perspective;
 
+        let mut outline = BlockOutline::new(&display, [0.05, 0.05, 0.05])?;
+        outline.projection = *perspective;
+
+        let mut section_outline = BlockOutline::new(&display, [0.9, 0.1, 0.1])?;
+        section_outline.projection = *perspective;
+
+        let crosshair = Crosshair::new(&display, [0.9, 0.9, 0.9], 10.0)?;
+
         let dejavu: &[u8] = include_bytes!("../../assets/fonts/open-sans/OpenSans-Bold.ttf");
         let fonts = vec![Font::from_bytes(dejavu).unwrap()];
 
         let glyph_brush = GlyphBrush::new(&(*display.inner), fonts);
 
-        Ok( ChunkDemoScene { gl: display.clone(), grid, chunk: WorldChunk::new(128, 32, 128), 
-                            perspective: *perspective, chunk_instance: None, force_chunk_regen: false, glyph_brush })
+        let world = World::new(&game_data.world, &game_data.chunk_generation, GeneratorRegistry::with_defaults(), blocks);
+
+        Ok( ChunkDemoScene { gl: display.clone(), grid, outline, section_outline, crosshair, world, camera_chunk: (0, 0), 
+                            camera_position: glm::vec3(0.0, 0.0, 0.0), target: None, draw_stats: DrawStats::default(),
+                            perspective: *perspective, last_animation: 0.0, glyph_brush })
+    }
+
+    pub fn get_world(self: &ChunkDemoScene<'font, 'a>) -> &World
+    {
+        &self.world
     }
 
-    pub fn get_chunk(self: &ChunkDemoScene<'font, 'a>) -> &WorldChunk
+    pub fn get_world_mut(self: &mut ChunkDemoScene<'font, 'a>) -> &mut World
     {
-        &self.chunk
+        &mut self.world
     }
 
-    pub fn make_chunk_single_layer(self: &mut ChunkDemoScene<'font, 'a>)
+    pub fn make_chunk_single_layer(chunk: &mut WorldChunk)
     {
-        self.chunk.layers[8].fill_with(1);
+        chunk.fill_layer(8, 1);
     }
 
-    pub fn make_chunk_single_layer_with_hole(self: &mut ChunkDemoScene<'font, 'a>)
+    pub fn make_chunk_single_layer_with_hole(chunk: &mut WorldChunk)
     {
-        self.chunk.layers[5].fill_with(1);
+        chunk.fill_layer(5, 1);
 
-        self.chunk.layers[5].layer[8][8].id = 0;
+        chunk.set(8, 5, 8, 0);
     }
 
-    pub fn make_test_one(self: &mut ChunkDemoScene<'font, 'a>)
+    pub fn make_test_one(chunk: &mut WorldChunk)
     {
         /*
             LAYERS: 16x16x16 = 4,096
@@ -77,299 +115,115 @@ impl<'font, 'a> ChunkDemoScene<'font, 'a>
 
         for i in 0..16
         {
-            self.chunk.layers[i].fill_with((i % 3 + 1) as u16);
+            chunk.fill_layer(i, (i % 3 + 1) as u16);
         }
 
-        self.chunk.layers[15].layer[7][7].id = 0;
+        chunk.set(7, 15, 7, 0);
     }
 
-    pub fn make_chunk_random2d(self: &mut ChunkDemoScene<'font, 'a>,  game_data: &GameData)
+    /// World space box around a chunk section. Blocks are centered on their
+    /// coordinates so the chunk starts half a block before its origin.
+    fn section_bounds(world: &World, coord: ChunkCoord, section: usize) -> (glm::Vec3, glm::Vec3)
     {
-        self.chunk.make_empty();
-        
-        let seed = match game_data.chunk_generation.seed
-        {
-            Some(s) => s,
-            None => {
-                [0; 32]
-            }
-        };
-
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let origin = world.chunk_origin(coord);
+        let bottom = (section * SECTION_HEIGHT) as f32 - 0.5;
+        let top = ((section + 1) * SECTION_HEIGHT).min(world.chunk_height) as f32 - 0.5;
 
-        for x in 0..self.chunk.width
-        {
-            for z in 0..self.chunk.depth
-            {
-                let height_scale = rng.gen::<f32>();
-                //println!("Noise sample at ({}, {}): {}", x, z, height_scale);
-                
-                let final_height = (1.0 + height_scale * ((self.chunk.height - 1) as f32)) as i32; 
-                //println!("height_scale: {} -- final_height: {}", height_scale, final_height);
-
-                // fill chunk column up to height
-                for i in 0..(final_height + 1)
-                {
-                    let value = match i
-                    {
-                        0..=7 => 3,
-                        8..=9 => 2,
-                        _ => 1
-                    };
-
-                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
-                    self.chunk.layers[i as usize].layer[x][z] = Voxel { id: value, visible: true };
-                }
-            }
-        }
-
-        // println!("\nNew chunk generated with Random 2D Noise:\nseed: {:?}", seed);
-        self.force_chunk_regen = true;
+        (glm::vec3(origin.0 as f32 - 0.5, bottom, origin.1 as f32 - 0.5),
+         glm::vec3((origin.0 + world.chunk_width as i32) as f32 - 0.5, top, (origin.1 + world.chunk_depth as i32) as f32 - 0.5))
     }
 
-    pub fn make_chunk_random3d(self: &mut ChunkDemoScene<'font, 'a>,  game_data: &GameData)
+    fn get_chunk_info_string(world: &World, camera_chunk: ChunkCoord, target: Option<RayHit>, draw_stats: &DrawStats, game_data: &GameData) -> String
     {
-        self.chunk.make_empty();
+        let (mut total_blocks, mut hidden_blocks, mut rendered_blocks, mut triangles, mut memory) = (0, 0, 0, 0, 0);
+        for (_, chunk) in world.iter()
+        {
+            total_blocks += chunk.total_blocks;
+            hidden_blocks += chunk.hidden_blocks;
+            rendered_blocks += chunk.rendered_blocks;
+            triangles += chunk.triangles;
+            memory += chunk.memory_usage();
+        }
 
-        let seed = match game_data.chunk_generation.seed
+        let camera_chunk_memory = match world.get_chunk(camera_chunk)
         {
-            Some(s) => s,
-            None => {
-                [0; 32]
-            }
+            Some(chunk) => chunk.memory_usage(),
+            None => 0
         };
 
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-        for y in 0..self.chunk.height
+        let mut info = String::from("Chunk Info:\n");
+        info += &format!("\nDimensions: ({}, {}, {})", world.chunk_width, world.chunk_height, world.chunk_depth);
+        info += &format!("\nCamera Chunk: ({}, {})\nLoaded Chunks: {}\nPending Chunks: {}", 
+                                        camera_chunk.0, camera_chunk.1, world.num_loaded(), world.num_pending());
+        info += &format!("\nTotal Blocks: {}\nHidden Blocks: {}\nRendered Blocks: {}", total_blocks, hidden_blocks, rendered_blocks);
+        info += &format!("\n\nVoxel Memory: {} KB\nPer Chunk: {} KB\nCamera Chunk: {} KB", memory / 1024, 
+                                        memory / world.num_loaded().max(1) / 1024, camera_chunk_memory / 1024);
+        info += &format!("\n\nMesh Mode: {:?}\nQuads: {}\nTriangles: {}", world.get_mesh_mode(), triangles / 2, triangles);
+        info += &format!("\nChunks Drawn/Culled: {}/{}\nSections Drawn/Culled: {}/{}", draw_stats.chunks_drawn, 
+                                        draw_stats.chunks_culled, draw_stats.sections_drawn, draw_stats.sections_culled);
+        info += &format!("\nSections Occluded: {}{}", draw_stats.sections_occluded, 
+                                        if game_data.debug.occlusion_culling { "" } else { " (Off)" });
+        info += &format!("\nChunks Per LOD: {:?}", world.lod_counts());
+        let block_name = |id: u16| match world.blocks().get(id)
         {
-            for x in 0..self.chunk.width
-            {
-                for z in 0..self.chunk.depth
-                {
-                    let noise_value = rng.gen::<f32>();
-
-                    let mut v = Voxel { id: 0, visible: true };
-
-                    if noise_value >= game_data.chunk_generation.threshold
-                    {
-                        v.id = match y
-                        {
-                            0..=7 => 3,
-                            8..=9 => 2,
-                            _ => 1
-                        };
-                    }
-
-                    self.chunk.layers[y as usize].layer[x][z] = v; 
-                }
-            }
-        }
-
-        // println!("\nNew chunk generated with Random 2D Noise:\nseed: {:?}\nthreshold: {}", seed, threshold);
-        self.force_chunk_regen = true;
-    }
-
-    #[allow(non_snake_case)]
-    pub fn make_noise2D_test(self: &mut ChunkDemoScene<'font, 'a>,  game_data: &GameData)
-    {
-        //println!("Generating chunk from 2D noise");
-        //println!("Octaves: {}, Bias: {}", octaves, bias);
-        // Sample noise to generate a random chunk
-        // Limit height by requiring larger sample values for higher blocks
-
-        self.chunk.make_empty();
-
-        // The noise generator
-        //let noise_machine = OlcNoise::new(self.chunk.width as i32, self.chunk.depth as i32, seed);
-        let noise_machine = OlcNoise::new(32 as i32, 32 as i32, game_data.chunk_generation.seed);
+            Some(block) => block.name.clone(),
+            None => String::from(if id == 0 { "air" } else { "unknown" })
+        };
 
-        // Only testing 2D noise to start
-        // In this test the chunk will be solid (no caves)
-        // but will have variable height
-        
-        for x in 0..self.chunk.width
+        info += &format!("\n\nSelected Block: {}", block_name(game_data.edit.selected_block));
+        info += &match target
         {
-            for z in 0..self.chunk.depth
+            Some(hit) => 
             {
-                // olc noise does not use x and y between 0 and 1
-               // let fx: f32 = (x as f32) / (self.chunk.width as f32);
-                //let fz: f32 = (z as f32) / (self.chunk.depth as f32);
-                let height_scale = noise_machine.sample2D(x as i32, z as i32, game_data.chunk_generation.octaves, game_data.chunk_generation.bias);
-                //println!("Noise sample at ({}, {}): {}", x, z, height_scale);
-                
-                // use height_scale to lerp between 1 and the chunk height
-                // a + x * (b - a)
-                // Obviously can be simplified
-                let final_height = (1.0 + height_scale * ((self.chunk.height - 1) as f32)) as i32; 
-                //println!("height_scale: {} -- final_height: {}", height_scale, final_height);
-
-                // fill chunk column up to height
-                for i in 0..(final_height + 1)
-                {
-                    let value = match i
-                    {
-                        0..=7 => 3,
-                        8..=9 => 2,
-                        _ => 1
-                    };
-
-                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
-                    self.chunk.layers[i as usize].layer[x][z] = Voxel { id: value, visible: true };
-                }
-            }
-        }
-
-        // println!("\nNew chunk generated with OLC Noise:\nseed: {:?}\nnum octaves: {}, bias: {}", seed, octaves, bias);
-        self.force_chunk_regen = true;
-    }
+                let id = world.get_block(hit.block.0, hit.block.1, hit.block.2);
+                format!("\nTarget: ({}, {}, {}) {} ({})", hit.block.0, hit.block.1, hit.block.2, block_name(id), id)
+            },
+            None => String::from("\nTarget: None")
+        };
+        info += &format!("\n\nNoise Type: {:?}", game_data.chunk_generation.noise_type);
+        info += &format!("\n\nSeed: {:?}\n", game_data.chunk_generation.seed);
 
-    #[allow(non_snake_case)]
-    pub fn make_simplex_noise2D(self: &mut ChunkDemoScene<'font, 'a>,  game_data: &GameData)
-    {
-        self.chunk.make_empty();
+        info += &match game_data.chunk_generation.noise_type
+        {
+            NoiseType::RANDOM_2D => String::from(""),
+            NoiseType::RANDOM_3D => format!("\nThreshold: {}", game_data.chunk_generation.threshold),
+            NoiseType::OLC => format!("\nOctaves: {}\nBias: {}", game_data.chunk_generation.octaves, game_data.chunk_generation.bias),
+            
+            NoiseType::SIMPLEX_2D => format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}", game_data.chunk_generation.offset.0,
+                                                             game_data.chunk_generation.offset.1, game_data.chunk_generation.zoom_factor, 
+                                                             game_data.chunk_generation.sx_scale),
 
-        // The noise generator
-        let noise_machine = SimplexNoise::new(game_data.chunk_generation.seed);
+            NoiseType::SIMPLEX_3D => format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}\nThreshold: {}\nThreshold Falloff: {}", 
+                                                            game_data.chunk_generation.offset.0, game_data.chunk_generation.offset.1,
+                                                            game_data.chunk_generation.zoom_factor, game_data.chunk_generation.sx_scale,  
+                                                            game_data.chunk_generation.threshold, game_data.chunk_generation.threshold_falloff),
 
-        // Only testing 2D noise to start
-        // In this test the chunk will be solid (no caves)
-        // but will have variable height
-        for x in 0..self.chunk.width
-        {
-            for z in 0..self.chunk.depth
+            NoiseType::FRACTAL_2D | NoiseType::FRACTAL_3D | NoiseType::WARPED_2D | NoiseType::WARPED_3D =>
             {
-                // Zoom into the noise by scaling down the x and z
-                // (or if zoom_factor is large than 1 it will scale up - resulting in chaotic noise)
-                let xf = (x as f32 + game_data.chunk_generation.offset.0) * game_data.chunk_generation.zoom_factor;
-                let zf = (z as f32 + game_data.chunk_generation.offset.1) * game_data.chunk_generation.zoom_factor;
-
-                let height_scale = noise_machine.noise_2D(xf, zf, game_data.chunk_generation.sx_scale);
-                
-                
-                // Result of the noise is between -1 and 1. Need to scale it to be between
-                // 0 and 1:
-                // NewValue = (((OldValue - OldMin) * (NewMax - NewMin)) / (OldMax - OldMin)) + NewMin
-
-                //  or split into 3 lines:
-                // OldRange = (OldMax - OldMin)  
-                // NewRange = (NewMax - NewMin)  
-                // NewValue = (((OldValue - OldMin) * NewRange) / OldRange) + NewMin
-                let old_range = 2.0;
-                let new_range = 1.0;
-                let height_scale = ((height_scale + 1.0) * new_range) / old_range;
-                let mut final_height = (1.0 + height_scale * ((self.chunk.height - 1) as f32)) as i32;
-
-                if final_height >= 32
+                let generation = &game_data.chunk_generation;
+                let mut fractal = format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}\nBase Noise: {:?}\nFractal Mode: {:?}\nOctaves: {}\nLacunarity: {}\nGain: {}",
+                                            generation.offset.0, generation.offset.1, generation.zoom_factor, generation.sx_scale,
+                                            generation.base_noise, generation.fractal_mode, generation.octaves, generation.lacunarity, generation.gain);
+                if generation.noise_type == NoiseType::WARPED_2D || generation.noise_type == NoiseType::WARPED_3D
                 {
-                    // println!("height_scale: {} -- final_height: {}", height_scale, final_height);
-                    final_height = 31;
+                    fractal += &format!("\nWarp Strength: {}\nWarp Frequency: {}\nDouble Warp: {}", generation.warp_strength,
+                                        generation.warp_frequency, generation.double_warp);
                 }
 
-                // fill chunk column up to height
-                for i in 0..(final_height + 1)
-                {
-                    let value = match i
-                    {
-                        0..=7 => 3,
-                        8..=9 => 2,
-                        _ => 1
-                    };
-
-                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
-                    self.chunk.layers[i as usize].layer[x][z] = Voxel { id: value, visible: true };
-                }
-            }
-        }
-        // println!("\nNew chunk generated with Simplex Noise:\nSeed: {:?}\nZoom Factor: {}", seed, zoom_factor);
-        self.force_chunk_regen = true;
-    }
-
-    #[allow(non_snake_case)]
-    pub fn make_simplex_noise3D(self: &mut ChunkDemoScene<'font, 'a>, game_data: &GameData)
-    {
-        self.chunk.make_empty();
-
-        // The noise generator
-        let noise_machine = SimplexNoise::new(game_data.chunk_generation.seed);
-
-        // Only testing 2D noise to start
-        // In this test the chunk will be solid (no caves)
-        // but will have variable height
-        for y in 0..self.chunk.height
-        {
-            for x in 0..self.chunk.width
-            {
-                for z in 0..self.chunk.depth
+                if generation.noise_type == NoiseType::FRACTAL_3D || generation.noise_type == NoiseType::WARPED_3D
                 {
-                    // Zoom into the noise by scaling down the x and z
-                    // (or if zoom_factor is large than 1 it will scale up - resulting in chaotic noise)
-                    let xf = (x as f32 + game_data.chunk_generation.offset.0) * game_data.chunk_generation.zoom_factor;
-                    let yf = y as f32 * game_data.chunk_generation.zoom_factor;
-                    let zf = (z as f32 + game_data.chunk_generation.offset.1) * game_data.chunk_generation.zoom_factor;
-
-                    let noise_value = noise_machine.noise_3D(xf, yf, zf, game_data.chunk_generation.sx_scale);
-                    
-                    
-                    // Result of the noise is between -1 and 1. Need to scale it to be between
-                    // 0 and 1:
-                    let old_range = 2.0;
-                    let new_range = 1.0;
-                    let noise_value = ((noise_value + 1.0) * new_range) / old_range;
-
-                    // use noise_value to decide on the block type
-                    // threshold will decide if the block should be created or not
-                    
-                    // Function to increase threshold as height increases
-                    // final = threshold + ((y^2) / 100) / falloff
-                    let sqy = (y*y) as i32;
-                    let sqy = sqy / 100;
-                    let final_threshold = game_data.chunk_generation.threshold + ((sqy as f32)/game_data.chunk_generation.threshold_falloff as f32);
-
-                    let mut v = Voxel { id: 0, visible: true };
-
-                    if noise_value >= final_threshold
-                    {
-                        v.id = match y
-                        {
-                            0..=7 => 3,
-                            8..=9 => 2,
-                            _ => 1
-                        };
-                    }
-
-                    self.chunk.layers[y as usize].layer[x][z] = v; 
-                    
+                    fractal += &format!("\nThreshold: {}\nThreshold Falloff: {}", generation.threshold, generation.threshold_falloff);
                 }
-            }
-        }
-        
-        // println!("\nNew chunk generated with Simplex Noise:\nSeed: {:?}\nZoom Factor: {}\nThreshold: {}\nThreshold Falloff: {}", 
-        //             seed, zoom_factor, threshold, threshold_falloff);
-        self.force_chunk_regen = true;
-    }
-
-    fn get_chunk_info_string(chunk: &WorldChunk, game_data: &GameData) -> String
-    {
-        let mut info = String::from("Chunk Info:\n");
-        info += &String::from(format!("\nDimensions: ({}, {}, {})", chunk.width, chunk.height, chunk.depth));
-        info += &String::from(format!("\nTotal Blocks: {}\nHidden Blocks: {}\nRendered Blocks: {}", chunk.total_blocks, chunk.hidden_blocks, chunk.rendered_blocks));
-        info += &String::from(format!("\n\nNoise Type: {:?}", game_data.chunk_generation.noise_type));
-        info += &String::from(format!("\n\nSeed: {:?}\n", game_data.chunk_generation.seed));
+                fractal
+            },
 
-        info += &match game_data.chunk_generation.noise_type
-        {
-            NoiseType::RANDOM_2D => String::from(""),
-            NoiseType::RANDOM_3D => String::from(format!("\nThreshold: {}", game_data.chunk_generation.threshold)),
-            NoiseType::OLC => String::from(format!("\nOctaves: {}\nBias: {}", game_data.chunk_generation.octaves, game_data.chunk_generation.bias)),
-            
-            NoiseType::SIMPLEX_2D => String::from(format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}", game_data.chunk_generation.offset.0,
-                                                             game_data.chunk_generation.offset.1, game_data.chunk_generation.zoom_factor, 
-                                                             game_data.chunk_generation.sx_scale)),
+            NoiseType::ANIMATED_3D => format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}\nThreshold: {}\nThreshold Falloff: {}\nTime: {:.2}",
+                                                game_data.chunk_generation.offset.0, game_data.chunk_generation.offset.1,
+                                                game_data.chunk_generation.zoom_factor, game_data.chunk_generation.sx_scale,
+                                                game_data.chunk_generation.threshold, game_data.chunk_generation.threshold_falloff,
+                                                game_data.chunk_generation.time),
 
-            NoiseType::SIMPLEX_3D => String::from(format!("\nOffsets: ({}, {})\nZoom Factor: {}\nScale Factor: {}\nThreshold: {}\nThreshold Falloff: {}", 
-                                                            game_data.chunk_generation.offset.0, game_data.chunk_generation.offset.1,
-                                                            game_data.chunk_generation.zoom_factor, game_data.chunk_generation.sx_scale,  
-                                                            game_data.chunk_generation.threshold, game_data.chunk_generation.threshold_falloff)),
+            NoiseType::CUSTOM(_) => String::from(""),
         };
 
         info
@@ -378,7 +232,9 @@ impl<'font, 'a> ChunkDemoScene<'font, 'a>
     fn get_scene_controls_string(game_data: &GameData) -> String
     {
         let mut controls_string = String::from("Demo Controls:\n\nF1: Show/Hide this message\nF2: Show/Hide Chunk Info");
-        controls_string += "\n\nWASD: Move\nE/Q: Move Up/Down\nMouse Move: Look\n\n1, 2, 3, 4, 5: Change Noise Type";
+        controls_string += "\nF3: Change Mesh Mode\nF4: Toggle Occlusion Culling\nF5: Show Occluded Sections";
+        controls_string += "\n\nLeft Click: Break Block\nRight Click: Place Block\nB: Change Selected Block";
+        controls_string += "\n\nWASD: Move\nE/Q: Move Up/Down\nMouse Move: Look\n\n0 - 9: Change Noise Type";
         controls_string += "\nV: Use Default Seed\nC: Use New Random Seed\n\nSHIFT: Move and Adjust Faster";
         controls_string += "\nLeft Arrow, Right Arrow: Adjust Noise X Offset\nUp Arrow, Down Arrow: Adjust Noise Z Offset";
       //  \n\tR: Adjust Bias/Zoom Factor Up\n\tF: Adjust Bias/Zoom Factor Down\n\tT: Adjust Threshold Up\n\tG: Adjust Threshold Down
@@ -391,56 +247,77 @@ impl<'font, 'a> ChunkDemoScene<'font, 'a>
             NoiseType::OLC => "\nR/F: Adjust Bias Up/Down\nSPACE: Increase Octave",
             NoiseType::SIMPLEX_2D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down",
             NoiseType::SIMPLEX_3D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nT/G: Adjust Threshold Up/Down\nY/H: Adjust Threshold Falloff Up/Down",
+            NoiseType::FRACTAL_2D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nF7: Change Base Noise\nM: Change Fractal Mode\nSPACE: Increase Octave\nI/K: Adjust Lacunarity Up/Down\nO/L: Adjust Gain Up/Down",
+            NoiseType::FRACTAL_3D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nF7: Change Base Noise\nM: Change Fractal Mode\nSPACE: Increase Octave\nI/K: Adjust Lacunarity Up/Down\nO/L: Adjust Gain Up/Down\nT/G: Adjust Threshold Up/Down\nY/H: Adjust Threshold Falloff Up/Down",
+            NoiseType::WARPED_2D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nF7: Change Base Noise\nM: Change Fractal Mode\nSPACE: Increase Octave\nI/K: Adjust Lacunarity Up/Down\nO/L: Adjust Gain Up/Down\nU/J: Adjust Warp Strength Up/Down\nP/N: Adjust Warp Frequency Up/Down\nF6: Toggle Double Warp",
+            NoiseType::WARPED_3D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nF7: Change Base Noise\nM: Change Fractal Mode\nSPACE: Increase Octave\nI/K: Adjust Lacunarity Up/Down\nO/L: Adjust Gain Up/Down\nU/J: Adjust Warp Strength Up/Down\nP/N: Adjust Warp Frequency Up/Down\nF6: Toggle Double Warp\nT/G: Adjust Threshold Up/Down\nY/H: Adjust Threshold Falloff Up/Down",
+            NoiseType::ANIMATED_3D => "\nR/F: Adjust Zoom Factor Up/Down\nZ/X: Adjust Scale Up/Down\nT/G: Adjust Threshold Up/Down\nY/H: Adjust Threshold Falloff Up/Down",
+            NoiseType::CUSTOM(_) => "",
         };
 
         controls_string
     }
 
-    pub fn update(self: &mut ChunkDemoScene<'font, 'a>, game_data: &mut GameData, _delta_time: f64)
+    /// elapsed_time is the program run time in seconds, it drives ANIMATED_3D noise
+    pub fn update(self: &mut ChunkDemoScene<'font, 'a>, game_data: &mut GameData, camera: &CameraFPS, _delta_time: f64, elapsed_time: f64)
     {
+        // Move animated noise forward at most animation_rate times a second, and only once the
+        // chunks from the last step are done so the workers never fall behind
+        if game_data.chunk_generation.noise_type == NoiseType::ANIMATED_3D
+            && elapsed_time - self.last_animation >= 1.0 / game_data.world.animation_rate as f64
+            && self.world.num_pending() == 0
+        {
+            game_data.chunk_generation.time = (elapsed_time * game_data.world.animation_speed as f64) as f32;
+            game_data.debug.remake_test_scene = true;
+            self.last_animation = elapsed_time;
+        }
+
         if game_data.debug.remake_test_scene
         {
-            match game_data.chunk_generation.noise_type
-            {
-                NoiseType::RANDOM_2D =>
-                    self.make_chunk_random2d(game_data),
+            self.world.regenerate_all(&game_data.chunk_generation);
+            game_data.debug.remake_test_scene = false;
+        }
 
-                NoiseType::RANDOM_3D =>
-                    self.make_chunk_random3d(game_data),
+        self.world.set_mesh_mode(game_data.world.mesh_mode);
 
-                NoiseType::OLC => 
-                    self.make_noise2D_test(game_data),
-                
-                NoiseType::SIMPLEX_2D => 
-                    self.make_simplex_noise2D(game_data),
+        if game_data.edit.next_block
+        {
+            game_data.edit.selected_block = self.world.blocks().next_id(game_data.edit.selected_block);
+            game_data.edit.next_block = false;
+        }
 
-                NoiseType::SIMPLEX_3D =>
-                    self.make_simplex_noise3D(game_data),
-            };
-            
-            game_data.debug.remake_test_scene = false;
+        self.target = self.world.raycast(&camera.get_position(), &camera.get_front(), game_data.edit.reach);
+        if let Some(hit) = self.target
+        {
+            if game_data.edit.break_block
+            {
+                self.world.set_block(hit.block.0, hit.block.1, hit.block.2, 0);
+            }
+            else if game_data.edit.place_block && hit.face != (0, 0, 0)
+            {
+                let (x, y, z) = hit.adjacent();
+                self.world.set_block(x, y, z, game_data.edit.selected_block);
+            }
         }
 
-        // The instance buffer must be created before drawing begins
+        game_data.edit.break_block = false;
+        game_data.edit.place_block = false;
+
+        // The instance buffers must be uploaded before drawing begins
         // so this cannot happen in render_scene()
-        self.chunk_instance = Some(self.chunk.get_instance_buffer(&self.gl, self.force_chunk_regen));
-        self.force_chunk_regen = false;
+        self.camera_chunk = self.world.chunk_coord_at(&camera.get_position());
+        self.camera_position = camera.get_position();
+        self.world.update(&self.gl, &camera.get_position());
     }
 
     pub fn render_scene(self: &mut ChunkDemoScene<'font, 'a>, assets: &mut AssetLib, game_data: &GameData, window_info: &WindowInfo,
                             display: &glium::Display, target: &mut glium::Frame, view: &glm::Mat4)
     {
-        if self.chunk.instance_buff.is_none()
-        {
-            return;
-        }
-
         use glium::Surface;
         let block_mesh = assets.get_mesh("assets/Cube/BasicCube.obj").unwrap();
-        let grass_tex = assets.get_texture("assets/textures/Grass.png", Flip::NONE).unwrap();
-        let dirt_tex = assets.get_texture("assets/textures/Dirt.png", Flip::NONE).unwrap();
-        let stone_tex = assets.get_texture("assets/textures/Stone.png", Flip::NONE).unwrap();
+        let block_textures = assets.get_texture_array("Block_textures", self.world.blocks().textures(), Flip::NONE).unwrap();
         let program = assets.get_program("Blocks_instanced", "assets/shaders/block_instanced.vert", "assets/shaders/block.frag").unwrap();
+        let mesh_program = assets.get_program("Blocks_meshed", "assets/shaders/block_mesh.vert", "assets/shaders/block.frag").unwrap();
 
         
         let params = glium::DrawParameters {
@@ -456,29 +333,115 @@ impl<'font, 'a> ChunkDemoScene<'font, 'a>
         self.grid.view = *view;
         self.grid.draw(target);
 
-        // uniforms
         let light = [-1.0, 0.4, 0.9f32];
-        let uniforms = &uniform! 
-        { 
-            model: mat4_to_array(&glm::Mat4::identity()), 
-            view: mat4_to_array(view),
-            perspective: mat4_to_array(&self.perspective),
-            u_light: light,
-            tex1: grass_tex.get_texture(),
-            tex2: dirt_tex.get_texture(),
-            tex3: stone_tex.get_texture()
+        let frustum = Frustum::from_matrix(&(self.perspective * view));
+        let world = &self.world;
+        let section_in_view = |(coord, s): (ChunkCoord, usize)|
+        {
+            let (min, max) = ChunkDemoScene::section_bounds(world, coord, s);
+            frustum.intersects_aabb(&min, &max)
         };
 
-        let instance_buff = match &self.chunk_instance
+        let visible = match game_data.debug.occlusion_culling
         {
-            Some(ci) => ci,
-            None => panic!("ERROR MISSING CHUNK INSTANCE BUFFER")
+            true => self.world.visible_sections(&self.camera_position, section_in_view),
+            false => None
         };
 
-        // Draw chunk
-        target.draw((&block_mesh.vb, instance_buff.per_instance().unwrap()),
-                    &block_mesh.indices, &program.program, uniforms,
-                    &params).unwrap();
+        let mut occluded = vec![];
+        let mut stats = DrawStats::default();
+        for (coord, buffers) in self.world.chunk_buffers()
+        {
+            // Each chunk's geometry is local to the chunk so
+            // the model matrix moves it into place
+            let origin = self.world.chunk_origin(*coord);
+
+            let (min, _) = ChunkDemoScene::section_bounds(&self.world, *coord, 0);
+            let max = min + glm::vec3(self.world.chunk_width as f32, self.world.chunk_height as f32, self.world.chunk_depth as f32);
+            let sections = match buffers
+            {
+                ChunkBuffers::Instanced(_, sections) | ChunkBuffers::Mesh(_, _, sections) => sections
+            };
+
+            if !frustum.intersects_aabb(&min, &max)
+            {
+                stats.chunks_culled += 1;
+                stats.sections_culled += sections.iter().filter(|r| !r.is_empty()).count() as u32;
+                continue;
+            }
+
+            stats.chunks_drawn += 1;
+            let model = glm::translate(&glm::Mat4::identity(), &glm::vec3(origin.0 as f32, 0.0, origin.1 as f32));
+
+            // uniforms
+            let uniforms = &uniform! 
+            { 
+                model: mat4_to_array(&model), 
+                view: mat4_to_array(view),
+                perspective: mat4_to_array(&self.perspective),
+                u_light: light,
+                block_textures: block_textures.sampled()
+            };
+
+            // Draw each section that's in view
+            for (s, range) in sections.iter().enumerate()
+            {
+                if range.is_empty()
+                {
+                    continue;
+                }
+
+                let (section_min, section_max) = ChunkDemoScene::section_bounds(&self.world, *coord, s);
+                if !frustum.intersects_aabb(&section_min, &section_max)
+                {
+                    stats.sections_culled += 1;
+                    continue;
+                }
+
+                if let Some(visible) = &visible
+                {
+                    if !visible.contains(&(*coord, s))
+                    {
+                        stats.sections_occluded += 1;
+                        occluded.push((section_min, section_max));
+                        continue;
+                    }
+                }
+
+                stats.sections_drawn += 1;
+                match buffers
+                {
+                    ChunkBuffers::Instanced(instance_buff, _) =>
+                        target.draw((&block_mesh.vb, instance_buff.slice(range.clone()).unwrap().per_instance().unwrap()),
+                                    &block_mesh.indices, &program.program, uniforms,
+                                    &params).unwrap(),
+
+                    ChunkBuffers::Mesh(vb, indices, _) =>
+                        target.draw(vb, indices.slice(range.clone()).unwrap(), &mesh_program.program, uniforms, &params).unwrap(),
+                };
+            }
+        }
+
+        self.draw_stats = stats;
+
+        if game_data.debug.show_occluded_sections
+        {
+            self.section_outline.view = *view;
+            for (min, max) in occluded.iter()
+            {
+                self.section_outline.draw_box(target, min, max);
+            }
+        }
+
+        // Highlight the block being looked at
+        if let Some(hit) = self.target
+        {
+            self.outline.view = *view;
+            self.outline.draw(target, &glm::vec3(hit.block.0 as f32, hit.block.1 as f32, hit.block.2 as f32));
+        }
+
+        // Overlay
+        self.crosshair.draw(target, window_info);
 
         // On screen text info
         let test_scale = 18.0;
@@ -496,7 +459,7 @@ impl<'font, 'a> ChunkDemoScene<'font, 'a>
         if game_data.debug.print_chunk_info
         {
             self.glyph_brush.queue(Section {
-                text: &ChunkDemoScene::get_chunk_info_string(self.get_chunk(), &game_data),
+                text: &ChunkDemoScene::get_chunk_info_string(&self.world, self.camera_chunk, self.target, &self.draw_stats, game_data),
                 scale: Scale { x: test_scale, y: test_scale },
                 screen_position: (window_info.size.width as f32 / 2.0 + 200.0, 0.0),
                 bounds: (250.0, window_info.size.height as f32 / 2.0),
@@ -0,0 +1,548 @@
+
+use std::ops::Range;
+use crate::game::{ WorldChunk, ChunkNeighbors, BlockRegistry, world_chunk::Attr, block_registry::BlockFace, chunk_storage::SECTION_HEIGHT };
+
+/// Vertex for chunk meshes. Unlike the instanced path every vertex
+/// carries its own texture slot since one buffer holds the whole chunk.
+#[derive(Copy, Clone, Debug)]
+pub struct ChunkVertex
+{
+    pub position: [f32; 3],
+    pub tex_coord: [f32; 2],
+    pub normal: [f32; 3],
+    pub texture: u32,
+}
+
+implement_vertex!(ChunkVertex, position, tex_coord, normal, texture);
+
+/// CPU side geometry for a whole chunk, ready to be uploaded
+pub struct ChunkMeshData
+{
+    pub vertices: Vec<ChunkVertex>,
+    pub indices: Vec<u32>,
+
+    /// Range of indices for each SECTION_HEIGHT high section of the chunk, from the
+    /// bottom up, so sections outside the view can be skipped when drawing
+    pub sections: Vec<Range<usize>>,
+}
+
+impl ChunkMeshData
+{
+    pub fn new() -> ChunkMeshData
+    {
+        ChunkMeshData { vertices: vec![], indices: vec![], sections: vec![] }
+    }
+
+    pub fn num_triangles(self: &ChunkMeshData) -> u32
+    {
+        (self.indices.len() / 3) as u32
+    }
+
+    /// Adds a quad. Corners must be counter-clockwise when looking at the front of the face.
+    pub fn push_quad(self: &mut ChunkMeshData, corners: [[f32; 3]; 4], tex_coords: [[f32; 2]; 4], normal: [f32; 3], texture: u32)
+    {
+        let base = self.vertices.len() as u32;
+        for i in 0..4
+        {
+            self.vertices.push(ChunkVertex { position: corners[i], tex_coord: tex_coords[i], normal, texture });
+        }
+
+        self.indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    /// Reorders the quads so each section's quads are next to each other and fills
+    /// in sections. Quads must not cross a section boundary.
+    pub fn sort_into_sections(self: &mut ChunkMeshData, chunk_height: usize)
+    {
+        // A quad's section is the section of the lowest block it covers. Blocks are
+        // centered on integer y so the bottom of block y is at y - 0.5.
+        let section_of = |quad: &[ChunkVertex]| 
+        {
+            let min_y = quad.iter().map(|v| v.position[1]).fold(f32::INFINITY, f32::min);
+            let block_y = if quad[0].normal[1] > 0.5 { min_y - 0.5 } else { min_y + 0.5 };
+            (block_y.round() as usize) / SECTION_HEIGHT
+        };
+
+        let mut quads: Vec<&[ChunkVertex]> = self.vertices.chunks(4).collect();
+        quads.sort_by_key(|quad| section_of(quad));
+
+        let mut sorted = ChunkMeshData::new();
+        let quad_sections: Vec<usize> = quads.iter().map(|quad| section_of(quad)).collect();
+        for quad in quads
+        {
+            sorted.push_quad([quad[0].position, quad[1].position, quad[2].position, quad[3].position],
+                            [quad[0].tex_coord, quad[1].tex_coord, quad[2].tex_coord, quad[3].tex_coord],
+                            quad[0].normal, quad[0].texture);
+        }
+
+        // 6 indices per quad
+        sorted.sections = section_ranges(&quad_sections, num_sections(chunk_height)).into_iter()
+            .map(|r| r.start * 6..r.end * 6)
+            .collect();
+
+        *self = sorted;
+    }
+}
+
+impl Default for ChunkMeshData
+{
+    fn default() -> ChunkMeshData
+    {
+        ChunkMeshData::new()
+    }
+}
+
+/// Number of SECTION_HEIGHT high sections in a chunk
+pub fn num_sections(chunk_height: usize) -> usize
+{
+    chunk_height.div_ceil(SECTION_HEIGHT)
+}
+
+/// Turns the (sorted) section of each item into the range of items in each section
+pub fn section_ranges(item_sections: &[usize], num_sections: usize) -> Vec<Range<usize>>
+{
+    let mut ranges = vec![];
+    let mut start = 0;
+    for section in 0..num_sections
+    {
+        let mut end = start;
+        while end < item_sections.len() && item_sections[end] == section
+        {
+            end += 1;
+        }
+
+        ranges.push(start..end);
+        start = end;
+    }
+
+    ranges
+}
+
+/// What the chunk workers hand back for drawing
+pub enum ChunkGeometry
+{
+    /// One BasicCube.obj instance per visible block, and the range
+    /// of instances in each section
+    Instances(Vec<Attr>, Vec<Range<usize>>),
+
+    /// A single mesh with only the exposed faces
+    Mesh(ChunkMeshData),
+}
+
+/// One of the six cube faces
+pub struct Face
+{
+    /// Offset to the neighbouring block this face touches
+    pub dir: (i32, i32, i32),
+    pub normal: [f32; 3],
+
+    /// Corners relative to the block center (same as BasicCube.obj, which
+    /// is a unit cube centered on the origin), counter-clockwise from the
+    /// bottom left when looking at the face from outside.
+    pub corners: [[f32; 3]; 4],
+
+    /// Which of the block's textures the face uses
+    pub block_face: BlockFace,
+}
+
+pub const FACES: [Face; 6] = [
+    // +X
+    Face { dir: (1, 0, 0), normal: [1.0, 0.0, 0.0],
+            corners: [[0.5, -0.5, 0.5], [0.5, -0.5, -0.5], [0.5, 0.5, -0.5], [0.5, 0.5, 0.5]],
+            block_face: BlockFace::SIDE },
+    // -X
+    Face { dir: (-1, 0, 0), normal: [-1.0, 0.0, 0.0],
+            corners: [[-0.5, -0.5, -0.5], [-0.5, -0.5, 0.5], [-0.5, 0.5, 0.5], [-0.5, 0.5, -0.5]],
+            block_face: BlockFace::SIDE },
+    // +Y (top)
+    Face { dir: (0, 1, 0), normal: [0.0, 1.0, 0.0],
+            corners: [[-0.5, 0.5, 0.5], [0.5, 0.5, 0.5], [0.5, 0.5, -0.5], [-0.5, 0.5, -0.5]],
+            block_face: BlockFace::TOP },
+    // -Y (bottom)
+    Face { dir: (0, -1, 0), normal: [0.0, -1.0, 0.0],
+            corners: [[-0.5, -0.5, -0.5], [0.5, -0.5, -0.5], [0.5, -0.5, 0.5], [-0.5, -0.5, 0.5]],
+            block_face: BlockFace::BOTTOM },
+    // +Z
+    Face { dir: (0, 0, 1), normal: [0.0, 0.0, 1.0],
+            corners: [[-0.5, -0.5, 0.5], [0.5, -0.5, 0.5], [0.5, 0.5, 0.5], [-0.5, 0.5, 0.5]],
+            block_face: BlockFace::SIDE },
+    // -Z
+    Face { dir: (0, 0, -1), normal: [0.0, 0.0, -1.0],
+            corners: [[0.5, -0.5, -0.5], [-0.5, -0.5, -0.5], [-0.5, 0.5, -0.5], [0.5, 0.5, -0.5]],
+            block_face: BlockFace::SIDE },
+];
+
+// Side faces have v going up the block like the BasicCube.obj UVs
+const QUAD_UVS: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+/// Coarsest level of detail, blocks are 2^MAX_LOD wide. Can't be more
+/// than SECTION_HEIGHT wide or LOD quads would cross section boundaries.
+pub const MAX_LOD: u32 = 3;
+
+pub struct ChunkMesher
+{
+}
+
+impl ChunkMesher
+{
+    /// Returns the id of the block if it has a face touching an empty (or
+    /// transparent) space in the given direction, otherwise 0
+    fn exposed_face_id(chunk: &WorldChunk, x: i32, y: i32, z: i32, face: &Face, neighbors: &ChunkNeighbors, blocks: &BlockRegistry) -> u16
+    {
+        let id = chunk.get_id(x, y, z);
+        if id < 1 || blocks.hides_face(id, chunk.get_id_with_neighbors(x + face.dir.0, y + face.dir.1, z + face.dir.2, neighbors))
+        {
+            return 0;
+        }
+
+        id
+    }
+
+    /// Builds a mesh containing only the block faces that touch an empty
+    /// space. Faces on the chunk border are only culled if the neighbouring chunk
+    /// is given. Updates the chunk's block counts.
+    pub fn build_culled(chunk: &mut WorldChunk, neighbors: &ChunkNeighbors, blocks: &BlockRegistry) -> ChunkMeshData
+    {
+        let mut mesh = ChunkMeshData::new();
+
+        for y in 0..chunk.height as i32
+        {
+            for x in 0..chunk.width as i32
+            {
+                for z in 0..chunk.depth as i32
+                {
+                    for face in FACES.iter()
+                    {
+                        let id = ChunkMesher::exposed_face_id(chunk, x, y, z, face, neighbors, blocks);
+                        if id < 1
+                        {
+                            continue;
+                        }
+
+                        let mut corners = face.corners;
+                        for corner in corners.iter_mut()
+                        {
+                            corner[0] += x as f32;
+                            corner[1] += y as f32;
+                            corner[2] += z as f32;
+                        }
+
+                        mesh.push_quad(corners, QUAD_UVS, face.normal, blocks.texture_index(id, face.block_face));
+                    }
+                }
+            }
+        }
+
+        chunk.update_visibility(neighbors, blocks);
+        chunk.triangles = mesh.num_triangles();
+        mesh.sort_into_sections(chunk.height);
+
+        mesh
+    }
+
+    /// Like build_culled but neighbouring faces that point the same way and have
+    /// the same block id are merged into larger quads. The texture coordinates go
+    /// past 1.0 so the texture still repeats once per block.
+    pub fn build_greedy(chunk: &mut WorldChunk, neighbors: &ChunkNeighbors, blocks: &BlockRegistry) -> ChunkMeshData
+    {
+        let mut mesh = ChunkMesher::merge_faces(chunk, neighbors, blocks, SECTION_HEIGHT);
+
+        chunk.update_visibility(neighbors, blocks);
+        chunk.triangles = mesh.num_triangles();
+        mesh.sort_into_sections(chunk.height);
+
+        mesh
+    }
+
+    /// Builds a mesh from a copy of the chunk downsampled by 2^lod (see WorldChunk::downsample),
+    /// scaled back up to the chunk's size. Faces on the chunk border are always kept so
+    /// there are no gaps next to chunks with a different level of detail.
+    pub fn build_lod(chunk: &mut WorldChunk, lod: u32, greedy: bool, blocks: &BlockRegistry) -> ChunkMeshData
+    {
+        let factor = 1 << lod.min(MAX_LOD);
+        let mut coarse = chunk.downsample(factor);
+
+        // Coarse quads mustn't cross the full size section boundaries either
+        let mut mesh = match greedy
+        {
+            true => ChunkMesher::merge_faces(&coarse, &ChunkNeighbors::none(), blocks, SECTION_HEIGHT / factor),
+            false => ChunkMesher::build_culled(&mut coarse, &ChunkNeighbors::none(), blocks)
+        };
+
+        // Coarse block (x, y, z) covers blocks x * factor to x * factor + factor - 1
+        let (scale, shift) = (factor as f32, (factor - 1) as f32 / 2.0);
+        for vertex in mesh.vertices.iter_mut()
+        {
+            for p in vertex.position.iter_mut()
+            {
+                *p = *p * scale + shift;
+            }
+
+            vertex.tex_coord = [vertex.tex_coord[0] * scale, vertex.tex_coord[1] * scale];
+        }
+
+        chunk.update_visibility(&ChunkNeighbors::none(), blocks);
+        chunk.triangles = mesh.num_triangles();
+        mesh.sort_into_sections(chunk.height);
+
+        mesh
+    }
+
+    /// The greedy meshing itself. Quads are not merged across multiples of section_height in y.
+    fn merge_faces(chunk: &WorldChunk, neighbors: &ChunkNeighbors, blocks: &BlockRegistry, section_height: usize) -> ChunkMeshData
+    {
+        let mut mesh = ChunkMeshData::new();
+        let dims = [chunk.width, chunk.height, chunk.depth];
+
+        for face in FACES.iter()
+        {
+            // The axis the face points along and the two axes the face's
+            // texture coordinates run along (taken from the corner order)
+            let normal_axis = axis_of(face.dir);
+            let u_axis = axis_of(corner_delta(face.corners[0], face.corners[1]));
+            let v_axis = axis_of(corner_delta(face.corners[0], face.corners[3]));
+            let (u_size, v_size) = (dims[u_axis], dims[v_axis]);
+
+            let mut mask = vec![0u16; u_size * v_size];
+
+            for slice in 0..dims[normal_axis]
+            {
+                // Find every exposed face in this slice
+                for v in 0..v_size
+                {
+                    for u in 0..u_size
+                    {
+                        let mut pos = [0i32; 3];
+                        pos[normal_axis] = slice as i32;
+                        pos[u_axis] = u as i32;
+                        pos[v_axis] = v as i32;
+
+                        mask[v * u_size + u] = ChunkMesher::exposed_face_id(chunk, pos[0], pos[1], pos[2], face, neighbors, blocks);
+                    }
+                }
+
+                // Grow each face as far as it can go along u, then along v
+                for v in 0..v_size
+                {
+                    let mut u = 0;
+                    while u < u_size
+                    {
+                        let id = mask[v * u_size + u];
+                        if id < 1
+                        {
+                            u += 1;
+                            continue;
+                        }
+
+                        // Quads stop at section boundaries so they can be drawn separately
+                        let crosses_section = |axis: usize, end: usize| axis == 1 && end.is_multiple_of(section_height);
+
+                        let mut w = 1;
+                        while u + w < u_size && mask[v * u_size + u + w] == id && !crosses_section(u_axis, u + w)
+                        {
+                            w += 1;
+                        }
+
+                        let mut h = 1;
+                        'grow: while v + h < v_size && !crosses_section(v_axis, v + h)
+                        {
+                            for k in 0..w
+                            {
+                                if mask[(v + h) * u_size + u + k] != id
+                                {
+                                    break 'grow;
+                                }
+                            }
+
+                            h += 1;
+                        }
+
+                        for dv in 0..h
+                        {
+                            for du in 0..w
+                            {
+                                mask[(v + dv) * u_size + u + du] = 0;
+                            }
+                        }
+
+                        // Stretch the single block face over the merged area
+                        let mut corners = face.corners;
+                        let mut tex_coords = QUAD_UVS;
+                        for i in 0..4
+                        {
+                            let c = face.corners[i];
+                            corners[i][normal_axis] = slice as f32 + c[normal_axis];
+                            corners[i][u_axis] = u as f32 + (c[u_axis] + 0.5) * w as f32 - 0.5;
+                            corners[i][v_axis] = v as f32 + (c[v_axis] + 0.5) * h as f32 - 0.5;
+
+                            tex_coords[i] = [QUAD_UVS[i][0] * w as f32, QUAD_UVS[i][1] * h as f32];
+                        }
+
+                        mesh.push_quad(corners, tex_coords, face.normal, blocks.texture_index(id, face.block_face));
+
+                        u += w;
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+}
+
+/// Returns which axis (0 = x, 1 = y, 2 = z) a unit direction points along
+fn axis_of(dir: (i32, i32, i32)) -> usize
+{
+    if dir.0 != 0
+    {
+        return 0;
+    }
+
+    if dir.1 != 0
+    {
+        return 1;
+    }
+
+    2
+}
+
+fn corner_delta(a: [f32; 3], b: [f32; 3]) -> (i32, i32, i32)
+{
+    ((b[0] - a[0]) as i32, (b[1] - a[1]) as i32, (b[2] - a[2]) as i32)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn blocks() -> BlockRegistry
+    {
+        BlockRegistry::load("assets/blocks.cfg").unwrap()
+    }
+
+    fn solid_chunk(size: usize) -> WorldChunk
+    {
+        let mut chunk = WorldChunk::new(size, size, size);
+        chunk.fill(1);
+        chunk
+    }
+
+    #[test]
+    fn culled_mesh_has_only_outside_faces()
+    {
+        let mut chunk = solid_chunk(4);
+        let mesh = ChunkMesher::build_culled(&mut chunk, &ChunkNeighbors::none(), &blocks());
+
+        // 6 sides of 4x4 faces, 2 triangles each
+        assert_eq!(mesh.num_triangles(), 6 * 16 * 2);
+        assert_eq!(chunk.rendered_blocks, 56);
+    }
+
+    #[test]
+    fn greedy_mesh_merges_each_side_into_one_quad()
+    {
+        let mut chunk = solid_chunk(4);
+        let mesh = ChunkMesher::build_greedy(&mut chunk, &ChunkNeighbors::none(), &blocks());
+
+        assert_eq!(mesh.num_triangles(), 12);
+        assert_eq!(chunk.rendered_blocks, 56);
+    }
+
+    #[test]
+    fn greedy_mesh_keeps_block_types_apart()
+    {
+        let mut chunk = solid_chunk(4);
+        chunk.fill_layer(3, 2);
+        let mesh = ChunkMesher::build_greedy(&mut chunk, &ChunkNeighbors::none(), &blocks());
+
+        // Top and bottom are single quads, each side is split between the two ids
+        assert_eq!(mesh.num_triangles(), (2 + 4 * 2) * 2);
+    }
+
+    #[test]
+    fn neighbouring_chunk_culls_border_faces()
+    {
+        let mut chunk = solid_chunk(4);
+        let pos_x = solid_chunk(4);
+        let neighbors = ChunkNeighbors { pos_x: Some(&pos_x), ..ChunkNeighbors::none() };
+        let mesh = ChunkMesher::build_culled(&mut chunk, &neighbors, &blocks());
+
+        assert_eq!(mesh.num_triangles(), 5 * 16 * 2);
+    }
+
+    #[test]
+    fn lod_meshes_cover_the_whole_chunk()
+    {
+        let mut chunk = WorldChunk::new(16, SECTION_HEIGHT * 2, 16);
+        chunk.fill(1);
+
+        let culled = ChunkMesher::build_lod(&mut chunk, 1, false, &blocks());
+        assert_eq!(culled.num_triangles(), (8 * 8 * 2 + 8 * 16 * 4) * 2);
+
+        // Each side is still split at the section boundary
+        let greedy = ChunkMesher::build_lod(&mut chunk, 2, true, &blocks());
+        assert_eq!(greedy.num_triangles(), (2 + 4 * 2) * 2);
+        assert_eq!(greedy.sections.len(), 2);
+
+        for mesh in [&culled, &greedy].iter()
+        {
+            for axis in 0..3
+            {
+                let min = mesh.vertices.iter().map(|v| v.position[axis]).fold(f32::INFINITY, f32::min);
+                let max = mesh.vertices.iter().map(|v| v.position[axis]).fold(f32::NEG_INFINITY, f32::max);
+                let size = [16.0, (SECTION_HEIGHT * 2) as f32, 16.0][axis];
+                assert_eq!((min, max), (-0.5, size - 0.5));
+            }
+        }
+
+        // Textures still repeat once per block
+        let max_u = greedy.vertices.iter().map(|v| v.tex_coord[0]).fold(0.0, f32::max);
+        assert_eq!(max_u, 16.0);
+    }
+
+    #[test]
+    fn meshes_are_split_into_sections()
+    {
+        let mut chunk = WorldChunk::new(4, SECTION_HEIGHT * 2 + 4, 4);
+        chunk.fill(1);
+
+        for mesh in [ChunkMesher::build_culled(&mut chunk, &ChunkNeighbors::none(), &blocks()), 
+                        ChunkMesher::build_greedy(&mut chunk, &ChunkNeighbors::none(), &blocks())].iter()
+        {
+            assert_eq!(mesh.sections.len(), 3);
+            assert_eq!(mesh.sections[0].start, 0);
+            assert_eq!(mesh.sections[2].end, mesh.indices.len());
+
+            for (s, range) in mesh.sections.iter().enumerate()
+            {
+                for &i in mesh.indices[range.clone()].iter()
+                {
+                    let y = mesh.vertices[i as usize].position[1] + 0.5;
+                    assert!(y >= (s * SECTION_HEIGHT) as f32 && y <= ((s + 1) * SECTION_HEIGHT) as f32);
+                }
+            }
+        }
+
+        // Each side is split at the two section boundaries
+        let greedy = ChunkMesher::build_greedy(&mut chunk, &ChunkNeighbors::none(), &blocks());
+        assert_eq!(greedy.num_triangles(), (2 + 4 * 3) * 2);
+    }
+
+    #[test]
+    fn faces_use_the_registry_textures()
+    {
+        let blocks = BlockRegistry::parse("[grass]\nid = 1\ntexture = dirt.png\ntop = grass.png").unwrap();
+        let mut chunk = WorldChunk::new(1, 1, 1);
+        chunk.set(0, 0, 0, 1);
+        let mesh = ChunkMesher::build_culled(&mut chunk, &ChunkNeighbors::none(), &blocks);
+
+        for quad in mesh.vertices.chunks(4)
+        {
+            let face = if quad[0].normal[1] > 0.5 { BlockFace::TOP } else if quad[0].normal[1] < -0.5 { BlockFace::BOTTOM } else { BlockFace::SIDE };
+            assert_eq!(quad[0].texture, blocks.texture_index(1, face));
+        }
+
+        assert_eq!(mesh.vertices.iter().filter(|v| v.texture == blocks.texture_index(1, BlockFace::TOP)).count(), 4);
+    }
+}
@@ -0,0 +1,364 @@
+
+use std::mem;
+
+/// Number of layers in a section. A chunk is split into sections along y so
+/// that sections of all air (or all stone) can be stored as a single id.
+pub const SECTION_HEIGHT: usize = 16;
+
+/// Voxel ids for a chunk, either one u16 per block or compressed with a palette.
+/// Blocks are addressed by their flat index (see WorldChunk::index).
+#[derive(Clone)]
+pub enum ChunkStorage
+{
+    Flat(Vec<u16>),
+    Palette(PaletteStorage),
+}
+
+impl ChunkStorage
+{
+    pub fn get(self: &ChunkStorage, i: usize) -> u16
+    {
+        match self
+        {
+            ChunkStorage::Flat(blocks) => blocks[i],
+            ChunkStorage::Palette(palette) => palette.get(i)
+        }
+    }
+
+    pub fn set(self: &mut ChunkStorage, i: usize, id: u16)
+    {
+        match self
+        {
+            ChunkStorage::Flat(blocks) => blocks[i] = id,
+            ChunkStorage::Palette(palette) => palette.set(i, id)
+        }
+    }
+
+    pub fn fill(self: &mut ChunkStorage, id: u16)
+    {
+        match self
+        {
+            ChunkStorage::Flat(blocks) =>
+            {
+                for block in blocks.iter_mut()
+                {
+                    *block = id;
+                }
+            },
+
+            ChunkStorage::Palette(palette) => palette.fill(id)
+        }
+    }
+
+    /// Bytes used by the voxel data
+    pub fn memory_usage(self: &ChunkStorage) -> usize
+    {
+        match self
+        {
+            ChunkStorage::Flat(blocks) => blocks.len() * mem::size_of::<u16>(),
+            ChunkStorage::Palette(palette) => palette.memory_usage()
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Section
+{
+    /// Every block in the section has this id
+    Uniform(u16),
+
+    /// Palette indices, bits_per_block wide. Indices never straddle two words.
+    Packed(Vec<u64>),
+}
+
+/// Ids are stored as indices into a palette of the ids used in the chunk, packed
+/// into as few bits as the palette size allows. The palette only grows, when it
+/// outgrows the current bit width every section is repacked with one more bit.
+/// Sections that become uniform through set are only collapsed again by
+/// WorldChunk::compress.
+#[derive(Clone)]
+pub struct PaletteStorage
+{
+    len: usize,
+    section_volume: usize,
+    palette: Vec<u16>,
+    bits_per_block: u32,
+    sections: Vec<Section>,
+}
+
+impl PaletteStorage
+{
+    /// Compresses the ids. section_volume is the number of blocks in a full section.
+    pub fn from_ids(ids: &[u16], section_volume: usize) -> PaletteStorage
+    {
+        let mut palette: Vec<u16> = vec![];
+        for &id in ids
+        {
+            if !palette.contains(&id)
+            {
+                palette.push(id);
+            }
+        }
+
+        if palette.is_empty()
+        {
+            palette.push(0);
+        }
+
+        let mut storage = PaletteStorage { len: ids.len(), section_volume, bits_per_block: bits_for(palette.len()),
+                                            palette, sections: vec![] };
+
+        for section in ids.chunks(section_volume)
+        {
+            let first = section[0];
+            if section.iter().all(|&id| id == first)
+            {
+                storage.sections.push(Section::Uniform(first));
+                continue;
+            }
+
+            let mut data = vec![0u64; packed_len(section.len(), storage.bits_per_block)];
+            for (i, id) in section.iter().enumerate()
+            {
+                let index = storage.palette.iter().position(|p| p == id).unwrap();
+                write_packed(&mut data, storage.bits_per_block, i, index);
+            }
+
+            storage.sections.push(Section::Packed(data));
+        }
+
+        storage
+    }
+
+    pub fn to_ids(self: &PaletteStorage) -> Vec<u16>
+    {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
+
+    pub fn palette_len(self: &PaletteStorage) -> usize
+    {
+        self.palette.len()
+    }
+
+    pub fn bits_per_block(self: &PaletteStorage) -> u32
+    {
+        self.bits_per_block
+    }
+
+    pub fn num_uniform_sections(self: &PaletteStorage) -> usize
+    {
+        self.sections.iter().filter(|s| matches!(s, Section::Uniform(_))).count()
+    }
+
+    fn section_len(self: &PaletteStorage, section: usize) -> usize
+    {
+        (self.len - section * self.section_volume).min(self.section_volume)
+    }
+
+    pub fn get(self: &PaletteStorage, i: usize) -> u16
+    {
+        match &self.sections[i / self.section_volume]
+        {
+            Section::Uniform(id) => *id,
+            Section::Packed(data) => self.palette[read_packed(data, self.bits_per_block, i % self.section_volume)]
+        }
+    }
+
+    pub fn set(self: &mut PaletteStorage, i: usize, id: u16)
+    {
+        let (section, offset) = (i / self.section_volume, i % self.section_volume);
+        if let Section::Uniform(current) = self.sections[section]
+        {
+            if current == id
+            {
+                return;
+            }
+        }
+
+        // Do this first, adding to the palette can change the bit width
+        let index = self.palette_index(id);
+
+        if let Section::Uniform(current) = self.sections[section]
+        {
+            let fill = self.palette_index(current);
+            let count = self.section_len(section);
+            let mut data = vec![0u64; packed_len(count, self.bits_per_block)];
+            for j in 0..count
+            {
+                write_packed(&mut data, self.bits_per_block, j, fill);
+            }
+
+            self.sections[section] = Section::Packed(data);
+        }
+
+        if let Section::Packed(data) = &mut self.sections[section]
+        {
+            write_packed(data, self.bits_per_block, offset, index);
+        }
+    }
+
+    pub fn fill(self: &mut PaletteStorage, id: u16)
+    {
+        self.palette = vec![id];
+        self.bits_per_block = 1;
+        for section in self.sections.iter_mut()
+        {
+            *section = Section::Uniform(id);
+        }
+    }
+
+    pub fn memory_usage(self: &PaletteStorage) -> usize
+    {
+        let mut bytes = self.palette.len() * mem::size_of::<u16>() + self.sections.len() * mem::size_of::<Section>();
+        for section in self.sections.iter()
+        {
+            if let Section::Packed(data) = section
+            {
+                bytes += data.len() * mem::size_of::<u64>();
+            }
+        }
+
+        bytes
+    }
+
+    /// Returns the palette index for the id, adding it (and widening
+    /// the packed sections if needed) if it isn't there yet
+    fn palette_index(self: &mut PaletteStorage, id: u16) -> usize
+    {
+        if let Some(index) = self.palette.iter().position(|&p| p == id)
+        {
+            return index;
+        }
+
+        self.palette.push(id);
+
+        let bits = bits_for(self.palette.len());
+        if bits != self.bits_per_block
+        {
+            self.repack(bits);
+        }
+
+        self.palette.len() - 1
+    }
+
+    fn repack(self: &mut PaletteStorage, bits: u32)
+    {
+        for s in 0..self.sections.len()
+        {
+            let count = self.section_len(s);
+            if let Section::Packed(old) = &self.sections[s]
+            {
+                let mut data = vec![0u64; packed_len(count, bits)];
+                for j in 0..count
+                {
+                    write_packed(&mut data, bits, j, read_packed(old, self.bits_per_block, j));
+                }
+
+                self.sections[s] = Section::Packed(data);
+            }
+        }
+
+        self.bits_per_block = bits;
+    }
+}
+
+/// Bits needed to store an index into a palette of this size (at least 1)
+fn bits_for(palette_len: usize) -> u32
+{
+    let mut bits = 1;
+    while (1usize << bits) < palette_len
+    {
+        bits += 1;
+    }
+
+    bits
+}
+
+fn packed_len(count: usize, bits: u32) -> usize
+{
+    let per_word = (64 / bits) as usize;
+    count.div_ceil(per_word)
+}
+
+fn read_packed(data: &[u64], bits: u32, i: usize) -> usize
+{
+    let per_word = (64 / bits) as usize;
+    let shift = (i % per_word) as u32 * bits;
+    let mask = (1u64 << bits) - 1;
+
+    ((data[i / per_word] >> shift) & mask) as usize
+}
+
+fn write_packed(data: &mut [u64], bits: u32, i: usize, value: usize)
+{
+    let per_word = (64 / bits) as usize;
+    let shift = (i % per_word) as u32 * bits;
+    let mask = ((1u64 << bits) - 1) << shift;
+
+    let word = &mut data[i / per_word];
+    *word = (*word & !mask) | ((value as u64) << shift);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn round_trips_ids()
+    {
+        let ids: Vec<u16> = (0..1000).map(|i| ((i * 7) % 5) as u16).collect();
+        let storage = PaletteStorage::from_ids(&ids, 256);
+
+        assert_eq!(storage.palette_len(), 5);
+        assert_eq!(storage.bits_per_block(), 3);
+        assert_eq!(storage.to_ids(), ids);
+    }
+
+    #[test]
+    fn uniform_sections_collapse()
+    {
+        let mut ids = vec![3u16; 512];
+        ids.extend(vec![0u16; 512]);
+        ids[700] = 1;
+
+        let storage = PaletteStorage::from_ids(&ids, 256);
+        assert_eq!(storage.num_uniform_sections(), 3);
+        assert_eq!(storage.to_ids(), ids);
+    }
+
+    #[test]
+    fn set_grows_the_palette()
+    {
+        let mut storage = PaletteStorage::from_ids(&vec![0u16; 600], 256);
+        assert_eq!(storage.num_uniform_sections(), 3);
+
+        for i in 0..20
+        {
+            storage.set(i * 3, i as u16 + 1);
+        }
+
+        assert_eq!(storage.palette_len(), 21);
+        assert_eq!(storage.bits_per_block(), 5);
+
+        for i in 0..20
+        {
+            assert_eq!(storage.get(i * 3), i as u16 + 1);
+            assert_eq!(storage.get(i * 3 + 1), 0);
+        }
+
+        // The last (partial) section was never touched
+        assert_eq!(storage.get(599), 0);
+        assert_eq!(storage.num_uniform_sections(), 2);
+    }
+
+    #[test]
+    fn uses_less_memory_than_flat()
+    {
+        let ids: Vec<u16> = (0..128 * 32 * 128).map(|i| if i < 128 * 128 * 10 { 1 + (i % 3) as u16 } else { 0 }).collect();
+        let flat = ChunkStorage::Flat(ids.clone());
+        let palette = ChunkStorage::Palette(PaletteStorage::from_ids(&ids, 128 * 128 * SECTION_HEIGHT));
+
+        assert!(palette.memory_usage() * 4 < flat.memory_usage());
+    }
+}
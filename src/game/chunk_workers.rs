@@ -0,0 +1,252 @@
+
+use std::{thread, sync::{Arc, Mutex, mpsc, atomic::{AtomicBool, Ordering}}};
+use crate::game::{WorldChunk, ChunkNeighbors, ChunkCoord, ChunkGeneration, NoiseType, MeshMode, BlockRegistry, generators::GeneratorRegistry, region_store::RegionStore, section_visibility::SectionConnections, 
+                    chunk_mesher::{ChunkMesher, ChunkGeometry, section_ranges, num_sections}};
+
+/// Everything a worker needs to build a chunk
+pub struct ChunkJob
+{
+    pub coord: ChunkCoord,
+    pub origin: (i32, i32),
+    pub dimensions: (usize, usize, usize),
+    pub settings: ChunkGeneration,
+    pub generators: Arc<GeneratorRegistry>,
+    pub blocks: Arc<BlockRegistry>,
+    pub mesh_mode: MeshMode,
+
+    /// Level of detail to build the geometry at, 0 is full detail
+    pub lod: u32,
+
+    /// Voxel data to rebuild the geometry for. If this is None
+    /// the chunk is generated first.
+    pub chunk: Option<Arc<WorldChunk>>,
+
+    /// Loaded chunks next to this one to hide border blocks against, in NEIGHBOR_OFFSETS order
+    pub neighbors: [Option<Arc<WorldChunk>>; 4],
+
+    /// Neighbours that were loaded, including ones left out of neighbors
+    /// because they have a different level of detail
+    pub loaded_neighbors: [bool; 4],
+
+    /// Compress the chunk once the geometry is built
+    pub compress: bool,
+
+    /// Where to look for a saved copy before generating the chunk
+    pub regions: Option<Arc<RegionStore>>,
+
+    /// Copied into the result so the world can tell if the
+    /// settings changed while the job was running
+    pub stamp: u32,
+}
+
+/// A finished chunk. Only the GPU upload of the geometry
+/// is left to do on the render thread.
+pub struct ChunkResult
+{
+    pub coord: ChunkCoord,
+    pub chunk: WorldChunk,
+    pub geometry: ChunkGeometry,
+    pub stamp: u32,
+    pub lod: u32,
+
+    /// Which neighbours were loaded when the geometry was built
+    pub neighbors: [bool; 4],
+
+    /// Which faces of each section can see each other, for occlusion culling
+    pub connections: Vec<SectionConnections>,
+}
+
+/// Offsets to the neighbouring chunks: +X, -X, +Z, -Z
+pub const NEIGHBOR_OFFSETS: [ChunkCoord; 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Pool of threads that generate voxel data and build the
+/// geometry for chunks.
+pub struct ChunkWorkers
+{
+    job_sender: Option<mpsc::Sender<ChunkJob>>,
+    result_receiver: mpsc::Receiver<ChunkResult>,
+    shutting_down: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl ChunkWorkers
+{
+    /// Starts the worker threads. If num_threads is 0 one thread per
+    /// core is started, leaving one core for the render thread.
+    pub fn new(num_threads: usize) -> ChunkWorkers
+    {
+        let num_threads = match num_threads
+        {
+            0 => match thread::available_parallelism()
+            {
+                Ok(n) => (n.get() - 1).max(1),
+                Err(_) => 1
+            },
+            n => n
+        };
+
+        let (job_sender, job_receiver) = mpsc::channel::<ChunkJob>();
+        let (result_sender, result_receiver) = mpsc::channel::<ChunkResult>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let shutting_down = Arc::new(AtomicBool::new(false));
+
+        let mut threads = vec![];
+        for i in 0..num_threads
+        {
+            let job_receiver = Arc::clone(&job_receiver);
+            let result_sender = result_sender.clone();
+            let shutting_down = Arc::clone(&shutting_down);
+
+            let handle = thread::Builder::new().name(format!("chunk_worker_{}", i)).spawn(move ||
+            {
+                loop
+                {
+                    // The lock is only held while waiting for the next job
+                    let job = match job_receiver.lock().unwrap().recv()
+                    {
+                        Ok(job) => job,
+
+                        // The sender was dropped, the pool is shutting down
+                        Err(_) => break
+                    };
+
+                    // Don't work through the rest of the queue when the pool is dropped
+                    if shutting_down.load(Ordering::Relaxed)
+                    {
+                        break;
+                    }
+
+                    let result = ChunkWorkers::run_job(job);
+                    if result_sender.send(result).is_err()
+                    {
+                        break;
+                    }
+                }
+            }).unwrap();
+
+            threads.push(handle);
+        }
+
+        ChunkWorkers { job_sender: Some(job_sender), result_receiver, shutting_down, threads }
+    }
+
+    fn run_job(job: ChunkJob) -> ChunkResult
+    {
+        let mut chunk = match &job.chunk
+        {
+            Some(chunk) => 
+            {
+                let mut chunk = (**chunk).clone();
+                chunk.decompress();
+                chunk
+            },
+            None => match ChunkWorkers::load_saved(&job)
+            {
+                Some(chunk) => chunk,
+                None =>
+                {
+                    let mut chunk = WorldChunk::new(job.dimensions.0, job.dimensions.1, job.dimensions.2);
+                    job.generators.generate(&mut chunk, job.origin, &job.settings, &job.blocks);
+                    chunk.generation = Some(job.settings);
+                    chunk
+                }
+            }
+        };
+
+        let neighbors = ChunkNeighbors { pos_x: job.neighbors[0].as_deref(), neg_x: job.neighbors[1].as_deref(),
+                                        pos_z: job.neighbors[2].as_deref(), neg_z: job.neighbors[3].as_deref() };
+
+        let geometry = match job.mesh_mode
+        {
+            _ if job.lod > 0 => ChunkGeometry::Mesh(ChunkMesher::build_lod(&mut chunk, job.lod, job.mesh_mode == MeshMode::GREEDY, &job.blocks)),
+
+            MeshMode::INSTANCED => 
+            {
+                // Instances are built bottom layer first so they're already sorted by section
+                let instances = chunk.gen_instance_data(&neighbors, &job.blocks, false);
+                let instance_sections: Vec<usize> = instances.iter().map(|instance| instance.section()).collect();
+                let sections = section_ranges(&instance_sections, num_sections(chunk.height));
+
+                ChunkGeometry::Instances(instances, sections)
+            },
+            MeshMode::CULLED => ChunkGeometry::Mesh(ChunkMesher::build_culled(&mut chunk, &neighbors, &job.blocks)),
+            MeshMode::GREEDY => ChunkGeometry::Mesh(ChunkMesher::build_greedy(&mut chunk, &neighbors, &job.blocks)),
+        };
+
+        let connections = SectionConnections::compute_chunk(&chunk, &job.blocks);
+
+        if job.compress
+        {
+            chunk.compress();
+        }
+
+        ChunkResult { coord: job.coord, chunk, geometry, stamp: job.stamp, lod: job.lod, neighbors: job.loaded_neighbors, connections }
+    }
+
+    /// Returns the saved chunk if there is one that was generated with the same settings.
+    /// Each chunk is saved once per generation settings (see RegionStore), so switching
+    /// back to earlier settings restores the edits made with them.
+    fn load_saved(job: &ChunkJob) -> Option<WorldChunk>
+    {
+        // Animated chunks are never saved (see World::save_chunks)
+        if job.settings.noise_type == NoiseType::ANIMATED_3D
+        {
+            return None;
+        }
+
+        let regions = job.regions.as_ref()?;
+        match regions.load_chunk(job.coord, &job.settings)
+        {
+            Ok(Some(mut chunk)) =>
+            {
+                if (chunk.width, chunk.height, chunk.depth) != job.dimensions
+                {
+                    return None;
+                }
+
+                chunk.decompress();
+                Some(chunk)
+            },
+            Ok(None) => None,
+            Err(e) =>
+            {
+                println!("{}", e);
+                None
+            }
+        }
+    }
+
+    pub fn num_threads(self: &ChunkWorkers) -> usize
+    {
+        self.threads.len()
+    }
+
+    pub fn submit(self: &ChunkWorkers, job: ChunkJob)
+    {
+        if let Some(sender) = &self.job_sender
+        {
+            sender.send(job).unwrap();
+        }
+    }
+
+    /// Returns a finished chunk if there is one, never blocks
+    pub fn try_recv(self: &ChunkWorkers) -> Option<ChunkResult>
+    {
+        self.result_receiver.try_recv().ok()
+    }
+}
+
+impl Drop for ChunkWorkers
+{
+    fn drop(&mut self)
+    {
+        // Dropping the sender wakes up any idle workers with an error so they exit
+        self.shutting_down.store(true, Ordering::Relaxed);
+        self.job_sender = None;
+
+        for handle in self.threads.drain(..)
+        {
+            let _ = handle.join();
+        }
+    }
+}
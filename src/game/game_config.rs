@@ -0,0 +1,456 @@
+
+use std::{ fs, path::Path };
+use crate::{ utils::{ FractalMode, NoiseAlgorithm }, game::{ GameData, NoiseType, MeshMode, chunk_mesher::MAX_LOD } };
+
+/// Config file read at start up if it exists and --config isn't given
+pub const DEFAULT_CONFIG: &str = "block_world.cfg";
+
+/// Every option that can be set from the config file or the command line: the config
+/// file section it goes in, its name and a description for the usage message.
+/// On the command line options are passed as --name value.
+const OPTIONS: [(&str, &str, &str); 33] =
+[
+    ("generation", "noise_type", "random2d, random3d, olc, simplex2d, simplex3d, fractal2d, fractal3d, warped2d, warped3d or animated3d"),
+    ("generation", "seed", "number or none for a random seed"),
+    ("generation", "offset", "x, z added to the noise sample position"),
+    ("generation", "zoom_factor", "scale applied to the noise sample position, > 0"),
+    ("generation", "sx_scale", "simplex noise scale, > 0"),
+    ("generation", "threshold", "density needed to place a block (3D noise)"),
+    ("generation", "threshold_falloff", "how quickly the threshold rises with height, >= 1"),
+    ("generation", "octaves", "olc and fractal noise octaves, 1 - 6"),
+    ("generation", "bias", "olc noise bias, >= 0.2"),
+    ("generation", "fractal_mode", "fbm, ridged, billow or turbulence"),
+    ("generation", "lacunarity", "frequency multiplier between fractal octaves, >= 1"),
+    ("generation", "gain", "amplitude multiplier between fractal octaves, 0 - 1"),
+    ("generation", "warp_strength", "how far the domain warp moves samples, in noise space, >= 0"),
+    ("generation", "warp_frequency", "domain warp noise frequency relative to the terrain noise, > 0"),
+    ("generation", "double_warp", "true to warp the warp as well"),
+    ("generation", "base_noise", "fractal and warped noise source: simplex, opensimplex2, perlin, value, worleyf1, worleyf2 or worleyf2minusf1"),
+
+    ("world", "chunk_width", "blocks, 1 - 1024"),
+    ("world", "chunk_height", "blocks, 1 - 1024"),
+    ("world", "chunk_depth", "blocks, 1 - 1024"),
+    ("world", "load_radius", "render distance in chunks around the camera, 0 - 32"),
+    ("world", "max_uploads_per_frame", "chunks uploaded to the GPU each frame, >= 1"),
+    ("world", "cull_chunk_borders", "true or false"),
+    ("world", "compress_chunks", "true or false"),
+    ("world", "save_dir", "directory for region files or none"),
+    ("world", "worker_threads", "0 uses one per core"),
+    ("world", "mesh_mode", "instanced, culled or greedy"),
+    ("world", "lod_distances", "increasing distances in blocks where each level of detail starts"),
+    ("world", "animation_speed", "how fast animated3d noise changes, noise units per second, >= 0"),
+    ("world", "animation_rate", "most times per second animated3d terrain is regenerated, 0 - 60"),
+
+    ("display", "window_width", "pixels"),
+    ("display", "window_height", "pixels"),
+    ("display", "fov", "vertical field of view in degrees, 10 - 150"),
+    ("display", "view_distance", "far clipping plane in blocks, > 1"),
+];
+
+impl GameData
+{
+    /// Applies the config file then the command line flags and checks the result.
+    /// The config file is --config if given, otherwise DEFAULT_CONFIG if it exists.
+    pub fn configure(self: &mut GameData, args: &[String]) -> Result<(), String>
+    {
+        let (config, flags) = take_config_arg(args)?;
+        self.load_config_or_default(config.as_deref())?;
+        self.apply_args(&flags)?;
+        self.validate()
+    }
+
+    /// Loads the given config file, or DEFAULT_CONFIG if there isn't one and it exists
+    pub fn load_config_or_default(self: &mut GameData, filename: Option<&str>) -> Result<(), String>
+    {
+        match filename
+        {
+            Some(filename) => self.load_config(filename),
+            None if Path::new(DEFAULT_CONFIG).exists() => self.load_config(DEFAULT_CONFIG),
+            None => Ok(())
+        }
+    }
+
+    pub fn load_config(self: &mut GameData, filename: &str) -> Result<(), String>
+    {
+        match fs::read_to_string(filename)
+        {
+            Ok(text) => match self.apply_config(&text)
+            {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("Error Loading Config! File: {}, Error: {}", filename, e))
+            },
+            Err(e) => Err(format!("Error Loading Config! File: {}, Error: {:?}", filename, e))
+        }
+    }
+
+    /// Parses the config file format, the same layout as the block file:
+    ///
+    ///     # comment
+    ///     [section]
+    ///     name = value
+    pub fn apply_config(self: &mut GameData, text: &str) -> Result<(), String>
+    {
+        let mut section: Option<String> = None;
+        for (i, line) in text.lines().enumerate()
+        {
+            let line_num = i + 1;
+            let line = match line.find('#')
+            {
+                Some(pos) => &line[..pos],
+                None => line
+            }.trim();
+
+            if line.is_empty()
+            {
+                continue;
+            }
+
+            if line.starts_with('[')
+            {
+                let name = line.trim_start_matches('[').trim_end_matches(']').trim();
+                if !line.ends_with(']') || !OPTIONS.iter().any(|(s, _, _)| *s == name)
+                {
+                    return Err(format!("Line {}: Unknown section {}", line_num, line));
+                }
+
+                section = Some(String::from(name));
+                continue;
+            }
+
+            let (key, value) = match line.find('=')
+            {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => return Err(format!("Line {}: Expected name = value, found {}", line_num, line))
+            };
+
+            match (&section, option_section(key))
+            {
+                (Some(current), Some(expected)) if current != expected =>
+                    return Err(format!("Line {}: {} belongs in [{}], not [{}]", line_num, key, expected, current)),
+                (None, _) => return Err(format!("Line {}: {} is not inside a [section]", line_num, key)),
+                _ => ()
+            }
+
+            if let Err(e) = self.set_option(key, value)
+            {
+                return Err(format!("Line {}: {}", line_num, e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies --name value pairs
+    pub fn apply_args(self: &mut GameData, args: &[String]) -> Result<(), String>
+    {
+        for pair in args.chunks(2)
+        {
+            let key = match pair[0].strip_prefix("--")
+            {
+                Some(key) => key,
+                None => return Err(format!("Expected --option, found {}", pair[0]))
+            };
+
+            match pair.get(1)
+            {
+                Some(value) => self.set_option(key, value)?,
+                None => return Err(format!("Missing value for {}", pair[0]))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets one option by name. Values are checked to be the right type here,
+    /// validate checks that they make sense.
+    pub fn set_option(self: &mut GameData, key: &str, value: &str) -> Result<(), String>
+    {
+        let generation = &mut self.chunk_generation;
+        let world = &mut self.world;
+        let display = &mut self.display;
+        match key
+        {
+            "noise_type" => generation.noise_type = match NoiseType::from_name(value)
+            {
+                Some(noise_type) => noise_type,
+                None => return Err(format!("Unknown noise type: {}", value))
+            },
+            "seed" => generation.seed = parse_seed(value)?,
+            "offset" => generation.offset = parse_pair(key, value)?,
+            "zoom_factor" => generation.zoom_factor = parse_value(key, value)?,
+            "sx_scale" => generation.sx_scale = parse_value(key, value)?,
+            "threshold" => generation.threshold = parse_value(key, value)?,
+            "threshold_falloff" => generation.threshold_falloff = parse_value(key, value)?,
+            "octaves" => generation.octaves = parse_value(key, value)?,
+            "bias" => generation.bias = parse_value(key, value)?,
+            "fractal_mode" => generation.fractal_mode = match FractalMode::from_name(value)
+            {
+                Some(mode) => mode,
+                None => return Err(format!("Unknown fractal mode: {}", value))
+            },
+            "lacunarity" => generation.lacunarity = parse_value(key, value)?,
+            "gain" => generation.gain = parse_value(key, value)?,
+            "warp_strength" => generation.warp_strength = parse_value(key, value)?,
+            "warp_frequency" => generation.warp_frequency = parse_value(key, value)?,
+            "double_warp" => generation.double_warp = parse_value(key, value)?,
+            "base_noise" => generation.base_noise = match NoiseAlgorithm::from_name(value)
+            {
+                Some(algorithm) => algorithm,
+                None => return Err(format!("Unknown noise algorithm: {}", value))
+            },
+
+            "chunk_width" => world.chunk_width = parse_value(key, value)?,
+            "chunk_height" => world.chunk_height = parse_value(key, value)?,
+            "chunk_depth" => world.chunk_depth = parse_value(key, value)?,
+            "load_radius" => world.load_radius = parse_value(key, value)?,
+            "max_uploads_per_frame" => world.max_uploads_per_frame = parse_value(key, value)?,
+            "cull_chunk_borders" => world.cull_chunk_borders = parse_value(key, value)?,
+            "compress_chunks" => world.compress_chunks = parse_value(key, value)?,
+            "save_dir" => world.save_dir = if value == "none" { None } else { Some(String::from(value)) },
+            "worker_threads" => world.worker_threads = parse_value(key, value)?,
+            "mesh_mode" => world.mesh_mode = match MeshMode::from_name(value)
+            {
+                Some(mode) => mode,
+                None => return Err(format!("Unknown mesh mode: {}", value))
+            },
+            "lod_distances" => world.lod_distances = if value.is_empty() { vec![] } else { parse_list(key, value)? },
+            "animation_speed" => world.animation_speed = parse_value(key, value)?,
+            "animation_rate" => world.animation_rate = parse_value(key, value)?,
+
+            "window_width" => display.window_width = parse_value(key, value)?,
+            "window_height" => display.window_height = parse_value(key, value)?,
+            "fov" => display.fov = parse_value(key, value)?,
+            "view_distance" => display.view_distance = parse_value(key, value)?,
+            _ => return Err(format!("Unknown option: {}", key))
+        }
+
+        Ok(())
+    }
+
+    /// Checks for values that would crash the game or can't produce a world
+    pub fn validate(self: &GameData) -> Result<(), String>
+    {
+        let generation = &self.chunk_generation;
+        let world = &self.world;
+        let display = &self.display;
+
+        let checks =
+        [
+            (generation.zoom_factor > 0.0 && generation.zoom_factor.is_finite(), "zoom_factor must be larger than 0"),
+            (generation.sx_scale > 0.0 && generation.sx_scale.is_finite(), "sx_scale must be larger than 0"),
+            (generation.threshold.is_finite(), "threshold must be a number"),
+            (generation.threshold_falloff >= 1, "threshold_falloff must be at least 1"),
+            ((1..=6).contains(&generation.octaves), "octaves must be between 1 and 6"),
+            (generation.bias >= 0.2 && generation.bias.is_finite(), "bias must be at least 0.2"),
+            (generation.lacunarity >= 1.0 && generation.lacunarity.is_finite(), "lacunarity must be at least 1"),
+            (generation.gain > 0.0 && generation.gain <= 1.0, "gain must be larger than 0 and at most 1"),
+            (generation.warp_strength >= 0.0 && generation.warp_strength.is_finite(), "warp_strength must be at least 0"),
+            (generation.warp_frequency > 0.0 && generation.warp_frequency.is_finite(), "warp_frequency must be larger than 0"),
+            ([world.chunk_width, world.chunk_height, world.chunk_depth].iter().all(|s| (1..=1024).contains(s)),
+                "chunk_width, chunk_height and chunk_depth must be between 1 and 1024"),
+            ((0..=32).contains(&world.load_radius), "load_radius must be between 0 and 32"),
+            (world.max_uploads_per_frame >= 1, "max_uploads_per_frame must be at least 1"),
+            (world.lod_distances.len() <= MAX_LOD as usize, "lod_distances has more levels than are supported (3)"),
+            (world.lod_distances.iter().all(|d| *d >= 0.0) && world.lod_distances.windows(2).all(|w| w[0] < w[1]),
+                "lod_distances must be positive and increasing"),
+            (world.animation_speed >= 0.0 && world.animation_speed.is_finite(), "animation_speed must be at least 0"),
+            (world.animation_rate > 0.0 && world.animation_rate <= 60.0, "animation_rate must be larger than 0 and at most 60"),
+            (display.window_width > 0 && display.window_height > 0, "window_width and window_height must be larger than 0"),
+            ((10.0..=150.0).contains(&display.fov), "fov must be between 10 and 150 degrees"),
+            (display.view_distance > 1.0 && display.view_distance.is_finite(), "view_distance must be larger than 1"),
+        ];
+
+        match checks.iter().find(|(ok, _)| !ok)
+        {
+            Some((_, message)) => Err(format!("Invalid Setting! {}", message)),
+            None => Ok(())
+        }
+    }
+}
+
+pub fn print_usage()
+{
+    println!("Usage: block_world [--config file] [--option value ...]");
+    println!("       block_world export [options] (see block_world export --help)");
+    println!("\nOptions can also be set in the config file ({} by default) under their [section]:", DEFAULT_CONFIG);
+
+    for section in ["generation", "world", "display"].iter()
+    {
+        println!("\n[{}]", section);
+        print_options(section);
+    }
+}
+
+/// Lists the options in one section of the config file with their descriptions
+pub fn print_options(section: &str)
+{
+    for (_, key, help) in OPTIONS.iter().filter(|(s, _, _)| *s == section)
+    {
+        println!("\t--{:<24}{}", key, help);
+    }
+}
+
+/// Splits "--config file" out of the arguments, returning the file and the other arguments
+pub fn take_config_arg(args: &[String]) -> Result<(Option<String>, Vec<String>), String>
+{
+    let mut config = None;
+    let mut rest = vec![];
+    let mut i = 0;
+    while i < args.len()
+    {
+        if args[i] == "--config"
+        {
+            match args.get(i + 1)
+            {
+                Some(filename) => config = Some(filename.clone()),
+                None => return Err(String::from("Missing value for --config"))
+            }
+            i += 2;
+            continue;
+        }
+
+        rest.push(args[i].clone());
+        i += 1;
+    }
+
+    Ok((config, rest))
+}
+
+/// Config file section the option is in, None for unknown options
+pub fn option_section(key: &str) -> Option<&'static str>
+{
+    OPTIONS.iter().find(|(_, k, _)| *k == key).map(|(s, _, _)| *s)
+}
+
+pub fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String>
+{
+    value.trim().parse().map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
+
+pub fn parse_list<T: std::str::FromStr>(name: &str, value: &str) -> Result<Vec<T>, String>
+{
+    value.split(',').map(|v| parse_value(name, v)).collect()
+}
+
+pub fn parse_pair<T: std::str::FromStr + Copy>(name: &str, value: &str) -> Result<(T, T), String>
+{
+    let list: Vec<T> = parse_list(name, value)?;
+    if list.len() != 2
+    {
+        return Err(format!("{} takes two values: {}", name, value));
+    }
+
+    Ok((list[0], list[1]))
+}
+
+/// A number is written into the first 8 bytes of the seed, "none" uses a random seed
+pub fn parse_seed(value: &str) -> Result<Option<[u8; 32]>, String>
+{
+    if value == "none"
+    {
+        return Ok(None);
+    }
+
+    let number: u64 = parse_value("seed", value)?;
+    let mut seed = [0; 32];
+    seed[..8].copy_from_slice(&number.to_le_bytes());
+    Ok(Some(seed))
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use super::*;
+    use crate::game::{ DebugSettings, ChunkGeneration, EditSettings, WorldSettings, DisplaySettings };
+
+    pub fn game_data() -> GameData
+    {
+        GameData { debug: DebugSettings { print_help: false, print_chunk_info: false, remake_test_scene: false,
+                                            occlusion_culling: true, show_occluded_sections: false },
+                    chunk_generation: ChunkGeneration { noise_type: NoiseType::SIMPLEX_2D, offset: (0.0, 0.0), zoom_factor: 0.01,
+                                                        sx_scale: 32.0, threshold: 0.3, threshold_falloff: 20,
+                                                        octaves: 3, bias: 0.5, seed: Some([0; 32]),
+                                                        fractal_mode: FractalMode::FBM, lacunarity: 2.0, gain: 0.5,
+                                                        warp_strength: 1.0, warp_frequency: 1.0, double_warp: false,
+                                                        base_noise: NoiseAlgorithm::SIMPLEX, time: 0.0 },
+                    edit: EditSettings { selected_block: 1, reach: 8.0, break_block: false, place_block: false, next_block: false },
+                    world: WorldSettings { chunk_width: 128, chunk_height: 32, chunk_depth: 128, load_radius: 1,
+                                            max_uploads_per_frame: 2, cull_chunk_borders: true, compress_chunks: true,
+                                            save_dir: None, worker_threads: 0, mesh_mode: MeshMode::INSTANCED,
+                                            lod_distances: vec![96.0, 192.0, 384.0],
+                                            animation_speed: 0.25, animation_rate: 5.0 },
+                    display: DisplaySettings { window_width: 1280, window_height: 720, fov: 60.0, view_distance: 1024.0 } }
+    }
+
+    fn args(list: &[&str]) -> Vec<String>
+    {
+        list.iter().map(|a| String::from(*a)).collect()
+    }
+
+    #[test]
+    fn config_file_sets_options()
+    {
+        let mut data = game_data();
+        data.apply_config("# test\n[generation]\nnoise_type = simplex3d\nseed = 42\noffset = 1.5, -2\nfractal_mode = billow\n\
+                            base_noise = OpenSimplex2\n\n\
+                            [world]\nchunk_width = 64 # narrow\nsave_dir = none\nmesh_mode = greedy\nlod_distances = 50, 100\n\
+                            [display]\nfov = 75").unwrap();
+
+        assert_eq!(data.chunk_generation.noise_type, NoiseType::SIMPLEX_3D);
+        assert_eq!(data.chunk_generation.seed.unwrap()[0], 42);
+        assert_eq!(data.chunk_generation.offset, (1.5, -2.0));
+        assert_eq!(data.chunk_generation.fractal_mode, FractalMode::BILLOW);
+        assert_eq!(data.chunk_generation.base_noise, NoiseAlgorithm::OPEN_SIMPLEX2);
+        assert_eq!(data.world.chunk_width, 64);
+        assert_eq!(data.world.save_dir, None);
+        assert_eq!(data.world.mesh_mode, MeshMode::GREEDY);
+        assert_eq!(data.world.lod_distances, vec![50.0, 100.0]);
+        assert_eq!(data.display.fov, 75.0);
+
+        // Untouched options keep their defaults
+        assert_eq!(data.chunk_generation.zoom_factor, 0.01);
+        assert!(data.validate().is_ok());
+    }
+
+    #[test]
+    fn config_errors_name_the_line()
+    {
+        let mut data = game_data();
+        assert_eq!(data.apply_config("[world]\nfov = 70"), Err(String::from("Line 2: fov belongs in [display], not [world]")));
+        assert!(data.apply_config("zoom_factor = 0.1").is_err());
+        assert!(data.apply_config("[terrain]").is_err());
+        assert!(data.apply_config("[generation]\nzoom = 0.1").is_err());
+        assert!(data.apply_config("[generation]\noctaves = many").is_err());
+    }
+
+    #[test]
+    fn flags_override_the_config_file()
+    {
+        let mut data = game_data();
+        data.apply_config("[generation]\nzoom_factor = 0.05\nbias = 0.8").unwrap();
+        data.apply_args(&args(&["--zoom_factor", "0.02", "--load_radius", "3"])).unwrap();
+
+        assert_eq!(data.chunk_generation.zoom_factor, 0.02);
+        assert_eq!(data.chunk_generation.bias, 0.8);
+        assert_eq!(data.world.load_radius, 3);
+
+        assert!(data.apply_args(&args(&["--zoom_factor"])).is_err());
+        assert!(data.apply_args(&args(&["zoom_factor", "1"])).is_err());
+    }
+
+    #[test]
+    fn nonsense_values_are_rejected()
+    {
+        let bad = [("zoom_factor", "0"), ("threshold_falloff", "0"), ("octaves", "9"), ("bias", "0"),
+                    ("chunk_height", "0"), ("load_radius", "-1"), ("fov", "180"), ("view_distance", "inf"),
+                    ("lod_distances", "100, 50"), ("lacunarity", "0.5"), ("gain", "2"), ("warp_frequency", "0"), ("lod_distances", "1, 2, 3, 4"), ("window_width", "0"),
+                    ("animation_speed", "-1"), ("animation_rate", "0")];
+
+        for (key, value) in bad.iter()
+        {
+            let mut data = game_data();
+            data.set_option(key, value).unwrap();
+            assert!(data.validate().is_err(), "{} = {} should not be valid", key, value);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 
+use crate::utils::{ FractalMode, NoiseAlgorithm };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ChunkGeneration
 {
     pub noise_type: NoiseType,
@@ -11,7 +12,24 @@ pub struct ChunkGeneration
     pub threshold_falloff: i32,
     pub octaves: i32,
     pub bias: f32,
-    pub seed: Option<[u8; 32]>
+    pub seed: Option<[u8; 32]>,
+
+    /// Fractal noise settings, octaves are shared with OLC noise
+    pub fractal_mode: FractalMode,
+    pub lacunarity: f32,
+    pub gain: f32,
+
+    /// Domain warp settings. The strength is in noise space (after zoom_factor) and
+    /// the frequency is relative to the terrain noise.
+    pub warp_strength: f32,
+    pub warp_frequency: f32,
+    pub double_warp: bool,
+
+    /// The noise the fractal and warped generators are built from
+    pub base_noise: NoiseAlgorithm,
+
+    /// Fourth noise coordinate for ANIMATED_3D, the demo moves it along with the elapsed time
+    pub time: f32,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -20,22 +38,165 @@ pub struct DebugSettings
     pub print_help: bool,
     pub print_chunk_info: bool,
     pub remake_test_scene: bool,
+
+    /// Skip chunk sections that can't be seen from the camera's section
+    pub occlusion_culling: bool,
+
+    /// Outline the sections skipped by occlusion culling
+    pub show_occluded_sections: bool,
 }
 
 #[derive(Copy, Clone, Debug)]
+pub struct EditSettings
+{
+    /// Block id placed with the right mouse button
+    pub selected_block: u16,
+
+    /// How far away blocks can be broken or placed
+    pub reach: f32,
+
+    /// Set by the input processor, handled (and cleared) by the scene update
+    pub break_block: bool,
+    pub place_block: bool,
+
+    /// Select the next block in the block registry
+    pub next_block: bool,
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeshMode
+{
+    /// Draw a cube instance for every block that can be seen
+    INSTANCED,
+
+    /// Build one mesh per chunk from only the exposed faces
+    CULLED,
+
+    /// Like CULLED but faces of the same block type are merged into larger quads
+    GREEDY
+}
+
+impl MeshMode
+{
+    pub fn from_name(name: &str) -> Option<MeshMode>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "instanced" => Some(MeshMode::INSTANCED),
+            "culled" => Some(MeshMode::CULLED),
+            "greedy" => Some(MeshMode::GREEDY),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldSettings
+{
+    pub chunk_width: usize,
+    pub chunk_height: usize,
+    pub chunk_depth: usize,
+
+    /// Chunks within this many chunks of the camera are kept loaded
+    pub load_radius: i32,
+
+    /// Finished chunks uploaded to the GPU each frame
+    pub max_uploads_per_frame: usize,
+
+    /// Hide blocks on a chunk border that are covered by the next chunk. Chunks
+    /// are rebuilt when a neighbour loads in after them.
+    pub cull_chunk_borders: bool,
+    /// Directory for region files. Edited chunks are saved here when they are unloaded
+    /// Directory for region files. Chunks are saved here when they are unloaded
+    /// and loaded from here instead of being generated. None disables saving.
+    pub save_dir: Option<String>,
+
+    /// Keep loaded chunks palette compressed to save memory
+    pub compress_chunks: bool,
+
+    /// Number of chunk generation threads (0 uses one per core)
+    pub worker_threads: usize,
+
+    pub mesh_mode: MeshMode,
+
+    /// Distance (in blocks, from the camera to the nearest point of a chunk) at which
+    /// each level of detail starts. Entry i is where chunks switch to blocks 2^(i + 1)
+    /// wide, at most 3 levels are used. Empty turns level of detail off.
+    pub lod_distances: Vec<f32>,
+
+    /// How fast ANIMATED_3D noise moves through time (noise units per second) and the
+    /// most times per second the world is regenerated for it
+    pub animation_speed: f32,
+    pub animation_rate: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DisplaySettings
+{
+    pub window_width: u32,
+    pub window_height: u32,
+
+    /// Vertical field of view in degrees
+    pub fov: f32,
+
+    /// Far clipping plane, nothing further than this from the camera is drawn
+    pub view_distance: f32,
+}
+
+#[derive(Clone, Debug)]
 pub struct GameData
 {
     pub debug: DebugSettings,
-    pub chunk_generation: ChunkGeneration
+    pub chunk_generation: ChunkGeneration,
+    pub edit: EditSettings,
+    pub world: WorldSettings,
+    pub display: DisplaySettings
 }
 
-#[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum NoiseType
 {
     RANDOM_2D,
     RANDOM_3D,
     OLC,
     SIMPLEX_2D,
-    SIMPLEX_3D
+    SIMPLEX_3D,
+
+    /// Simplex noise layered by FractalNoise, as a height map and as density
+    FRACTAL_2D,
+    FRACTAL_3D,
+
+    /// Fractal noise sampled through a DomainWarp, as a height map and as density
+    WARPED_2D,
+    WARPED_3D,
+
+    /// 4D simplex noise density, the fourth coordinate is ChunkGeneration::time
+    ANIMATED_3D,
+
+    /// For generators registered outside of the game module
+    CUSTOM(u32)
+}
+
+impl NoiseType
+{
+    /// Looks up a built in noise type by its lower case name without underscores ("simplex2d")
+    pub fn from_name(name: &str) -> Option<NoiseType>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "random2d" => Some(NoiseType::RANDOM_2D),
+            "random3d" => Some(NoiseType::RANDOM_3D),
+            "olc" => Some(NoiseType::OLC),
+            "simplex2d" => Some(NoiseType::SIMPLEX_2D),
+            "simplex3d" => Some(NoiseType::SIMPLEX_3D),
+            "fractal2d" => Some(NoiseType::FRACTAL_2D),
+            "fractal3d" => Some(NoiseType::FRACTAL_3D),
+            "warped2d" => Some(NoiseType::WARPED_2D),
+            "warped3d" => Some(NoiseType::WARPED_3D),
+            "animated3d" => Some(NoiseType::ANIMATED_3D),
+            _ => None
+        }
+    }
 }
\ No newline at end of file
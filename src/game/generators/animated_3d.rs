@@ -0,0 +1,44 @@
+
+use crate::{ utils::SimplexNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks } } };
+
+/// Density from 4D simplex noise with settings.time as the fourth coordinate, so
+/// the terrain changes smoothly as the time moves. Uses the same rising threshold
+/// as Simplex3DGenerator.
+pub struct Animated3DGenerator
+{
+}
+
+impl ChunkGenerator for Animated3DGenerator
+{
+    fn generate(self: &Animated3DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let noise_machine = SimplexNoise::new(settings.seed);
+
+        for y in 0..chunk.height
+        {
+            // final = threshold + ((y^2) / 100) / falloff
+            let final_threshold = settings.threshold + ((y * y / 100) as f32 / settings.threshold_falloff as f32);
+
+            for x in 0..chunk.width
+            {
+                for z in 0..chunk.depth
+                {
+                    let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                    let yf = y as f32 * settings.zoom_factor;
+                    let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                    let noise_value = noise_machine.noise_4D(xf, yf, zf, settings.time, settings.sx_scale);
+                    let noise_value = (noise_value + 1.0) / 2.0;
+
+                    if noise_value >= final_threshold
+                    {
+                        chunk.set(x, y, z, terrain.layer(y));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+
+#![allow(non_snake_case)]
+use crate::{ utils::{ SimplexNoise, Noise, Noise2D, Noise3D, NoiseAlgorithm }, game::{ WorldChunk, ChunkGeneration, BlockRegistry } };
+
+/// Fills a chunk with blocks. Generators are shared with the chunk
+/// worker threads so they must be Send + Sync.
+pub trait ChunkGenerator: Send + Sync
+{
+    /// The origin is the world space block coordinate (x, z) of the
+    /// chunk's first column. Sample noise at (origin + local position)
+    /// so neighbouring chunks line up.
+    fn generate(&self, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry);
+}
+
+/// The blocks the generators build terrain out of, looked up by name in the
+/// block registry. Missing blocks are generated as air.
+pub struct TerrainBlocks
+{
+    pub stone: u16,
+    pub dirt: u16,
+    pub grass: u16,
+}
+
+impl TerrainBlocks
+{
+    pub fn new(blocks: &BlockRegistry) -> TerrainBlocks
+    {
+        TerrainBlocks { stone: blocks.id_of("stone").unwrap_or(0),
+                        dirt: blocks.id_of("dirt").unwrap_or(0),
+                        grass: blocks.id_of("grass").unwrap_or(0) }
+    }
+
+    /// Block for layer y: stone at the bottom, then dirt, then grass
+    pub fn layer(self: &TerrainBlocks, y: usize) -> u16
+    {
+        match y
+        {
+            0..=7 => self.stone,
+            8..=9 => self.dirt,
+            _ => self.grass
+        }
+    }
+}
+
+/// Mixes the chunk origin into the seed so the random generators
+/// don't produce the same chunk everywhere
+pub fn chunk_seed(seed: Option<[u8; 32]>, origin: (i32, i32)) -> [u8; 32]
+{
+    let mut seed = seed.unwrap_or_default();
+
+    let x = origin.0.to_le_bytes();
+    let z = origin.1.to_le_bytes();
+    for i in 0..4
+    {
+        seed[i] ^= x[i];
+        seed[i + 4] ^= z[i];
+    }
+
+    seed
+}
+
+/// Simplex noise sampled with the settings' sx_scale, as the generators did before
+/// the base noise could be chosen. Not clamped, so existing worlds generate the same.
+struct ScaledSimplexNoise
+{
+    noise: SimplexNoise,
+    scale: f32,
+}
+
+impl Noise2D for ScaledSimplexNoise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        self.noise.noise_2D(x, y, self.scale)
+    }
+}
+
+impl Noise3D for ScaledSimplexNoise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        self.noise.noise_3D(x, y, z, self.scale)
+    }
+}
+
+/// The noise the fractal and warped generators are built from (settings.base_noise)
+pub fn base_noise(settings: &ChunkGeneration) -> Box<dyn Noise>
+{
+    match settings.base_noise
+    {
+        NoiseAlgorithm::SIMPLEX => Box::new(ScaledSimplexNoise { noise: SimplexNoise::new(settings.seed), scale: settings.sx_scale }),
+        algorithm => algorithm.create(settings.seed)
+    }
+}
@@ -0,0 +1,39 @@
+
+use crate::{ utils::FractalNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks, base_noise } } };
+
+/// Height map from noise layered with FractalNoise. The chunk is solid (no caves).
+pub struct Fractal2DGenerator
+{
+}
+
+impl ChunkGenerator for Fractal2DGenerator
+{
+    fn generate(self: &Fractal2DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let noise_machine = base_noise(settings);
+        let fractal = FractalNoise::new(settings.fractal_mode, settings.octaves, settings.lacunarity, settings.gain);
+
+        for x in 0..chunk.width
+        {
+            for z in 0..chunk.depth
+            {
+                let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                let noise_value = fractal.sample_2D(xf, zf, |x, z| noise_machine.sample_2D(x, z));
+
+                // Scale from -1 to 1 to the height of the chunk
+                let height_scale = (noise_value + 1.0) / 2.0;
+                let final_height = ((height_scale * (chunk.height - 1) as f32) as usize).min(chunk.height - 1);
+
+                for y in 0..=final_height
+                {
+                    chunk.set(x, y, z, terrain.layer(y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+
+use crate::{ utils::FractalNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks, base_noise } } };
+
+/// Density from 3D noise layered with FractalNoise. Uses the same rising
+/// threshold as Simplex3DGenerator.
+pub struct Fractal3DGenerator
+{
+}
+
+impl ChunkGenerator for Fractal3DGenerator
+{
+    fn generate(self: &Fractal3DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let noise_machine = base_noise(settings);
+        let fractal = FractalNoise::new(settings.fractal_mode, settings.octaves, settings.lacunarity, settings.gain);
+
+        for y in 0..chunk.height
+        {
+            // final = threshold + ((y^2) / 100) / falloff
+            let final_threshold = settings.threshold + ((y * y / 100) as f32 / settings.threshold_falloff as f32);
+
+            for x in 0..chunk.width
+            {
+                for z in 0..chunk.depth
+                {
+                    let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                    let yf = y as f32 * settings.zoom_factor;
+                    let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                    let noise_value = fractal.sample_3D(xf, yf, zf, |x, y, z| noise_machine.sample_3D(x, y, z));
+                    let noise_value = (noise_value + 1.0) / 2.0;
+
+                    if noise_value >= final_threshold
+                    {
+                        chunk.set(x, y, z, terrain.layer(y));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+
+use std::{ collections::HashMap, sync::Arc };
+use crate::game::{ WorldChunk, ChunkGeneration, NoiseType, BlockRegistry };
+use crate::game::generators::{ ChunkGenerator, Random2DGenerator, Random3DGenerator, OlcGenerator, 
+                                Simplex2DGenerator, Simplex3DGenerator, Fractal2DGenerator, Fractal3DGenerator,
+                                Warped2DGenerator, Warped3DGenerator, Animated3DGenerator };
+
+/// Maps each NoiseType to the generator that builds chunks for it
+#[derive(Clone)]
+pub struct GeneratorRegistry
+{
+    generators: HashMap<NoiseType, Arc<dyn ChunkGenerator>>,
+}
+
+impl GeneratorRegistry
+{
+    /// Creates an empty registry
+    pub fn new() -> GeneratorRegistry
+    {
+        GeneratorRegistry { generators: HashMap::new() }
+    }
+
+    /// Creates a registry with the built in generators registered
+    pub fn with_defaults() -> GeneratorRegistry
+    {
+        let mut registry = GeneratorRegistry::new();
+        registry.register(NoiseType::RANDOM_2D, Random2DGenerator { });
+        registry.register(NoiseType::RANDOM_3D, Random3DGenerator { });
+        registry.register(NoiseType::OLC, OlcGenerator { });
+        registry.register(NoiseType::SIMPLEX_2D, Simplex2DGenerator { });
+        registry.register(NoiseType::SIMPLEX_3D, Simplex3DGenerator { });
+        registry.register(NoiseType::FRACTAL_2D, Fractal2DGenerator { });
+        registry.register(NoiseType::FRACTAL_3D, Fractal3DGenerator { });
+        registry.register(NoiseType::WARPED_2D, Warped2DGenerator { });
+        registry.register(NoiseType::WARPED_3D, Warped3DGenerator { });
+        registry.register(NoiseType::ANIMATED_3D, Animated3DGenerator { });
+
+        registry
+    }
+
+    /// Adds a generator, replacing any generator already registered for the noise type
+    pub fn register<G: ChunkGenerator + 'static>(self: &mut GeneratorRegistry, noise_type: NoiseType, generator: G)
+    {
+        self.generators.insert(noise_type, Arc::new(generator));
+    }
+
+    pub fn get(self: &GeneratorRegistry, noise_type: NoiseType) -> Option<Arc<dyn ChunkGenerator>>
+    {
+        self.generators.get(&noise_type).cloned()
+    }
+
+    pub fn contains(self: &GeneratorRegistry, noise_type: NoiseType) -> bool
+    {
+        self.generators.contains_key(&noise_type)
+    }
+
+    /// Generates the chunk with the generator registered for settings.noise_type.
+    /// Returns false (and leaves the chunk untouched) if there isn't one.
+    pub fn generate(self: &GeneratorRegistry, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry) -> bool
+    {
+        match self.generators.get(&settings.noise_type)
+        {
+            Some(generator) => 
+            {
+                generator.generate(chunk, origin, settings, blocks);
+                true
+            },
+            None => false
+        }
+    }
+}
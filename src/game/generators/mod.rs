@@ -0,0 +1,29 @@
+
+pub use self::chunk_generator::ChunkGenerator;
+pub use self::chunk_generator::chunk_seed;
+pub use self::chunk_generator::base_noise;
+pub use self::chunk_generator::TerrainBlocks;
+pub use self::generator_registry::GeneratorRegistry;
+pub use self::random_2d::Random2DGenerator;
+pub use self::random_3d::Random3DGenerator;
+pub use self::olc_2d::OlcGenerator;
+pub use self::simplex_2d::Simplex2DGenerator;
+pub use self::simplex_3d::Simplex3DGenerator;
+pub use self::fractal_2d::Fractal2DGenerator;
+pub use self::fractal_3d::Fractal3DGenerator;
+pub use self::warped_2d::Warped2DGenerator;
+pub use self::warped_3d::Warped3DGenerator;
+pub use self::animated_3d::Animated3DGenerator;
+
+mod chunk_generator;
+mod generator_registry;
+mod random_2d;
+mod random_3d;
+mod olc_2d;
+mod simplex_2d;
+mod simplex_3d;
+mod fractal_2d;
+mod fractal_3d;
+mod warped_2d;
+mod warped_3d;
+mod animated_3d;
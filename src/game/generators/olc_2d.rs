@@ -0,0 +1,57 @@
+
+use crate::{ utils::OlcNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks } } };
+
+/// Height map from OlcNoise (see utils/noise.rs)
+pub struct OlcGenerator
+{
+}
+
+impl ChunkGenerator for OlcGenerator
+{
+    fn generate(self: &OlcGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        //println!("Generating chunk from 2D noise");
+        //println!("Octaves: {}, Bias: {}", octaves, bias);
+        // Sample noise to generate a random chunk
+        // Limit height by requiring larger sample values for higher blocks
+
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        // The noise generator
+        let noise_machine = OlcNoise::new(32, settings.seed);
+
+        // Only testing 2D noise to start
+        // In this test the chunk will be solid (no caves)
+        // but will have variable height
+        
+        for x in 0..chunk.width
+        {
+            for z in 0..chunk.depth
+            {
+                // olc noise does not use x and y between 0 and 1
+               // let fx: f32 = (x as f32) / (chunk.width as f32);
+                //let fz: f32 = (z as f32) / (chunk.depth as f32);
+                let height_scale = noise_machine.sample2D((x as i32 + origin.0) as i64, (z as i32 + origin.1) as i64, settings.octaves, settings.bias);
+                //println!("Noise sample at ({}, {}): {}", x, z, height_scale);
+                
+                // use height_scale to lerp between 1 and the chunk height
+                // a + x * (b - a)
+                // Obviously can be simplified
+                let final_height = (1.0 + height_scale * ((chunk.height - 1) as f32)) as i32; 
+                //println!("height_scale: {} -- final_height: {}", height_scale, final_height);
+
+                // fill chunk column up to height
+                for i in 0..(final_height + 1)
+                {
+                    let value = terrain.layer(i as usize);
+
+                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
+                    chunk.set(x, i as usize, z, value);
+                }
+            }
+        }
+
+        // println!("\nNew chunk generated with OLC Noise:\nseed: {:?}\nnum octaves: {}, bias: {}", seed, octaves, bias);
+    }
+}
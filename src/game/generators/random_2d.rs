@@ -0,0 +1,45 @@
+
+use rand::{ Rng, rngs::StdRng, SeedableRng};
+use crate::game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ChunkGenerator, TerrainBlocks, chunk_seed} };
+
+/// Column heights picked straight from a seeded random number generator.
+/// Every column is independent so the terrain is very spiky.
+pub struct Random2DGenerator
+{
+}
+
+impl ChunkGenerator for Random2DGenerator
+{
+    fn generate(self: &Random2DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+        
+        let seed = chunk_seed(settings.seed, origin);
+
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        for x in 0..chunk.width
+        {
+            for z in 0..chunk.depth
+            {
+                let height_scale = rng.gen::<f32>();
+                //println!("Noise sample at ({}, {}): {}", x, z, height_scale);
+                
+                let final_height = (1.0 + height_scale * ((chunk.height - 1) as f32)) as i32; 
+                //println!("height_scale: {} -- final_height: {}", height_scale, final_height);
+
+                // fill chunk column up to height
+                for i in 0..(final_height + 1)
+                {
+                    let value = terrain.layer(i as usize);
+
+                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
+                    chunk.set(x, i as usize, z, value);
+                }
+            }
+        }
+
+        // println!("\nNew chunk generated with Random 2D Noise:\nseed: {:?}", seed);
+    }
+}
@@ -0,0 +1,42 @@
+
+use rand::{ Rng, rngs::StdRng, SeedableRng};
+use crate::game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ChunkGenerator, TerrainBlocks, chunk_seed} };
+
+/// Every block is placed if a random sample is above the threshold.
+pub struct Random3DGenerator
+{
+}
+
+impl ChunkGenerator for Random3DGenerator
+{
+    fn generate(self: &Random3DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let seed = chunk_seed(settings.seed, origin);
+
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        for y in 0..chunk.height
+        {
+            for x in 0..chunk.width
+            {
+                for z in 0..chunk.depth
+                {
+                    let noise_value = rng.gen::<f32>();
+
+                    let mut id = 0;
+
+                    if noise_value >= settings.threshold
+                    {
+                        id = terrain.layer(y);
+                    }
+
+                    chunk.set(x, y, z, id);
+                }
+            }
+        }
+
+        // println!("\nNew chunk generated with Random 2D Noise:\nseed: {:?}\nthreshold: {}", seed, threshold);
+    }
+}
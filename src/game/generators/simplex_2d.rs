@@ -0,0 +1,59 @@
+
+use crate::{ utils::SimplexNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks } } };
+
+/// Height map from 2D simplex noise. The chunk is solid (no caves).
+pub struct Simplex2DGenerator
+{
+}
+
+impl ChunkGenerator for Simplex2DGenerator
+{
+    fn generate(self: &Simplex2DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        // The noise generator
+        let noise_machine = SimplexNoise::new(settings.seed);
+
+        // Only testing 2D noise to start
+        // In this test the chunk will be solid (no caves)
+        // but will have variable height
+        for x in 0..chunk.width
+        {
+            for z in 0..chunk.depth
+            {
+                // Zoom into the noise by scaling down the x and z
+                // (or if zoom_factor is large than 1 it will scale up - resulting in chaotic noise)
+                let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                let height_scale = noise_machine.noise_2D(xf, zf, settings.sx_scale);
+                
+                
+                // Result of the noise is between -1 and 1. Need to scale it to be between
+                // 0 and 1:
+                // NewValue = (((OldValue - OldMin) * (NewMax - NewMin)) / (OldMax - OldMin)) + NewMin
+
+                //  or split into 3 lines:
+                // OldRange = (OldMax - OldMin)  
+                // NewRange = (NewMax - NewMin)  
+                // NewValue = (((OldValue - OldMin) * NewRange) / OldRange) + NewMin
+                let old_range = 2.0;
+                let new_range = 1.0;
+                let height_scale = ((height_scale + 1.0) * new_range) / old_range;
+                let final_height = ((1.0 + height_scale * ((chunk.height - 1) as f32)) as i32).min(chunk.height as i32 - 1);
+
+                // fill chunk column up to height
+                for i in 0..(final_height + 1)
+                {
+                    let value = terrain.layer(i as usize);
+
+                    // heigth - i is a hack to put the grass on the top and the stone on the bottom of the chunk
+                    chunk.set(x, i as usize, z, value);
+                }
+            }
+        }
+        // println!("\nNew chunk generated with Simplex Noise:\nSeed: {:?}\nZoom Factor: {}", seed, zoom_factor);
+    }
+}
@@ -0,0 +1,69 @@
+
+use crate::{ utils::SimplexNoise, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks } } };
+
+/// Density from 3D simplex noise. Blocks are placed where the noise is above
+/// a threshold that rises with height, so the terrain has caves and overhangs.
+pub struct Simplex3DGenerator
+{
+}
+
+impl ChunkGenerator for Simplex3DGenerator
+{
+    fn generate(self: &Simplex3DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        // The noise generator
+        let noise_machine = SimplexNoise::new(settings.seed);
+
+        // Only testing 2D noise to start
+        // In this test the chunk will be solid (no caves)
+        // but will have variable height
+        for y in 0..chunk.height
+        {
+            for x in 0..chunk.width
+            {
+                for z in 0..chunk.depth
+                {
+                    // Zoom into the noise by scaling down the x and z
+                    // (or if zoom_factor is large than 1 it will scale up - resulting in chaotic noise)
+                    let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                    let yf = y as f32 * settings.zoom_factor;
+                    let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                    let noise_value = noise_machine.noise_3D(xf, yf, zf, settings.sx_scale);
+                    
+                    
+                    // Result of the noise is between -1 and 1. Need to scale it to be between
+                    // 0 and 1:
+                    let old_range = 2.0;
+                    let new_range = 1.0;
+                    let noise_value = ((noise_value + 1.0) * new_range) / old_range;
+
+                    // use noise_value to decide on the block type
+                    // threshold will decide if the block should be created or not
+                    
+                    // Function to increase threshold as height increases
+                    // final = threshold + ((y^2) / 100) / falloff
+                    let sqy = (y*y) as i32;
+                    let sqy = sqy / 100;
+                    let final_threshold = settings.threshold + ((sqy as f32)/settings.threshold_falloff as f32);
+
+                    let mut id = 0;
+
+                    if noise_value >= final_threshold
+                    {
+                        id = terrain.layer(y);
+                    }
+
+                    chunk.set(x, y, z, id);
+                    
+                }
+            }
+        }
+        
+        // println!("\nNew chunk generated with Simplex Noise:\nSeed: {:?}\nZoom Factor: {}\nThreshold: {}\nThreshold Falloff: {}", 
+        //             seed, zoom_factor, threshold, threshold_falloff);
+    }
+}
@@ -0,0 +1,45 @@
+
+use crate::{ utils::{ FractalNoise, DomainWarp }, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks, base_noise } } };
+
+/// Height map from fractal noise sampled at domain warped positions.
+/// The chunk is solid (no caves).
+pub struct Warped2DGenerator
+{
+}
+
+impl ChunkGenerator for Warped2DGenerator
+{
+    fn generate(self: &Warped2DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let noise_machine = base_noise(settings);
+        let fractal = FractalNoise::new(settings.fractal_mode, settings.octaves, settings.lacunarity, settings.gain);
+        let warp = DomainWarp::new(settings.warp_strength, settings.warp_frequency, settings.double_warp);
+
+        // The warp is a single octave, clamped so it never moves further than the strength
+        let warp_noise = |x, z| noise_machine.sample_2D(x, z).clamp(-1.0, 1.0);
+
+        for x in 0..chunk.width
+        {
+            for z in 0..chunk.depth
+            {
+                let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                let (xf, zf) = warp.warp_2D(xf, zf, warp_noise);
+                let noise_value = fractal.sample_2D(xf, zf, |x, z| noise_machine.sample_2D(x, z));
+
+                // Scale from -1 to 1 to the height of the chunk
+                let height_scale = (noise_value + 1.0) / 2.0;
+                let final_height = ((height_scale * (chunk.height - 1) as f32) as usize).min(chunk.height - 1);
+
+                for y in 0..=final_height
+                {
+                    chunk.set(x, y, z, terrain.layer(y));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+
+use crate::{ utils::{ FractalNoise, DomainWarp }, game::{ WorldChunk, ChunkGeneration, BlockRegistry, generators::{ ChunkGenerator, TerrainBlocks, base_noise } } };
+
+/// Density from fractal 3D noise sampled at domain warped positions.
+/// Uses the same rising threshold as Simplex3DGenerator.
+pub struct Warped3DGenerator
+{
+}
+
+impl ChunkGenerator for Warped3DGenerator
+{
+    fn generate(self: &Warped3DGenerator, chunk: &mut WorldChunk, origin: (i32, i32), settings: &ChunkGeneration, blocks: &BlockRegistry)
+    {
+        chunk.make_empty();
+        let terrain = TerrainBlocks::new(blocks);
+
+        let noise_machine = base_noise(settings);
+        let fractal = FractalNoise::new(settings.fractal_mode, settings.octaves, settings.lacunarity, settings.gain);
+        let warp = DomainWarp::new(settings.warp_strength, settings.warp_frequency, settings.double_warp);
+        let warp_noise = |x, y, z| noise_machine.sample_3D(x, y, z).clamp(-1.0, 1.0);
+
+        for y in 0..chunk.height
+        {
+            // final = threshold + ((y^2) / 100) / falloff
+            let final_threshold = settings.threshold + ((y * y / 100) as f32 / settings.threshold_falloff as f32);
+
+            for x in 0..chunk.width
+            {
+                for z in 0..chunk.depth
+                {
+                    let xf = ((x as i32 + origin.0) as f32 + settings.offset.0) * settings.zoom_factor;
+                    let yf = y as f32 * settings.zoom_factor;
+                    let zf = ((z as i32 + origin.1) as f32 + settings.offset.1) * settings.zoom_factor;
+
+                    let (xf, yf, zf) = warp.warp_3D(xf, yf, zf, warp_noise);
+                    let noise_value = fractal.sample_3D(xf, yf, zf, |x, y, z| noise_machine.sample_3D(x, y, z));
+                    let noise_value = (noise_value + 1.0) / 2.0;
+
+                    if noise_value >= final_threshold
+                    {
+                        chunk.set(x, y, z, terrain.layer(y));
+                    }
+                }
+            }
+        }
+    }
+}
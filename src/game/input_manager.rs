@@ -1,39 +1,99 @@
-
-use crate::win_input::{KeyBoard, KeyCode};
+use glium::glutin;
+use crate::graphics::{Gl, WindowInfo};
+use crate::input::{InputBackend, KeyCode, MouseState, MouseButton};
 
 #[allow(dead_code)]
 pub struct InputManager
 {
+    backend: Box<dyn InputBackend>,
     key_states: [bool; 256],
+    mouse_button_states: [bool; 3],
 }
 
 impl InputManager
 {
-    pub fn new() -> InputManager
+    /// Creates an InputManager using the default backend for this platform
+    pub fn new(gl: &Gl) -> InputManager
+    {
+        InputManager::with_backend(InputManager::default_backend(gl))
+    }
+
+    pub fn with_backend(backend: Box<dyn InputBackend>) -> InputManager
     {
-        InputManager { key_states: [false; 256] }
+        InputManager { backend, key_states: [false; 256], mouse_button_states: [false; 3] }
+    }
+
+    #[cfg(windows)]
+    fn default_backend(_gl: &Gl) -> Box<dyn InputBackend>
+    {
+        Box::new(crate::input::WinInput::new())
+    }
+
+    #[cfg(not(windows))]
+    fn default_backend(gl: &Gl) -> Box<dyn InputBackend>
+    {
+        Box::new(crate::input::GlutinInput::new(gl))
+    }
+
+    /// Must be called with every event from the events loop
+    pub fn process_event(self: &mut InputManager, event: &glutin::Event)
+    {
+        self.backend.process_event(event);
     }
 
     pub fn key_down(self: &InputManager, key: KeyCode) -> bool
     {
-        KeyBoard::key_down(key)
+        self.backend.key_down(key)
     }
 
 
     pub fn key_pressed(self: &mut InputManager, key: KeyCode) -> bool
     {
-        let down_now = KeyBoard::key_down(key);
-        if down_now && self.key_states[key as usize] == false
+        let down_now = self.backend.key_down(key);
+        if down_now && !self.key_states[key as usize]
         {
             self.key_states[key as usize] = true;
             return true;
         }
-        
+
         if !down_now
         {
             self.key_states[key as usize] = false;
         }
 
-        return false;
+        false
+    }
+
+    /// True only on the frame the button goes down, like key_pressed
+    pub fn mouse_pressed(self: &mut InputManager, button: MouseButton) -> bool
+    {
+        let mouse_state = self.backend.get_mouse_state();
+        let (down_now, index) = match button
+        {
+            MouseButton::LEFT => (mouse_state.left_button, 0),
+            MouseButton::RIGHT => (mouse_state.right_button, 1),
+            MouseButton::MIDDLE => (mouse_state.middle_button, 2),
+        };
+
+        let pressed = down_now && !self.mouse_button_states[index];
+        self.mouse_button_states[index] = down_now;
+
+        pressed
+    }
+
+    pub fn get_mouse_state(self: &InputManager) -> MouseState
+    {
+        self.backend.get_mouse_state()
     }
-}
\ No newline at end of file
+
+    /// Returns the mouse movement since the last call and re-centers the cursor
+    pub fn take_look_delta(self: &mut InputManager, window_info: &WindowInfo) -> (f32, f32)
+    {
+        self.backend.take_look_delta(window_info)
+    }
+
+    pub fn center_cursor(self: &mut InputManager, window_info: &WindowInfo)
+    {
+        self.backend.center_cursor(window_info);
+    }
+}
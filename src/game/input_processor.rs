@@ -1,9 +1,8 @@
 
 use crate::graphics::{WindowInfo, CameraFPS};
-use crate::game::{GameData, NoiseType, InputManager};
+use crate::game::{GameData, NoiseType, MeshMode, InputManager};
 
-#[cfg(windows)]
-use crate::win_input::{KeyCode, Mouse};
+use crate::input::{KeyCode, MouseButton};
 
 pub struct InputProcessor
 {
@@ -23,26 +22,30 @@ impl InputProcessor
     pub fn process_input_debug(dt: f64, cam: &mut CameraFPS, window_info: &WindowInfo, input_manager: &mut InputManager, game_data: &mut GameData) -> bool
     {
         // Handle Mouse Movement
-        let mouse_state = Mouse::get_state();
+        // Looking isn't paused while a button is held since the left button breaks blocks
+        let (delta_x, delta_y) = input_manager.take_look_delta(window_info);
+        cam.apply_look_offset(delta_x, delta_y);
 
-        // TODO: Need a way to check if the button was just released
-        //       so we can reset the mouse position and not use
-        //       its offsets
-        if !mouse_state.left_button
+        // Block editing, done in the scene update where the world is
+        if input_manager.mouse_pressed(MouseButton::LEFT)
         {
-            let delta_x = window_info.center.x - mouse_state.coords.x;
-            let delta_y = window_info.center.y - mouse_state.coords.y;
+            game_data.edit.break_block = true;
+        }
 
-            cam.apply_look_offset(delta_x as f32, delta_y as f32);
-        
-            Mouse::set_position(window_info.center.x, window_info.center.y);
+        if input_manager.mouse_pressed(MouseButton::RIGHT)
+        {
+            game_data.edit.place_block = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::B)
+        {
+            game_data.edit.next_block = true;
         }
-        //  
 
         let mut speed = 35.0 * dt as f32;
         if input_manager.key_down(KeyCode::LSHIFT)
         {
-            speed = speed * 2.0;
+            speed *= 2.0;
         }
 
         // reprint help messsage
@@ -57,6 +60,28 @@ impl InputProcessor
             game_data.debug.print_chunk_info = !game_data.debug.print_chunk_info;
         }
 
+        // chunk mesh mode
+        if input_manager.key_pressed(KeyCode::F3)
+        {
+            game_data.world.mesh_mode = match game_data.world.mesh_mode
+            {
+                MeshMode::INSTANCED => MeshMode::CULLED,
+                MeshMode::CULLED => MeshMode::GREEDY,
+                MeshMode::GREEDY => MeshMode::INSTANCED,
+            };
+        }
+
+        // occlusion culling
+        if input_manager.key_pressed(KeyCode::F4)
+        {
+            game_data.debug.occlusion_culling = !game_data.debug.occlusion_culling;
+        }
+
+        if input_manager.key_pressed(KeyCode::F5)
+        {
+            game_data.debug.show_occluded_sections = !game_data.debug.show_occluded_sections;
+        }
+
         // Camera movement
         if input_manager.key_down(KeyCode::Escape)
         {
@@ -93,8 +118,14 @@ impl InputProcessor
             cam.move_up(-speed);
         }
 
+        let noise_type = game_data.chunk_generation.noise_type;
+        let warped = noise_type == NoiseType::WARPED_2D || noise_type == NoiseType::WARPED_3D;
+        let fractal = noise_type == NoiseType::FRACTAL_2D || noise_type == NoiseType::FRACTAL_3D || warped;
+        let animated = noise_type == NoiseType::ANIMATED_3D;
+        let density = noise_type == NoiseType::FRACTAL_3D || noise_type == NoiseType::WARPED_3D || animated;
+
         // Octaves
-        if input_manager.key_pressed(KeyCode::SPACE) && game_data.chunk_generation.noise_type == NoiseType::OLC
+        if input_manager.key_pressed(KeyCode::SPACE) && (noise_type == NoiseType::OLC || fractal)
         {
             game_data.chunk_generation.octaves += 1;
             if game_data.chunk_generation.octaves > 6
@@ -109,9 +140,9 @@ impl InputProcessor
         if input_manager.key_pressed(KeyCode::C)
         {
             let mut seed: [u8; 32] = [0; 32];
-            for i in 0..32
+            for byte in seed.iter_mut()
             {
-                seed[i] = rand::random::<u8>();
+                *byte = rand::random::<u8>();
             }
             
             game_data.chunk_generation.seed = Some(seed);
@@ -140,6 +171,11 @@ impl InputProcessor
 
                 NoiseType::SIMPLEX_2D => { game_data.chunk_generation.zoom_factor += zoom_speed },
                 NoiseType::SIMPLEX_3D => { game_data.chunk_generation.zoom_factor += zoom_speed },
+                NoiseType::FRACTAL_2D => { game_data.chunk_generation.zoom_factor += zoom_speed },
+                NoiseType::FRACTAL_3D => { game_data.chunk_generation.zoom_factor += zoom_speed },
+                NoiseType::WARPED_2D => { game_data.chunk_generation.zoom_factor += zoom_speed },
+                NoiseType::WARPED_3D => { game_data.chunk_generation.zoom_factor += zoom_speed },
+                NoiseType::ANIMATED_3D => { game_data.chunk_generation.zoom_factor += zoom_speed },
                 
                 NoiseType::OLC =>
                 {
@@ -182,6 +218,11 @@ impl InputProcessor
 
                 NoiseType::SIMPLEX_2D => dec_zoom(),
                 NoiseType::SIMPLEX_3D => dec_zoom(),
+                NoiseType::FRACTAL_2D => dec_zoom(),
+                NoiseType::FRACTAL_3D => dec_zoom(),
+                NoiseType::WARPED_2D => dec_zoom(),
+                NoiseType::WARPED_3D => dec_zoom(),
+                NoiseType::ANIMATED_3D => dec_zoom(),
 
                 _ => ()
             };
@@ -198,14 +239,14 @@ impl InputProcessor
         };
 
         if input_manager.key_down(KeyCode::Z) && (game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_2D
-                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D)
+                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D || fractal || animated)
         {
             game_data.chunk_generation.sx_scale += sx_speed;
             game_data.debug.remake_test_scene = true;
         }
 
         if input_manager.key_down(KeyCode::X) && (game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_2D
-                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D)
+                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D || fractal || animated)
         {
             game_data.chunk_generation.sx_scale -= sx_speed;
             game_data.debug.remake_test_scene = true;
@@ -219,14 +260,16 @@ impl InputProcessor
         };
         
         if input_manager.key_down(KeyCode::T) && (game_data.chunk_generation.noise_type == NoiseType::RANDOM_3D
-                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D)
+                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+                                            || density)
         {
             game_data.chunk_generation.threshold += t_speed;
             game_data.debug.remake_test_scene = true;
         }
 
         if input_manager.key_down(KeyCode::G) && (game_data.chunk_generation.noise_type == NoiseType::RANDOM_3D
-                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D)
+                                            || game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+                                            || density)
         {
             game_data.chunk_generation.threshold -= t_speed;
             if game_data.chunk_generation.threshold < 0.0
@@ -243,14 +286,16 @@ impl InputProcessor
             true => (250.0 * dt as f32) as i32,
             false => (100.0 * dt as f32) as i32,
         };
-        if input_manager.key_down(KeyCode::Y) && game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+        if input_manager.key_down(KeyCode::Y) && (game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+                                            || density)
         {
         // println!("ft_speed: {}", ft_speed);
             game_data.chunk_generation.threshold_falloff += ft_speed;
             game_data.debug.remake_test_scene = true;
         }
 
-        if input_manager.key_down(KeyCode::H) && game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+        if input_manager.key_down(KeyCode::H) && (game_data.chunk_generation.noise_type == NoiseType::SIMPLEX_3D
+                                            || density)
         {
             game_data.chunk_generation.threshold_falloff -= ft_speed;
 
@@ -262,6 +307,105 @@ impl InputProcessor
             game_data.debug.remake_test_scene = true;
         }
 
+        // Fractal settings
+        if input_manager.key_pressed(KeyCode::M) && fractal
+        {
+            game_data.chunk_generation.fractal_mode = game_data.chunk_generation.fractal_mode.next();
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::F7) && fractal
+        {
+            game_data.chunk_generation.base_noise = game_data.chunk_generation.base_noise.next();
+            game_data.debug.remake_test_scene = true;
+        }
+
+        let fractal_speed = match input_manager.key_down(KeyCode::LSHIFT)
+        {
+            true => 1.0 * dt as f32,
+            false => 0.25 * dt as f32,
+        };
+
+        if input_manager.key_down(KeyCode::I) && fractal
+        {
+            game_data.chunk_generation.lacunarity += fractal_speed;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::K) && fractal
+        {
+            game_data.chunk_generation.lacunarity -= fractal_speed;
+            if game_data.chunk_generation.lacunarity < 1.0
+            {
+                game_data.chunk_generation.lacunarity = 1.0;
+            }
+
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::O) && fractal
+        {
+            game_data.chunk_generation.gain += fractal_speed;
+            if game_data.chunk_generation.gain > 1.0
+            {
+                game_data.chunk_generation.gain = 1.0;
+            }
+
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::L) && fractal
+        {
+            game_data.chunk_generation.gain -= fractal_speed;
+            if game_data.chunk_generation.gain < 0.05
+            {
+                game_data.chunk_generation.gain = 0.05;
+            }
+
+            game_data.debug.remake_test_scene = true;
+        }
+
+        // Domain warp settings
+        if input_manager.key_pressed(KeyCode::F6) && warped
+        {
+            game_data.chunk_generation.double_warp = !game_data.chunk_generation.double_warp;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::U) && warped
+        {
+            game_data.chunk_generation.warp_strength += fractal_speed;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::J) && warped
+        {
+            game_data.chunk_generation.warp_strength -= fractal_speed;
+            if game_data.chunk_generation.warp_strength < 0.0
+            {
+                game_data.chunk_generation.warp_strength = 0.0;
+            }
+
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::P) && warped
+        {
+            game_data.chunk_generation.warp_frequency += fractal_speed;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_down(KeyCode::N) && warped
+        {
+            game_data.chunk_generation.warp_frequency -= fractal_speed;
+            if game_data.chunk_generation.warp_frequency < 0.05
+            {
+                game_data.chunk_generation.warp_frequency = 0.05;
+            }
+
+            game_data.debug.remake_test_scene = true;
+        }
+
         // Noise Offsets Z
         let ot_speed = match input_manager.key_down(KeyCode::LSHIFT)
         {
@@ -324,6 +468,36 @@ impl InputProcessor
             game_data.debug.remake_test_scene = true;
         }
 
-        return false;
+        if input_manager.key_pressed(KeyCode::NUM6)
+        {
+            game_data.chunk_generation.noise_type = NoiseType::FRACTAL_2D;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::NUM7)
+        {
+            game_data.chunk_generation.noise_type = NoiseType::FRACTAL_3D;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::NUM8)
+        {
+            game_data.chunk_generation.noise_type = NoiseType::WARPED_2D;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::NUM9)
+        {
+            game_data.chunk_generation.noise_type = NoiseType::WARPED_3D;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        if input_manager.key_pressed(KeyCode::NUM0)
+        {
+            game_data.chunk_generation.noise_type = NoiseType::ANIMATED_3D;
+            game_data.debug.remake_test_scene = true;
+        }
+
+        false
     }
 }
\ No newline at end of file
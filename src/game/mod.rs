@@ -1,9 +1,14 @@
 
 #![allow(dead_code)]
 
+#[allow(unused_imports)]
 pub use self::object_demo_scene::ObjectDemoScene;
 pub use self::chunk_demo_scene::ChunkDemoScene;
-pub use self::world_chunk::WorldChunk;
+pub use self::world_chunk::{WorldChunk, ChunkNeighbors};
+pub use self::world::World;
+pub use self::world::ChunkCoord;
+pub use self::raycast::RayHit;
+pub use self::block_registry::BlockRegistry;
 pub use self::asset_lib::AssetLib;
 pub use self::input_manager::InputManager;
 pub use self::input_processor::InputProcessor;
@@ -11,12 +16,28 @@ pub use self::game_data::GameData;
 pub use self::game_data::DebugSettings;
 pub use self::game_data::ChunkGeneration;
 pub use self::game_data::NoiseType;
+pub use self::game_data::WorldSettings;
+pub use self::game_data::MeshMode;
+pub use self::game_data::EditSettings;
+pub use self::game_data::DisplaySettings;
+pub use self::noise_export::ExportSettings;
 
 mod game_data;
+pub mod game_config;
 mod object_demo_scene;
 mod chunk_demo_scene;
 mod world_chunk;
+mod world;
+mod chunk_workers;
+mod chunk_mesher;
+mod chunk_storage;
+mod region_store;
+mod raycast;
+mod section_visibility;
+pub mod noise_export;
+mod block_registry;
 mod asset_lib;
 mod input_manager;
 mod input_processor;
+pub mod generators;
 
@@ -0,0 +1,354 @@
+
+use std::path::Path;
+use image::{ GrayImage, RgbImage, Luma, Rgb };
+use crate::game::{ GameData, WorldChunk, ChunkGeneration, NoiseType, BlockRegistry, generators::GeneratorRegistry,
+                    game_config::{ self, parse_value, parse_list, parse_pair, option_section } };
+
+/// Colours for block ids in slice images, air is black. Ids past the end wrap around.
+const SLICE_COLORS: [[u8; 3]; 8] = [[96, 160, 64], [134, 96, 67], [128, 128, 128], [200, 180, 120],
+                                    [64, 96, 200], [220, 220, 220], [160, 64, 64], [96, 64, 160]];
+
+/// What to generate and where to write it for the headless export mode
+#[derive(Clone, Debug)]
+pub struct ExportSettings
+{
+    pub generation: ChunkGeneration,
+
+    /// Size of the chunks the area is generated in. The random generators seed
+    /// each chunk separately so this should match the world's chunk size.
+    pub chunk_size: (usize, usize, usize),
+
+    /// Block coordinate (x, z) of the first column and the size (x, z) of the exported area
+    pub origin: (i32, i32),
+    pub size: (usize, usize),
+
+    /// Layers written as slice images
+    pub slices: Vec<usize>,
+
+    pub out_dir: String,
+
+    /// Start of every file name, defaults to the noise type
+    pub prefix: String,
+}
+
+impl ExportSettings
+{
+    pub fn new(generation: ChunkGeneration, chunk_size: (usize, usize, usize)) -> ExportSettings
+    {
+        ExportSettings { generation, chunk_size, origin: (0, 0), size: (chunk_size.0, chunk_size.2), slices: vec![],
+                            out_dir: String::from("export"), prefix: noise_name(generation.noise_type) }
+    }
+
+    /// Reads the export options. Generation options use the game's names and are set on
+    /// game_data, which is checked once everything is applied. Anything not given keeps its current value.
+    pub fn parse_args(self: &mut ExportSettings, game_data: &mut GameData, args: &[String]) -> Result<(), String>
+    {
+        let mut prefix_set = false;
+        let mut i = 0;
+        while i < args.len()
+        {
+            let flag = args[i].as_str();
+            let value = match args.get(i + 1)
+            {
+                Some(v) => v.as_str(),
+                None => return Err(format!("Missing value for {}", flag))
+            };
+
+            match flag
+            {
+                "--time" => self.generation.time = parse_value(flag, value)?,
+                "--chunk" =>
+                {
+                    let size: Vec<usize> = parse_list(flag, value)?;
+                    if size.len() != 3
+                    {
+                        return Err(format!("{} takes width,height,depth: {}", flag, value));
+                    }
+                    self.chunk_size = (size[0], size[1], size[2]);
+                },
+                "--origin" => self.origin = parse_pair(flag, value)?,
+                "--size" => self.size = parse_pair(flag, value)?,
+                "--slices" => self.slices = parse_list(flag, value)?,
+                "--out" => self.out_dir = String::from(value),
+                "--prefix" =>
+                {
+                    self.prefix = String::from(value);
+                    prefix_set = true;
+                },
+                _ => match flag.strip_prefix("--")
+                {
+                    Some(key) if option_section(key) == Some("generation") => game_data.set_option(key, value)?,
+                    _ => return Err(format!("Unknown export option: {}", flag))
+                }
+            }
+
+            i += 2;
+        }
+
+        game_data.validate()?;
+        self.generation = ChunkGeneration { time: self.generation.time, ..game_data.chunk_generation };
+
+        if !prefix_set
+        {
+            self.prefix = noise_name(self.generation.noise_type);
+        }
+
+        if self.chunk_size.0 == 0 || self.chunk_size.1 == 0 || self.chunk_size.2 == 0 || self.size.0 == 0 || self.size.1 == 0
+        {
+            return Err(String::from("Chunk and export sizes must be larger than 0"));
+        }
+
+        if let Some(y) = self.slices.iter().find(|y| **y >= self.chunk_size.1)
+        {
+            return Err(format!("Slice {} is above the top of the chunk (height {})", y, self.chunk_size.1));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn print_export_usage()
+{
+    println!("Usage: block_world export [--config file] [options]");
+    println!("\n[generation] options not given are read from the config file:");
+    game_config::print_options("generation");
+    println!("\nExport options:");
+    println!("\t--time <f32>\n\t--chunk <width,height,depth>\n\t--origin <x,z>\n\t--size <x,z>\n\t--slices <y,y,...>");
+    println!("\t--out <directory>\n\t--prefix <file name prefix>");
+}
+
+/// Lower case name of the noise type, as used on the command line
+pub fn noise_name(noise_type: NoiseType) -> String
+{
+    match noise_type
+    {
+        NoiseType::CUSTOM(id) => format!("custom{}", id),
+        _ => format!("{:?}", noise_type).replace('_', "").to_lowercase()
+    }
+}
+
+/// Generates the area chunk by chunk, the same way the world does, and copies it
+/// into a single chunk the size of the area
+pub fn generate_area(settings: &ExportSettings, generators: &GeneratorRegistry, blocks: &BlockRegistry) -> Result<WorldChunk, String>
+{
+    let (cw, ch, cd) = settings.chunk_size;
+    let mut area = WorldChunk::new(settings.size.0, ch, settings.size.1);
+    let mut chunk = WorldChunk::new(cw, ch, cd);
+
+    // First chunk that overlaps the area, rounding down for negative coordinates
+    let first = (settings.origin.0.div_euclid(cw as i32), settings.origin.1.div_euclid(cd as i32));
+    let last = ((settings.origin.0 + settings.size.0 as i32 - 1).div_euclid(cw as i32),
+                (settings.origin.1 + settings.size.1 as i32 - 1).div_euclid(cd as i32));
+
+    for cx in first.0..=last.0
+    {
+        for cz in first.1..=last.1
+        {
+            let origin = (cx * cw as i32, cz * cd as i32);
+            if !generators.generate(&mut chunk, origin, &settings.generation, blocks)
+            {
+                return Err(format!("No generator registered for {:?}", settings.generation.noise_type));
+            }
+
+            for x in 0..cw
+            {
+                for z in 0..cd
+                {
+                    let ax = origin.0 + x as i32 - settings.origin.0;
+                    let az = origin.1 + z as i32 - settings.origin.1;
+                    if ax < 0 || az < 0 || ax >= settings.size.0 as i32 || az >= settings.size.1 as i32
+                    {
+                        continue;
+                    }
+
+                    for y in 0..ch
+                    {
+                        area.set(ax as usize, y, az as usize, chunk.get(x, y, z));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(area)
+}
+
+/// Height of the highest block in each column, scaled so the top of the chunk is white.
+/// Empty columns are black.
+pub fn heightmap_image(chunk: &WorldChunk) -> GrayImage
+{
+    let mut image = GrayImage::new(chunk.width as u32, chunk.depth as u32);
+    for (x, z, column) in chunk.columns()
+    {
+        let height = column.enumerate().filter(|(_, id)| *id != 0).map(|(y, _)| y + 1).max().unwrap_or(0);
+        image.put_pixel(x as u32, z as u32, Luma([(height * 255 / chunk.height) as u8]));
+    }
+
+    image
+}
+
+/// Fraction of each column that is filled, shows caves that the heightmap hides
+pub fn density_image(chunk: &WorldChunk) -> GrayImage
+{
+    let mut image = GrayImage::new(chunk.width as u32, chunk.depth as u32);
+    for (x, z, column) in chunk.columns()
+    {
+        let filled = column.filter(|id| *id != 0).count();
+        image.put_pixel(x as u32, z as u32, Luma([(filled * 255 / chunk.height) as u8]));
+    }
+
+    image
+}
+
+/// One layer of the chunk coloured by block id
+pub fn slice_image(chunk: &WorldChunk, y: usize) -> RgbImage
+{
+    let mut image = RgbImage::new(chunk.width as u32, chunk.depth as u32);
+    for x in 0..chunk.width
+    {
+        for z in 0..chunk.depth
+        {
+            let color = match chunk.get(x, y, z)
+            {
+                0 => [0, 0, 0],
+                id => SLICE_COLORS[(id as usize - 1) % SLICE_COLORS.len()]
+            };
+            image.put_pixel(x as u32, z as u32, Rgb(color));
+        }
+    }
+
+    image
+}
+
+/// Generates the area and writes the heightmap, density and slice images.
+/// Returns the files written.
+pub fn run_export(settings: &ExportSettings, generators: &GeneratorRegistry, blocks: &BlockRegistry) -> Result<Vec<String>, String>
+{
+    let area = generate_area(settings, generators, blocks)?;
+
+    if let Err(e) = std::fs::create_dir_all(&settings.out_dir)
+    {
+        return Err(format!("Error Creating Export Directory! Directory: {}, Error: {:?}", settings.out_dir, e));
+    }
+
+    let mut written = vec![];
+    let mut save = |name: String, result: std::io::Result<()>| -> Result<(), String>
+    {
+        match result
+        {
+            Ok(_) =>
+            {
+                written.push(name);
+                Ok(())
+            },
+            Err(e) => Err(format!("Error Saving Export Image! File: {}, Error: {:?}", name, e))
+        }
+    };
+
+    let path = |name: &str| Path::new(&settings.out_dir).join(format!("{}_{}.png", settings.prefix, name)).to_string_lossy().into_owned();
+
+    let file = path("heightmap");
+    save(file.clone(), heightmap_image(&area).save(&file))?;
+
+    let file = path("density");
+    save(file.clone(), density_image(&area).save(&file))?;
+
+    for y in settings.slices.iter()
+    {
+        let file = path(&format!("slice_{}", y));
+        save(file.clone(), slice_image(&area, *y).save(&file))?;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::game::game_config::tests::game_data;
+
+    fn settings() -> ExportSettings
+    {
+        ExportSettings::new(game_data().chunk_generation, (16, 32, 16))
+    }
+
+    fn args(list: &[&str]) -> Vec<String>
+    {
+        list.iter().map(|a| String::from(*a)).collect()
+    }
+
+    #[test]
+    fn parses_options()
+    {
+        let mut data = game_data();
+        let mut settings = settings();
+        settings.parse_args(&mut data, &args(&["--noise_type", "simplex3d", "--zoom_factor", "0.05", "--seed", "7", "--size", "40,20",
+                                                "--origin", "-8,4", "--slices", "0,12"])).unwrap();
+
+        assert_eq!(settings.generation.noise_type, NoiseType::SIMPLEX_3D);
+        assert_eq!(settings.generation.zoom_factor, 0.05);
+        assert_eq!(settings.generation.seed.unwrap()[0], 7);
+        assert_eq!(settings.size, (40, 20));
+        assert_eq!(settings.origin, (-8, 4));
+        assert_eq!(settings.slices, vec![0, 12]);
+        assert_eq!(settings.prefix, "simplex3d");
+
+        assert!(settings.parse_args(&mut data, &args(&["--zoom_factor", "fast"])).is_err());
+        assert!(settings.parse_args(&mut data, &args(&["--noise_type", "perlin"])).is_err());
+        assert!(settings.parse_args(&mut data, &args(&["--slices", "40"])).is_err());
+        assert!(settings.parse_args(&mut data, &args(&["--size"])).is_err());
+        assert!(settings.parse_args(&mut data, &args(&["--load_radius", "2"])).is_err());
+    }
+
+    #[test]
+    fn invalid_generation_settings_are_rejected()
+    {
+        assert!(settings().parse_args(&mut game_data(), &args(&["--zoom_factor", "0"])).is_err());
+        assert!(settings().parse_args(&mut game_data(), &args(&["--bias", "0", "--gain", "5", "--lacunarity", "0.1"])).is_err());
+        assert!(settings().parse_args(&mut game_data(), &args(&["--zoom", "0.05"])).is_err());
+    }
+
+    #[test]
+    fn area_matches_the_world_chunks()
+    {
+        let blocks = BlockRegistry::load("assets/blocks.cfg").unwrap();
+        let generators = GeneratorRegistry::with_defaults();
+
+        // An area that straddles four chunks, starting in a negative chunk
+        let mut settings = settings();
+        settings.origin = (-4, -4);
+        settings.size = (24, 24);
+        let area = generate_area(&settings, &generators, &blocks).unwrap();
+
+        let mut chunk = WorldChunk::new(16, 32, 16);
+        generators.generate(&mut chunk, (0, 0), &settings.generation, &blocks);
+        for y in 0..32
+        {
+            assert_eq!(area.get(4, y, 4), chunk.get(0, y, 0));
+            assert_eq!(area.get(19, y, 10), chunk.get(15, y, 6));
+        }
+
+        let heightmap = heightmap_image(&area);
+        assert_eq!(heightmap.dimensions(), (24, 24));
+        let height = (0..32).filter(|y| chunk.get(0, *y, 0) != 0).max().map(|y| y + 1).unwrap_or(0);
+        assert_eq!(heightmap.get_pixel(4, 4)[0] as usize, height * 255 / 32);
+    }
+
+    #[test]
+    fn animated_noise_moves_with_time()
+    {
+        let blocks = BlockRegistry::load("assets/blocks.cfg").unwrap();
+        let generators = GeneratorRegistry::with_defaults();
+
+        let mut data = game_data();
+        let mut settings = settings();
+        settings.parse_args(&mut data, &args(&["--noise_type", "animated3d", "--zoom_factor", "0.05", "--size", "16,16"])).unwrap();
+        let start = generate_area(&settings, &generators, &blocks).unwrap();
+        assert!(start.layers().zip(generate_area(&settings, &generators, &blocks).unwrap().layers()).all(|(a, b)| a.eq(b)));
+
+        settings.parse_args(&mut data, &args(&["--time", "0.5"])).unwrap();
+        let later = generate_area(&settings, &generators, &blocks).unwrap();
+        assert!(start.layers().zip(later.layers()).any(|(a, b)| !a.eq(b)));
+    }
+}
@@ -34,7 +34,7 @@ impl ObjectDemoScene
         let dirt_transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(3.0, 1.0, 0.0));
         let stone_transform = glm::translate(&glm::Mat4::identity(), &glm::Vec3::new(-3.0, 1.0, 0.0));
 
-        let mut grid = GridPlane::new(&display, [0.75, 0.75, 0.75], 1.0, 20, 20).unwrap();
+        let mut grid = GridPlane::new(display, [0.75, 0.75, 0.75], 1.0, 20, 20).unwrap();
         grid.projection = *perspective;
 
         Ok( ObjectDemoScene { grid, grass_transform, dirt_transform, stone_transform, perspective: *perspective })
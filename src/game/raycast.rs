@@ -0,0 +1,158 @@
+
+/// A block hit by a ray
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit
+{
+    /// Block coordinate of the hit block
+    pub block: (i32, i32, i32),
+
+    /// Normal of the face the ray entered through. (0, 0, 0) if the
+    /// ray started inside the block.
+    pub face: (i32, i32, i32),
+
+    /// Distance along the ray to where it entered the block
+    pub distance: f32,
+}
+
+impl RayHit
+{
+    /// The block next to the hit face, where a new block would be placed
+    pub fn adjacent(self: &RayHit) -> (i32, i32, i32)
+    {
+        (self.block.0 + self.face.0, self.block.1 + self.face.1, self.block.2 + self.face.2)
+    }
+}
+
+/// Walks the blocks along a ray (Amanatides & Woo voxel traversal) and returns the
+/// first one is_solid returns true for. Blocks are unit cubes centered on integer
+/// coordinates, same as the cubes in the chunk meshes.
+pub fn raycast<F: Fn(i32, i32, i32) -> bool>(origin: &glm::Vec3, direction: &glm::Vec3, max_distance: f32, is_solid: F) -> Option<RayHit>
+{
+    if glm::length(direction) < f32::EPSILON
+    {
+        return None;
+    }
+
+    let dir = glm::normalize(direction);
+    let dir = [dir.x, dir.y, dir.z];
+
+    // Shift by half a block so each block covers [n, n + 1)
+    let start = [origin.x + 0.5, origin.y + 0.5, origin.z + 0.5];
+    let mut cell = [start[0].floor() as i32, start[1].floor() as i32, start[2].floor() as i32];
+
+    if is_solid(cell[0], cell[1], cell[2])
+    {
+        return Some(RayHit { block: (cell[0], cell[1], cell[2]), face: (0, 0, 0), distance: 0.0 });
+    }
+
+    // For each axis: which way to step, how far along the ray the next block
+    // boundary is and how far the ray travels between boundaries
+    let mut step = [0; 3];
+    let mut t_max = [f32::INFINITY; 3];
+    let mut t_delta = [f32::INFINITY; 3];
+    for i in 0..3
+    {
+        if dir[i] > 0.0
+        {
+            step[i] = 1;
+            t_max[i] = (cell[i] as f32 + 1.0 - start[i]) / dir[i];
+            t_delta[i] = 1.0 / dir[i];
+        }
+        else if dir[i] < 0.0
+        {
+            step[i] = -1;
+            t_max[i] = (start[i] - cell[i] as f32) / -dir[i];
+            t_delta[i] = 1.0 / -dir[i];
+        }
+    }
+
+    loop
+    {
+        let mut axis = 0;
+        if t_max[1] < t_max[axis]
+        {
+            axis = 1;
+        }
+
+        if t_max[2] < t_max[axis]
+        {
+            axis = 2;
+        }
+
+        let distance = t_max[axis];
+        if distance > max_distance
+        {
+            return None;
+        }
+
+        cell[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+
+        if is_solid(cell[0], cell[1], cell[2])
+        {
+            let mut face = [0; 3];
+            face[axis] = -step[axis];
+
+            return Some(RayHit { block: (cell[0], cell[1], cell[2]), face: (face[0], face[1], face[2]), distance });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn floor_and_pillar(x: i32, y: i32, z: i32) -> bool
+    {
+        y < 0 || (x == 3 && z == 0 && y < 4)
+    }
+
+    #[test]
+    fn hits_the_floor_looking_down()
+    {
+        let hit = raycast(&glm::vec3(0.0, 5.0, 0.0), &glm::vec3(0.0, -1.0, 0.0), 10.0, floor_and_pillar).unwrap();
+
+        assert_eq!(hit.block, (0, -1, 0));
+        assert_eq!(hit.face, (0, 1, 0));
+        assert_eq!(hit.adjacent(), (0, 0, 0));
+        assert!((hit.distance - 5.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn hits_the_side_of_a_block()
+    {
+        let hit = raycast(&glm::vec3(0.0, 1.0, 0.0), &glm::vec3(1.0, 0.0, 0.0), 10.0, floor_and_pillar).unwrap();
+
+        assert_eq!(hit.block, (3, 1, 0));
+        assert_eq!(hit.face, (-1, 0, 0));
+        assert!((hit.distance - 2.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn diagonal_ray_enters_through_the_right_face()
+    {
+        // Going down at 45 degrees towards -z hits the floor from above
+        let hit = raycast(&glm::vec3(0.0, 2.0, 0.0), &glm::vec3(0.0, -1.0, -1.0), 10.0, floor_and_pillar).unwrap();
+
+        assert_eq!(hit.face, (0, 1, 0));
+        assert_eq!(hit.block.1, -1);
+    }
+
+    #[test]
+    fn stops_at_max_distance()
+    {
+        assert!(raycast(&glm::vec3(0.0, 5.0, 0.0), &glm::vec3(0.0, -1.0, 0.0), 5.0, floor_and_pillar).is_none());
+        assert!(raycast(&glm::vec3(0.0, 5.0, 0.0), &glm::vec3(0.0, 1.0, 0.0), 100.0, floor_and_pillar).is_none());
+        assert!(raycast(&glm::vec3(0.0, 5.0, 0.0), &glm::vec3(0.0, 0.0, 0.0), 100.0, floor_and_pillar).is_none());
+    }
+
+    #[test]
+    fn starting_inside_a_block()
+    {
+        let hit = raycast(&glm::vec3(3.2, 1.0, 0.0), &glm::vec3(1.0, 0.0, 0.0), 10.0, floor_and_pillar).unwrap();
+
+        assert_eq!(hit.block, (3, 1, 0));
+        assert_eq!(hit.face, (0, 0, 0));
+    }
+}
@@ -0,0 +1,892 @@
+
+use std::{ fs, thread, collections::HashMap, path::{Path, PathBuf}, sync::{Arc, Mutex, mpsc} };
+use crate::{ utils::{ FractalMode, NoiseAlgorithm }, game::{ WorldChunk, ChunkCoord, ChunkGeneration, NoiseType } };
+
+/// Chunks per side of a region. Each region file holds up to REGION_SIZE * REGION_SIZE chunks.
+pub const REGION_SIZE: i32 = 8;
+
+const CHUNK_MAGIC: &[u8; 4] = b"BWCK";
+const CHUNK_VERSION: u16 = 5;
+
+// Settings added after version 1, older chunks are read with these
+const V1_FRACTAL: (FractalMode, f32, f32) = (FractalMode::FBM, 2.0, 0.5);
+const V2_WARP: (f32, f32, bool) = (1.0, 1.0, false);
+const V3_BASE: NoiseAlgorithm = NoiseAlgorithm::SIMPLEX;
+const V4_TIME: f32 = 0.0;
+
+// Largest chunk side accepted when loading, anything bigger is treated as corrupt
+const MAX_CHUNK_SIZE: u32 = 1024;
+
+const REGION_MAGIC: &[u8; 4] = b"BWRG";
+const REGION_VERSION: u16 = 2;
+
+/// Saves and loads chunks to region files in a directory. Regions are
+/// named r.<x>.<z>.bwr after their region coordinate. A chunk is saved once
+/// for each set of generation settings, so edits made with other settings
+/// are kept.
+///
+/// Region file (little endian):
+///     "BWRG", version: u16, entry count: u32,
+///     entries: local x: u8, local z: u8, length: u32, chunk bytes (see encode_chunk)
+/// Version 1 files have at most one entry per local coordinate.
+pub struct RegionStore
+{
+    dir: PathBuf,
+
+    // The save thread writes while the workers load
+    lock: Mutex<()>,
+
+    // Chunks waiting for the save thread. Loads look here first so a chunk
+    // that comes back before it has been written still has its edits.
+    queued: Mutex<Vec<(ChunkCoord, Arc<WorldChunk>)>>,
+}
+
+impl RegionStore
+{
+    pub fn new<P: AsRef<Path>>(dir: P) -> RegionStore
+    {
+        RegionStore { dir: dir.as_ref().to_path_buf(), lock: Mutex::new(()), queued: Mutex::new(vec![]) }
+    }
+
+    pub fn region_of(coord: ChunkCoord) -> (i32, i32)
+    {
+        (coord.0.div_euclid(REGION_SIZE), coord.1.div_euclid(REGION_SIZE))
+    }
+
+    fn local_coord(coord: ChunkCoord) -> (u8, u8)
+    {
+        (coord.0.rem_euclid(REGION_SIZE) as u8, coord.1.rem_euclid(REGION_SIZE) as u8)
+    }
+
+    fn region_path(self: &RegionStore, region: (i32, i32)) -> PathBuf
+    {
+        self.dir.join(format!("r.{}.{}.bwr", region.0, region.1))
+    }
+
+    /// Returns Ok(None) if the chunk has never been saved with these settings
+    pub fn load_chunk(self: &RegionStore, coord: ChunkCoord, generation: &ChunkGeneration) -> Result<Option<WorldChunk>, String>
+    {
+        let queued = self.queued.lock().unwrap().iter()
+            .find(|(c, chunk)| *c == coord && chunk.generation.as_ref() == Some(generation))
+            .map(|(_, chunk)| (**chunk).clone());
+        if queued.is_some()
+        {
+            return Ok(queued);
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        let path = self.region_path(RegionStore::region_of(coord));
+        let entries = read_region(&path)?;
+        let saves = match entries.get(&RegionStore::local_coord(coord))
+        {
+            Some(saves) => saves,
+            None => return Ok(None)
+        };
+
+        let error = |e: String| format!("Error Loading Chunk {:?} from {}: {}", coord, path.display(), e);
+        for bytes in saves
+        {
+            if chunk_generation(bytes).map_err(error)?.as_ref() == Some(generation)
+            {
+                return decode_chunk(bytes).map(Some).map_err(error);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Saves the chunks, replacing any saved copies with the same generation
+    /// settings. Each region file that is touched is rewritten once.
+    pub fn save_chunks(self: &RegionStore, chunks: &[(ChunkCoord, &WorldChunk)]) -> Result<(), String>
+    {
+        if chunks.is_empty()
+        {
+            return Ok(());
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        if let Err(e) = fs::create_dir_all(&self.dir)
+        {
+            return Err(format!("Error Creating Save Directory! Dir: {}, Error: {:?}", self.dir.display(), e));
+        }
+
+        let mut regions: HashMap<(i32, i32), Vec<(ChunkCoord, &WorldChunk)>> = HashMap::new();
+        for (coord, chunk) in chunks.iter()
+        {
+            regions.entry(RegionStore::region_of(*coord)).or_default().push((*coord, *chunk));
+        }
+
+        for (region, chunks) in regions
+        {
+            let path = self.region_path(region);
+            let mut entries = read_region(&path)?;
+            for (coord, chunk) in chunks
+            {
+                let saves = entries.entry(RegionStore::local_coord(coord)).or_default();
+                saves.retain(|bytes| chunk_generation(bytes).ok() != Some(chunk.generation));
+                saves.push(encode_chunk(chunk));
+            }
+
+            write_region(&path, &entries)?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the chunks queued by a SaveThread. They stay queued if writing fails.
+    fn write_queued(self: &RegionStore) -> Result<(), String>
+    {
+        let writing = self.queued.lock().unwrap().clone();
+        let chunks: Vec<(ChunkCoord, &WorldChunk)> = writing.iter().map(|(coord, chunk)| (*coord, &**chunk)).collect();
+        self.save_chunks(&chunks)?;
+
+        // Chunks queued again while writing are newer, leave them for the next write
+        self.queued.lock().unwrap().retain(|(_, chunk)| !writing.iter().any(|(_, written)| Arc::ptr_eq(chunk, written)));
+        Ok(())
+    }
+}
+
+/// Writes chunks to a RegionStore on its own thread so saving never
+/// stalls the render thread. Dropping it waits for the queued chunks.
+pub struct SaveThread
+{
+    regions: Arc<RegionStore>,
+    sender: Option<mpsc::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl SaveThread
+{
+    pub fn new(regions: Arc<RegionStore>) -> SaveThread
+    {
+        let (sender, receiver) = mpsc::channel::<()>();
+        let store = Arc::clone(&regions);
+        let thread = thread::Builder::new().name(String::from("region_saver")).spawn(move ||
+        {
+            while receiver.recv().is_ok()
+            {
+                // Everything queued so far is written together
+                while receiver.try_recv().is_ok() {}
+
+                if let Err(e) = store.write_queued()
+                {
+                    println!("{}", e);
+                }
+            }
+        }).unwrap();
+
+        SaveThread { regions, sender: Some(sender), thread: Some(thread) }
+    }
+
+    /// Queues the chunks to be written, replacing queued copies with the same settings
+    pub fn save(self: &SaveThread, chunks: Vec<(ChunkCoord, Arc<WorldChunk>)>)
+    {
+        if chunks.is_empty()
+        {
+            return;
+        }
+
+        {
+            let mut queued = self.regions.queued.lock().unwrap();
+            for (coord, chunk) in chunks
+            {
+                queued.retain(|(c, q)| *c != coord || q.generation != chunk.generation);
+                queued.push((coord, chunk));
+            }
+        }
+
+        if let Some(sender) = &self.sender
+        {
+            let _ = sender.send(());
+        }
+    }
+}
+
+impl Drop for SaveThread
+{
+    fn drop(&mut self)
+    {
+        // Closing the channel lets the thread finish what's queued and exit
+        self.sender = None;
+        if let Some(thread) = self.thread.take()
+        {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The encoded chunks in a region for each local coordinate, one per generation settings
+type RegionEntries = HashMap<(u8, u8), Vec<Vec<u8>>>;
+
+fn read_region(path: &Path) -> Result<RegionEntries, String>
+{
+    let mut entries = HashMap::new();
+    if !path.exists()
+    {
+        return Ok(entries);
+    }
+
+    let bytes = match fs::read(path)
+    {
+        Ok(b) => b,
+        Err(e) => return Err(format!("Error Reading Region! File: {}, Error: {:?}", path.display(), e))
+    };
+
+    let mut reader = ByteReader { bytes: &bytes, pos: 0 };
+    if reader.take(4)? != REGION_MAGIC
+    {
+        return Err(format!("Not a region file: {}", path.display()));
+    }
+
+    let version = reader.u16()?;
+    if version == 0 || version > REGION_VERSION
+    {
+        return Err(format!("Unsupported region version {} in {}", version, path.display()));
+    }
+
+    let count = reader.u32()?;
+    for _ in 0..count
+    {
+        let local = (reader.u8()?, reader.u8()?);
+        let len = reader.u32()? as usize;
+        entries.entry(local).or_default().push(reader.take(len)?.to_vec());
+    }
+
+    Ok(entries)
+}
+
+fn write_region(path: &Path, entries: &RegionEntries) -> Result<(), String>
+{
+    let count: usize = entries.values().map(|saves| saves.len()).sum();
+    let mut bytes = vec![];
+    bytes.extend_from_slice(REGION_MAGIC);
+    bytes.extend_from_slice(&REGION_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(count as u32).to_le_bytes());
+
+    // Sorted so saving the same chunks always gives the same file
+    let mut locals: Vec<&(u8, u8)> = entries.keys().collect();
+    locals.sort();
+    for local in locals
+    {
+        for chunk in entries[local].iter()
+        {
+            bytes.push(local.0);
+            bytes.push(local.1);
+            bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(chunk);
+        }
+    }
+
+    // Write to a temporary file first so a crash can't leave half a region behind
+    let temp = path.with_extension("bwr.tmp");
+    if let Err(e) = fs::write(&temp, &bytes).and_then(|_| fs::rename(&temp, path))
+    {
+        return Err(format!("Error Writing Region! File: {}, Error: {:?}", path.display(), e));
+    }
+
+    Ok(())
+}
+
+/// Chunk format (little endian):
+///     "BWCK", version: u16, width: u32, height: u32, depth: u32,
+///     has generation: u8, [generation settings (see write_generation)],
+///     run count: u32, runs: id: u16, length: u32
+/// The runs cover the blocks in y, x, z order.
+pub fn encode_chunk(chunk: &WorldChunk) -> Vec<u8>
+{
+    let mut bytes = vec![];
+    bytes.extend_from_slice(CHUNK_MAGIC);
+    bytes.extend_from_slice(&CHUNK_VERSION.to_le_bytes());
+    for dim in [chunk.width, chunk.height, chunk.depth].iter()
+    {
+        bytes.extend_from_slice(&(*dim as u32).to_le_bytes());
+    }
+
+    match &chunk.generation
+    {
+        Some(generation) =>
+        {
+            bytes.push(1);
+            write_generation(&mut bytes, generation);
+        },
+        None => bytes.push(0)
+    }
+
+    let mut runs: Vec<(u16, u32)> = vec![];
+    for layer in chunk.layers()
+    {
+        for id in layer
+        {
+            match runs.last_mut()
+            {
+                Some(run) if run.0 == id => run.1 += 1,
+                _ => runs.push((id, 1))
+            }
+        }
+    }
+
+    bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+    for (id, len) in runs
+    {
+        bytes.extend_from_slice(&id.to_le_bytes());
+        bytes.extend_from_slice(&len.to_le_bytes());
+    }
+
+    bytes
+}
+
+pub fn decode_chunk(bytes: &[u8]) -> Result<WorldChunk, String>
+{
+    let mut reader = ByteReader { bytes, pos: 0 };
+    let (width, height, depth, generation) = read_header(&mut reader)?;
+
+    // Read the runs before allocating so a corrupt header can't ask for a huge chunk
+    let run_count = reader.u32()? as usize;
+    if run_count > reader.remaining() / 6
+    {
+        return Err(String::from("Unexpected end of data"));
+    }
+
+    let mut runs = Vec::with_capacity(run_count);
+    let mut filled = 0;
+    for _ in 0..run_count
+    {
+        let id = reader.u16()?;
+        let len = reader.u32()? as usize;
+
+        filled += len;
+        if filled > width * height * depth
+        {
+            return Err(String::from("Chunk has more blocks than its dimensions allow"));
+        }
+
+        runs.push((id, len));
+    }
+
+    if filled != width * height * depth
+    {
+        return Err(String::from("Chunk is missing blocks"));
+    }
+
+    let mut chunk = WorldChunk::new(width, height, depth);
+    chunk.generation = generation;
+
+    let (mut x, mut y, mut z) = (0, 0, 0);
+    for (id, len) in runs
+    {
+        for _ in 0..len
+        {
+            chunk.set(x, y, z, id);
+
+            z += 1;
+            if z == depth
+            {
+                z = 0;
+                x += 1;
+                if x == width
+                {
+                    x = 0;
+                    y += 1;
+                }
+            }
+        }
+    }
+
+    Ok(chunk)
+}
+
+/// The generation settings a chunk was saved with, without decoding its blocks
+pub fn chunk_generation(bytes: &[u8]) -> Result<Option<ChunkGeneration>, String>
+{
+    let mut reader = ByteReader { bytes, pos: 0 };
+    Ok(read_header(&mut reader)?.3)
+}
+
+/// Reads everything before the runs: the dimensions and generation settings
+fn read_header(reader: &mut ByteReader) -> Result<(usize, usize, usize, Option<ChunkGeneration>), String>
+{
+    if reader.take(4)? != CHUNK_MAGIC
+    {
+        return Err(String::from("Not a chunk"));
+    }
+
+    let version = reader.u16()?;
+    if version == 0 || version > CHUNK_VERSION
+    {
+        return Err(format!("Unsupported chunk version {}", version));
+    }
+
+    let (width, height, depth) = (reader.u32()?, reader.u32()?, reader.u32()?);
+    if [width, height, depth].iter().any(|dim| *dim == 0 || *dim > MAX_CHUNK_SIZE)
+    {
+        return Err(format!("Bad chunk dimensions {}x{}x{}", width, height, depth));
+    }
+    let (width, height, depth) = (width as usize, height as usize, depth as usize);
+
+    let generation = match reader.u8()?
+    {
+        0 => None,
+        _ => Some(read_generation(reader, version)?)
+    };
+
+    Ok((width, height, depth, generation))
+}
+
+/// noise type: u8 (255 = CUSTOM followed by its id: u32), offset: f32 x2, zoom_factor: f32,
+/// sx_scale: f32, threshold: f32, threshold_falloff: i32, octaves: i32, bias: f32,
+/// fractal mode: u8, lacunarity: f32, gain: f32 (since version 2),
+/// warp strength: f32, warp frequency: f32, double warp: u8 (since version 3),
+/// base noise: u8 (since version 4), time: f32 (since version 5),
+/// has seed: u8, [seed: 32 bytes]
+fn write_generation(bytes: &mut Vec<u8>, generation: &ChunkGeneration)
+{
+    match generation.noise_type
+    {
+        NoiseType::RANDOM_2D => bytes.push(0),
+        NoiseType::RANDOM_3D => bytes.push(1),
+        NoiseType::OLC => bytes.push(2),
+        NoiseType::SIMPLEX_2D => bytes.push(3),
+        NoiseType::SIMPLEX_3D => bytes.push(4),
+        NoiseType::FRACTAL_2D => bytes.push(5),
+        NoiseType::FRACTAL_3D => bytes.push(6),
+        NoiseType::WARPED_2D => bytes.push(7),
+        NoiseType::WARPED_3D => bytes.push(8),
+        NoiseType::ANIMATED_3D => bytes.push(9),
+        NoiseType::CUSTOM(id) =>
+        {
+            bytes.push(255);
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+    }
+
+    for value in [generation.offset.0, generation.offset.1, generation.zoom_factor, generation.sx_scale, generation.threshold].iter()
+    {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes.extend_from_slice(&generation.threshold_falloff.to_le_bytes());
+    bytes.extend_from_slice(&generation.octaves.to_le_bytes());
+    bytes.extend_from_slice(&generation.bias.to_le_bytes());
+
+    bytes.push(match generation.fractal_mode
+    {
+        FractalMode::FBM => 0,
+        FractalMode::RIDGED => 1,
+        FractalMode::BILLOW => 2,
+        FractalMode::TURBULENCE => 3,
+    });
+    bytes.extend_from_slice(&generation.lacunarity.to_le_bytes());
+    bytes.extend_from_slice(&generation.gain.to_le_bytes());
+
+    bytes.extend_from_slice(&generation.warp_strength.to_le_bytes());
+    bytes.extend_from_slice(&generation.warp_frequency.to_le_bytes());
+    bytes.push(generation.double_warp as u8);
+
+    bytes.push(match generation.base_noise
+    {
+        NoiseAlgorithm::SIMPLEX => 0,
+        NoiseAlgorithm::OPEN_SIMPLEX2 => 1,
+        NoiseAlgorithm::PERLIN => 2,
+        NoiseAlgorithm::VALUE => 3,
+        NoiseAlgorithm::WORLEY_F1 => 4,
+        NoiseAlgorithm::WORLEY_F2 => 5,
+        NoiseAlgorithm::WORLEY_F2_MINUS_F1 => 6,
+    });
+
+    bytes.extend_from_slice(&generation.time.to_le_bytes());
+
+    match generation.seed
+    {
+        Some(seed) =>
+        {
+            bytes.push(1);
+            bytes.extend_from_slice(&seed);
+        },
+        None => bytes.push(0)
+    }
+}
+
+fn read_generation(reader: &mut ByteReader, version: u16) -> Result<ChunkGeneration, String>
+{
+    let noise_type = match reader.u8()?
+    {
+        0 => NoiseType::RANDOM_2D,
+        1 => NoiseType::RANDOM_3D,
+        2 => NoiseType::OLC,
+        3 => NoiseType::SIMPLEX_2D,
+        4 => NoiseType::SIMPLEX_3D,
+        5 => NoiseType::FRACTAL_2D,
+        6 => NoiseType::FRACTAL_3D,
+        7 => NoiseType::WARPED_2D,
+        8 => NoiseType::WARPED_3D,
+        9 => NoiseType::ANIMATED_3D,
+        255 => NoiseType::CUSTOM(reader.u32()?),
+        n => return Err(format!("Unknown noise type {}", n))
+    };
+
+    let offset = (reader.f32()?, reader.f32()?);
+    let (zoom_factor, sx_scale, threshold) = (reader.f32()?, reader.f32()?, reader.f32()?);
+    let (threshold_falloff, octaves) = (reader.i32()?, reader.i32()?);
+    let bias = reader.f32()?;
+
+    let (fractal_mode, lacunarity, gain) = match version
+    {
+        1 => V1_FRACTAL,
+        _ =>
+        {
+            let mode = match reader.u8()?
+            {
+                0 => FractalMode::FBM,
+                1 => FractalMode::RIDGED,
+                2 => FractalMode::BILLOW,
+                3 => FractalMode::TURBULENCE,
+                n => return Err(format!("Unknown fractal mode {}", n))
+            };
+            (mode, reader.f32()?, reader.f32()?)
+        }
+    };
+
+    let (warp_strength, warp_frequency, double_warp) = match version
+    {
+        1 | 2 => V2_WARP,
+        _ => (reader.f32()?, reader.f32()?, reader.u8()? != 0)
+    };
+
+    let base_noise = match version
+    {
+        1..=3 => V3_BASE,
+        _ => match reader.u8()?
+        {
+            0 => NoiseAlgorithm::SIMPLEX,
+            1 => NoiseAlgorithm::OPEN_SIMPLEX2,
+            2 => NoiseAlgorithm::PERLIN,
+            3 => NoiseAlgorithm::VALUE,
+            4 => NoiseAlgorithm::WORLEY_F1,
+            5 => NoiseAlgorithm::WORLEY_F2,
+            6 => NoiseAlgorithm::WORLEY_F2_MINUS_F1,
+            n => return Err(format!("Unknown base noise {}", n))
+        }
+    };
+
+    let time = match version
+    {
+        1..=4 => V4_TIME,
+        _ => reader.f32()?
+    };
+
+    let seed = match reader.u8()?
+    {
+        0 => None,
+        _ =>
+        {
+            let mut seed = [0; 32];
+            seed.copy_from_slice(reader.take(32)?);
+            Some(seed)
+        }
+    };
+
+    Ok(ChunkGeneration { noise_type, offset, zoom_factor, sx_scale, threshold, threshold_falloff, octaves, bias, seed,
+                            fractal_mode, lacunarity, gain, warp_strength, warp_frequency, double_warp, base_noise, time })
+}
+
+struct ByteReader<'a>
+{
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a>
+{
+    fn take(self: &mut ByteReader<'a>, len: usize) -> Result<&'a [u8], String>
+    {
+        if self.pos + len > self.bytes.len()
+        {
+            return Err(String::from("Unexpected end of data"));
+        }
+
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn remaining(self: &ByteReader<'a>) -> usize
+    {
+        self.bytes.len() - self.pos
+    }
+
+    fn u8(self: &mut ByteReader<'a>) -> Result<u8, String>
+    {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(self: &mut ByteReader<'a>) -> Result<u16, String>
+    {
+        let mut b = [0; 2];
+        b.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(b))
+    }
+
+    fn u32(self: &mut ByteReader<'a>) -> Result<u32, String>
+    {
+        let mut b = [0; 4];
+        b.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(b))
+    }
+
+    fn i32(self: &mut ByteReader<'a>) -> Result<i32, String>
+    {
+        Ok(self.u32()? as i32)
+    }
+
+    fn f32(self: &mut ByteReader<'a>) -> Result<f32, String>
+    {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn test_generation() -> ChunkGeneration
+    {
+        ChunkGeneration { noise_type: NoiseType::SIMPLEX_3D, offset: (1.5, -2.0), zoom_factor: 0.01, sx_scale: 32.0,
+                            threshold: 0.3, threshold_falloff: 20, octaves: 3, bias: 0.5, seed: Some([7; 32]),
+                            fractal_mode: FractalMode::RIDGED, lacunarity: 2.5, gain: 0.4,
+                            warp_strength: 0.75, warp_frequency: 2.0, double_warp: true,
+                            base_noise: NoiseAlgorithm::WORLEY_F1, time: 2.5 }
+    }
+
+    fn test_chunk(fill: u16) -> WorldChunk
+    {
+        let mut chunk = WorldChunk::new(8, 6, 4);
+        chunk.fill_layer(0, fill);
+        chunk.fill_layer(1, 2);
+        chunk.set(3, 5, 1, 9);
+        chunk.generation = Some(test_generation());
+        chunk
+    }
+
+    fn assert_same_blocks(a: &WorldChunk, b: &WorldChunk)
+    {
+        assert_eq!((a.width, a.height, a.depth), (b.width, b.height, b.depth));
+        for (la, lb) in a.layers().zip(b.layers())
+        {
+            assert!(la.eq(lb));
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf
+    {
+        let dir = std::env::temp_dir().join(format!("block_world_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn chunk_round_trip()
+    {
+        let chunk = test_chunk(3);
+        let decoded = decode_chunk(&encode_chunk(&chunk)).unwrap();
+
+        assert_same_blocks(&chunk, &decoded);
+        assert_eq!(decoded.generation, chunk.generation);
+    }
+
+    #[test]
+    fn chunk_round_trip_without_generation()
+    {
+        let mut chunk = test_chunk(3);
+        chunk.generation = Some(ChunkGeneration { noise_type: NoiseType::CUSTOM(42), seed: None, ..test_generation() });
+        let decoded = decode_chunk(&encode_chunk(&chunk)).unwrap();
+        assert_eq!(decoded.generation, chunk.generation);
+
+        chunk.generation = None;
+        let decoded = decode_chunk(&encode_chunk(&chunk)).unwrap();
+        assert_eq!(decoded.generation, None);
+        assert_same_blocks(&chunk, &decoded);
+    }
+
+    #[test]
+    fn reads_older_chunk_versions()
+    {
+        // Each version adds settings after the bias: fractal (2) and warp (3) are 9 bytes, base noise (4)
+        // is 1 and time (5) is 4
+        let chunk = test_chunk(3);
+        let fractal_start = 4 + 2 + 12 + 1 + 1 + 5 * 4 + 3 * 4;
+        let (fractal_mode, lacunarity, gain) = V1_FRACTAL;
+        let (warp_strength, warp_frequency, double_warp) = V2_WARP;
+        let (base_noise, time) = (V3_BASE, V4_TIME);
+
+        let mut bytes = encode_chunk(&chunk);
+        bytes.drain(fractal_start + 19..fractal_start + 23);
+        bytes[4..6].copy_from_slice(&4u16.to_le_bytes());
+
+        let decoded = decode_chunk(&bytes).unwrap();
+        assert_eq!(decoded.generation, Some(ChunkGeneration { time, ..test_generation() }));
+        assert_same_blocks(&chunk, &decoded);
+
+        bytes.remove(fractal_start + 18);
+        bytes[4..6].copy_from_slice(&3u16.to_le_bytes());
+
+        let decoded = decode_chunk(&bytes).unwrap();
+        assert_eq!(decoded.generation, Some(ChunkGeneration { base_noise, time, ..test_generation() }));
+        assert_same_blocks(&chunk, &decoded);
+
+        bytes.drain(fractal_start + 9..fractal_start + 18);
+        bytes[4..6].copy_from_slice(&2u16.to_le_bytes());
+
+        let decoded = decode_chunk(&bytes).unwrap();
+        assert_eq!(decoded.generation, Some(ChunkGeneration { warp_strength, warp_frequency, double_warp, base_noise, time,
+                                                                ..test_generation() }));
+        assert_same_blocks(&chunk, &decoded);
+
+        bytes.drain(fractal_start..fractal_start + 9);
+        bytes[4..6].copy_from_slice(&1u16.to_le_bytes());
+
+        let decoded = decode_chunk(&bytes).unwrap();
+        assert_eq!(decoded.generation, Some(ChunkGeneration { fractal_mode, lacunarity, gain, warp_strength, warp_frequency,
+                                                                double_warp, base_noise, time, ..test_generation() }));
+        assert_same_blocks(&chunk, &decoded);
+    }
+
+    #[test]
+    fn compressed_chunk_round_trip()
+    {
+        let mut chunk = test_chunk(3);
+        chunk.compress();
+        let decoded = decode_chunk(&encode_chunk(&chunk)).unwrap();
+
+        assert_same_blocks(&chunk, &decoded);
+    }
+
+    #[test]
+    fn rejects_bad_data()
+    {
+        let bytes = encode_chunk(&test_chunk(3));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 99;
+        assert!(decode_chunk(&wrong_version).is_err());
+
+        assert!(decode_chunk(&bytes[..bytes.len() - 3]).is_err());
+        assert!(decode_chunk(b"nope").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_dimensions()
+    {
+        let bytes = encode_chunk(&test_chunk(3));
+        for dims in [(0, 6, 4), (8, 2000, 4), (u32::MAX, u32::MAX, u32::MAX)].iter()
+        {
+            let mut bad = bytes.clone();
+            bad[6..10].copy_from_slice(&dims.0.to_le_bytes());
+            bad[10..14].copy_from_slice(&dims.1.to_le_bytes());
+            bad[14..18].copy_from_slice(&dims.2.to_le_bytes());
+            assert!(decode_chunk(&bad).is_err(), "{:?}", dims);
+        }
+
+        // In range but bigger than the runs cover
+        let mut bad = bytes.clone();
+        bad[6..10].copy_from_slice(&1024u32.to_le_bytes());
+        assert!(decode_chunk(&bad).is_err());
+
+        // A run count the payload can't hold. test_chunk has 5 runs (3, 2, air, 9, air)
+        // of 6 bytes each after the count.
+        let count_at = bytes.len() - 6 * 5 - 4;
+        assert_eq!(bytes[count_at], 5);
+        let mut bad = bytes.clone();
+        bad[count_at..count_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode_chunk(&bad).is_err());
+    }
+
+    #[test]
+    fn region_round_trip()
+    {
+        let dir = temp_dir("region_round_trip");
+        let store = RegionStore::new(&dir);
+
+        // (-1, -1) and (-8, -3) share a region, (0, 0) is in the next one
+        let (a, b, c) = (test_chunk(1), test_chunk(3), test_chunk(4));
+        store.save_chunks(&[((-1, -1), &a), ((-8, -3), &b), ((0, 0), &c)]).unwrap();
+
+        assert_same_blocks(&store.load_chunk((-1, -1), &test_generation()).unwrap().unwrap(), &a);
+        assert_same_blocks(&store.load_chunk((-8, -3), &test_generation()).unwrap().unwrap(), &b);
+        assert_same_blocks(&store.load_chunk((0, 0), &test_generation()).unwrap().unwrap(), &c);
+        assert!(store.load_chunk((-2, -1), &test_generation()).unwrap().is_none());
+        assert!(store.load_chunk((50, 50), &test_generation()).unwrap().is_none());
+
+        // Saving again replaces the chunk and keeps its neighbours in the region
+        let mut edited = test_chunk(1);
+        edited.set(0, 3, 0, 5);
+        store.save_chunks(&[((-1, -1), &edited)]).unwrap();
+
+        let loaded = store.load_chunk((-1, -1), &test_generation()).unwrap().unwrap();
+        assert_eq!(loaded.get(0, 3, 0), 5);
+        assert_same_blocks(&store.load_chunk((-8, -3), &test_generation()).unwrap().unwrap(), &b);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn saves_are_kept_for_each_generation()
+    {
+        let dir = temp_dir("saves_per_generation");
+        let store = RegionStore::new(&dir);
+        let other = ChunkGeneration { seed: Some([8; 32]), ..test_generation() };
+
+        let first = test_chunk(1);
+        let mut second = test_chunk(4);
+        second.generation = Some(other);
+        store.save_chunks(&[((2, 3), &first), ((2, 3), &second)]).unwrap();
+
+        assert_same_blocks(&store.load_chunk((2, 3), &test_generation()).unwrap().unwrap(), &first);
+        assert_same_blocks(&store.load_chunk((2, 3), &other).unwrap().unwrap(), &second);
+        let unsaved = ChunkGeneration { seed: Some([9; 32]), ..test_generation() };
+        assert!(store.load_chunk((2, 3), &unsaved).unwrap().is_none());
+
+        // Saving with the first settings again only replaces the first save
+        let edited = test_chunk(5);
+        store.save_chunks(&[((2, 3), &edited)]).unwrap();
+        assert_same_blocks(&store.load_chunk((2, 3), &test_generation()).unwrap().unwrap(), &edited);
+        assert_same_blocks(&store.load_chunk((2, 3), &other).unwrap().unwrap(), &second);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn save_thread_writes_queued_chunks()
+    {
+        let dir = temp_dir("save_thread");
+        let store = Arc::new(RegionStore::new(&dir));
+        let saver = SaveThread::new(Arc::clone(&store));
+        let chunk = test_chunk(1);
+        saver.save(vec![((1, 2), Arc::new(chunk.clone()))]);
+
+        // Loads find it whether or not it has been written yet
+        assert_same_blocks(&store.load_chunk((1, 2), &test_generation()).unwrap().unwrap(), &chunk);
+
+        drop(saver);
+        assert!(store.queued.lock().unwrap().is_empty());
+        assert_same_blocks(&RegionStore::new(&dir).load_chunk((1, 2), &test_generation()).unwrap().unwrap(), &chunk);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn corrupt_region_is_an_error()
+    {
+        let dir = temp_dir("corrupt_region");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("r.0.0.bwr"), b"garbage").unwrap();
+
+        let store = RegionStore::new(&dir);
+        assert!(store.load_chunk((1, 1), &test_generation()).is_err());
+        assert!(store.save_chunks(&[((1, 1), &test_chunk(1))]).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,276 @@
+
+use std::collections::{ HashSet, VecDeque };
+use crate::game::{ WorldChunk, ChunkCoord, BlockRegistry, chunk_mesher::FACES, chunk_storage::SECTION_HEIGHT };
+
+/// A SECTION_HEIGHT high slice of a chunk: the chunk and the section index (0 is the bottom)
+pub type SectionCoord = (ChunkCoord, usize);
+
+/// Which faces of a chunk section can see each other through the air (and transparent
+/// blocks) inside the section, "cave culling" style. Faces are indexed in FACES order
+/// (+X, -X, +Y, -Y, +Z, -Z) so the opposite of face i is i ^ 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SectionConnections
+{
+    // Bit a * 6 + b is set if face a can see face b
+    bits: u64,
+}
+
+impl SectionConnections
+{
+    /// Every face sees every other face, like an empty section
+    pub fn all() -> SectionConnections
+    {
+        let mut connections = SectionConnections::default();
+        connections.connect(0b11_1111);
+        connections
+    }
+
+    pub fn connects(self: &SectionConnections, a: usize, b: usize) -> bool
+    {
+        self.bits & (1 << (a * 6 + b)) != 0
+    }
+
+    /// Connects every pair of faces in the mask
+    fn connect(self: &mut SectionConnections, faces: u8)
+    {
+        for a in 0..6
+        {
+            for b in 0..6
+            {
+                if faces & (1 << a) != 0 && faces & (1 << b) != 0
+                {
+                    self.bits |= 1 << (a * 6 + b);
+                }
+            }
+        }
+    }
+
+    /// Flood fills each pocket of air in the section and connects the faces it touches
+    pub fn compute(chunk: &WorldChunk, blocks: &BlockRegistry, section: usize) -> SectionConnections
+    {
+        let y_start = section * SECTION_HEIGHT;
+        let y_end = (y_start + SECTION_HEIGHT).min(chunk.height);
+        let (w, h, d) = (chunk.width, y_end - y_start, chunk.depth);
+
+        let index = |x: usize, y: usize, z: usize| (y * w + x) * d + z;
+        let mut visited = vec![false; w * h * d];
+        for x in 0..w
+        {
+            for y in 0..h
+            {
+                for z in 0..d
+                {
+                    visited[index(x, y, z)] = blocks.is_opaque(chunk.get(x, y_start + y, z));
+                }
+            }
+        }
+
+        let mut connections = SectionConnections::default();
+        let mut stack = vec![];
+        for start in 0..visited.len()
+        {
+            if visited[start]
+            {
+                continue;
+            }
+
+            visited[start] = true;
+            stack.push(((start / d) % w, start / (w * d), start % d));
+
+            let mut faces = 0u8;
+            while let Some((x, y, z)) = stack.pop()
+            {
+                if x == w - 1 { faces |= 1 << 0; }
+                if x == 0 { faces |= 1 << 1; }
+                if y == h - 1 { faces |= 1 << 2; }
+                if y == 0 { faces |= 1 << 3; }
+                if z == d - 1 { faces |= 1 << 4; }
+                if z == 0 { faces |= 1 << 5; }
+
+                for face in FACES.iter()
+                {
+                    let (nx, ny, nz) = (x as i32 + face.dir.0, y as i32 + face.dir.1, z as i32 + face.dir.2);
+                    if nx < 0 || ny < 0 || nz < 0 || nx >= w as i32 || ny >= h as i32 || nz >= d as i32
+                    {
+                        continue;
+                    }
+
+                    let i = index(nx as usize, ny as usize, nz as usize);
+                    if !visited[i]
+                    {
+                        visited[i] = true;
+                        stack.push((nx as usize, ny as usize, nz as usize));
+                    }
+                }
+            }
+
+            connections.connect(faces);
+        }
+
+        connections
+    }
+
+    /// Connections for every section of the chunk, bottom first
+    pub fn compute_chunk(chunk: &WorldChunk, blocks: &BlockRegistry) -> Vec<SectionConnections>
+    {
+        (0..chunk.height.div_ceil(SECTION_HEIGHT)).map(|s| SectionConnections::compute(chunk, blocks, s)).collect()
+    }
+}
+
+/// Finds the sections that could be visible by walking out from the start sections
+/// through connected faces. A section is only entered if in_view returns true for it
+/// and connections (None for sections that aren't loaded) has it. The walk never turns
+/// back on a direction it already went, so it can't wrap around behind a wall.
+///
+/// Each start section has the face it's entered through, None for the section the
+/// camera is in.
+pub fn visible_sections<F, V>(start: &[(SectionCoord, Option<usize>)], num_sections: usize, connections: F, in_view: V)
+    -> HashSet<SectionCoord>
+    where F: Fn(SectionCoord) -> Option<SectionConnections>, V: Fn(SectionCoord) -> bool
+{
+    let mut visible = HashSet::new();
+    let mut queue = VecDeque::new();
+    for &(section, entered) in start
+    {
+        if visible.insert(section)
+        {
+            // Entering through a face means travelling the opposite way
+            let directions = match entered
+            {
+                Some(face) => 1u8 << (face ^ 1),
+                None => 0
+            };
+
+            queue.push_back((section, entered, directions));
+        }
+    }
+
+    while let Some((section, entered, directions)) = queue.pop_front()
+    {
+        let section_connections = match connections(section)
+        {
+            Some(c) => c,
+            None => continue
+        };
+
+        let ((cx, cz), s) = section;
+        for (out, face) in FACES.iter().enumerate()
+        {
+            if directions & (1 << (out ^ 1)) != 0
+            {
+                continue;
+            }
+
+            if let Some(entered) = entered
+            {
+                if !section_connections.connects(entered, out)
+                {
+                    continue;
+                }
+            }
+
+            let next_s = s as i32 + face.dir.1;
+            if next_s < 0 || next_s >= num_sections as i32
+            {
+                continue;
+            }
+
+            let next = ((cx + face.dir.0, cz + face.dir.2), next_s as usize);
+            if visible.contains(&next) || !in_view(next) || connections(next).is_none()
+            {
+                continue;
+            }
+
+            visible.insert(next);
+            queue.push_back((next, Some(out ^ 1), directions | (1 << out)));
+        }
+    }
+
+    visible
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::collections::HashMap;
+
+    fn blocks() -> BlockRegistry
+    {
+        BlockRegistry::load("assets/blocks.cfg").unwrap()
+    }
+
+    #[test]
+    fn empty_and_solid_sections()
+    {
+        let mut chunk = WorldChunk::new(4, SECTION_HEIGHT, 4);
+        assert_eq!(SectionConnections::compute(&chunk, &blocks(), 0), SectionConnections::all());
+
+        chunk.fill(3);
+        assert_eq!(SectionConnections::compute(&chunk, &blocks(), 0), SectionConnections::default());
+    }
+
+    #[test]
+    fn tunnel_connects_only_its_ends()
+    {
+        // Solid section with a tunnel along x
+        let mut chunk = WorldChunk::new(4, SECTION_HEIGHT, 4);
+        chunk.fill(3);
+        for x in 0..4
+        {
+            chunk.set(x, 5, 2, 0);
+        }
+
+        let connections = SectionConnections::compute(&chunk, &blocks(), 0);
+        assert!(connections.connects(0, 1));
+        assert!(connections.connects(1, 0));
+        assert!(!connections.connects(0, 2));
+        assert!(!connections.connects(4, 5));
+    }
+
+    #[test]
+    fn floor_separates_the_layers_above_and_below()
+    {
+        let mut chunk = WorldChunk::new(4, SECTION_HEIGHT, 4);
+        chunk.fill_layer(8, 3);
+
+        let connections = SectionConnections::compute(&chunk, &blocks(), 0);
+        assert!(!connections.connects(2, 3));
+        assert!(connections.connects(2, 0));
+        assert!(connections.connects(3, 0));
+        assert!(connections.connects(0, 1));
+    }
+
+    #[test]
+    fn walls_hide_the_sections_behind_them()
+    {
+        // A row of chunks along x, one section high. Chunk 2 is solid.
+        let mut connections = HashMap::new();
+        for x in 0..5
+        {
+            let c = if x == 2 { SectionConnections::default() } else { SectionConnections::all() };
+            connections.insert(((x, 0), 0), c);
+        }
+
+        let visible = visible_sections(&[(((0, 0), 0), None)], 1, |s| connections.get(&s).cloned(), |_| true);
+
+        // The wall itself is visible, nothing behind it is
+        assert!(visible.contains(&((1, 0), 0)));
+        assert!(visible.contains(&((2, 0), 0)));
+        assert!(!visible.contains(&((3, 0), 0)));
+        assert!(!visible.contains(&((4, 0), 0)));
+    }
+
+    #[test]
+    fn sections_out_of_view_are_skipped()
+    {
+        let mut connections = HashMap::new();
+        for x in -3..=3
+        {
+            connections.insert(((x, 0), 0), SectionConnections::all());
+        }
+
+        let visible = visible_sections(&[(((0, 0), 0), None)], 1, |s| connections.get(&s).cloned(), |((x, _), _)| x >= 0);
+        assert_eq!(visible.len(), 4);
+    }
+}
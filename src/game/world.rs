@@ -0,0 +1,735 @@
+use std::{ collections::{HashMap, HashSet}, sync::Arc, ops::Range };
+use crate::{ graphics::Gl, WorldChunk, game::ChunkGeneration, game::NoiseType, game::MeshMode, game::game_data::WorldSettings, 
+                game::world_chunk::Attr, game::chunk_workers::{ChunkWorkers, ChunkJob, ChunkResult, NEIGHBOR_OFFSETS}, game::generators::{GeneratorRegistry, ChunkGenerator},
+                game::chunk_mesher::{ChunkVertex, ChunkGeometry, MAX_LOD}, game::region_store::{RegionStore, SaveThread}, game::RayHit, game::raycast::raycast, game::BlockRegistry,
+                game::section_visibility::{SectionCoord, SectionConnections, visible_sections}, game::chunk_storage::SECTION_HEIGHT };
+
+/// How far past an LOD distance (in blocks) a chunk has to be before it drops to that level
+const LOD_HYSTERESIS: f32 = 8.0;
+
+/// Chunk position in chunk units (x, z). Chunks are only laid out
+/// horizontally so there is no y.
+pub type ChunkCoord = (i32, i32);
+
+/// GPU buffers for a loaded chunk
+#[allow(clippy::large_enum_variant)]
+pub enum ChunkBuffers
+{
+    // The ranges are the instances or indices in each section
+    Instanced(glium::VertexBuffer<Attr>, Vec<Range<usize>>),
+    Mesh(glium::VertexBuffer<ChunkVertex>, glium::IndexBuffer<u32>, Vec<Range<usize>>),
+}
+
+pub struct World
+{
+    pub chunk_width: usize,
+    pub chunk_height: usize,
+    pub chunk_depth: usize,
+    pub load_radius: i32,
+    pub max_uploads_per_frame: usize,
+    pub cull_chunk_borders: bool,
+    pub compress_chunks: bool,
+    pub lod_distances: Vec<f32>,
+    mesh_mode: MeshMode,
+
+    // Chunks are shared with the workers so neighbours can be read while meshing
+    chunks: HashMap<ChunkCoord, Arc<WorldChunk>>,
+
+    // Which neighbours were loaded when each chunk's geometry was built and
+    // the level of detail it was built at
+    built_with: HashMap<ChunkCoord, [bool; 4]>,
+    lods: HashMap<ChunkCoord, u32>,
+
+    // Chunks that were already being rebuilt when a neighbour's voxels were
+    // replaced. Their border was culled against the old voxels.
+    stale_borders: HashSet<ChunkCoord>,
+    camera_position: glm::Vec3,
+    buffers: HashMap<ChunkCoord, ChunkBuffers>,
+
+    // Section connectivity for each chunk with buffers, see section_visibility.rs
+    connections: HashMap<ChunkCoord, Vec<SectionConnections>>,
+    generators: Arc<GeneratorRegistry>,
+    blocks: Arc<BlockRegistry>,
+    settings: ChunkGeneration,
+    regions: Option<Arc<RegionStore>>,
+    saver: Option<SaveThread>,
+
+    // The revision each edited chunk was last saved at, so unchanged chunks aren't written again
+    saved_revisions: HashMap<ChunkCoord, u32>,
+
+    // Chunks that have been sent to the workers and the stamp they were sent with.
+    // The stamp goes up every time the generation settings change.
+    workers: ChunkWorkers,
+    pending: HashMap<ChunkCoord, u32>,
+    stamp: u32,
+}
+
+impl World
+{
+    pub fn new(settings: &WorldSettings, chunk_generation: &ChunkGeneration, generators: GeneratorRegistry, blocks: BlockRegistry) -> World
+    {
+        let regions = settings.save_dir.as_ref().map(|dir| Arc::new(RegionStore::new(dir)));
+        World { chunk_width: settings.chunk_width, chunk_height: settings.chunk_height, chunk_depth: settings.chunk_depth,
+                load_radius: settings.load_radius, max_uploads_per_frame: settings.max_uploads_per_frame,
+                cull_chunk_borders: settings.cull_chunk_borders, compress_chunks: settings.compress_chunks, 
+                lod_distances: settings.lod_distances.clone(), mesh_mode: settings.mesh_mode, 
+                chunks: HashMap::new(), built_with: HashMap::new(), lods: HashMap::new(), 
+                stale_borders: HashSet::new(), camera_position: glm::vec3(0.0, 0.0, 0.0), buffers: HashMap::new(), connections: HashMap::new(),
+                generators: Arc::new(generators), blocks: Arc::new(blocks), settings: *chunk_generation,
+                saver: regions.as_ref().map(|r| SaveThread::new(Arc::clone(r))), regions, saved_revisions: HashMap::new(),
+                workers: ChunkWorkers::new(settings.worker_threads), pending: HashMap::new(), stamp: 0 }
+    }
+
+    pub fn blocks(self: &World) -> &BlockRegistry
+    {
+        &self.blocks
+    }
+
+    /// Returns the coordinate of the chunk containing the world space position
+    pub fn chunk_coord_at(self: &World, position: &glm::Vec3) -> ChunkCoord
+    {
+        ((position.x / self.chunk_width as f32).floor() as i32, (position.z / self.chunk_depth as f32).floor() as i32)
+    }
+
+    /// Returns the world space block coordinate (x, z) of the chunk's first column
+    pub fn chunk_origin(self: &World, coord: ChunkCoord) -> (i32, i32)
+    {
+        (coord.0 * self.chunk_width as i32, coord.1 * self.chunk_depth as i32)
+    }
+
+    /// Splits a world block coordinate into the chunk it's in and the block within that chunk
+    pub fn block_to_local(self: &World, x: i32, z: i32) -> (ChunkCoord, (usize, usize))
+    {
+        let (w, d) = (self.chunk_width as i32, self.chunk_depth as i32);
+        ((x.div_euclid(w), z.div_euclid(d)), (x.rem_euclid(w) as usize, z.rem_euclid(d) as usize))
+    }
+
+    /// Block id at the world block coordinate. Blocks in chunks that
+    /// aren't loaded are empty.
+    pub fn get_block(self: &World, x: i32, y: i32, z: i32) -> u16
+    {
+        let (coord, (lx, lz)) = self.block_to_local(x, z);
+        match self.chunks.get(&coord)
+        {
+            Some(chunk) => chunk.get_id(lx as i32, y, lz as i32),
+            None => 0
+        }
+    }
+
+    /// Changes a block and rebuilds the geometry of its chunk (and the neighbouring
+    /// chunk if it's on a border). Returns false if the block isn't in a loaded chunk.
+    pub fn set_block(self: &mut World, x: i32, y: i32, z: i32, id: u16) -> bool
+    {
+        let (coord, (lx, lz)) = self.block_to_local(x, z);
+        if y < 0 || y >= self.chunk_height as i32
+        {
+            return false;
+        }
+
+        match self.chunks.get_mut(&coord)
+        {
+            Some(chunk) =>
+            {
+                // Copies the chunk if a worker is still using it
+                let chunk = Arc::make_mut(chunk);
+                chunk.set(lx, y as usize, lz, id);
+                chunk.revision += 1;
+            },
+            None => return false
+        }
+
+        // If the chunk is already being rebuilt the result is
+        // older than this edit, that's handled when it comes back
+        if !self.pending.contains_key(&coord)
+        {
+            self.request_remesh(coord);
+        }
+
+        let mut neighbors = vec![];
+        if lx == 0 { neighbors.push((coord.0 - 1, coord.1)); }
+        if lx == self.chunk_width - 1 { neighbors.push((coord.0 + 1, coord.1)); }
+        if lz == 0 { neighbors.push((coord.0, coord.1 - 1)); }
+        if lz == self.chunk_depth - 1 { neighbors.push((coord.0, coord.1 + 1)); }
+
+        for neighbor in neighbors
+        {
+            if self.chunks.contains_key(&neighbor) && !self.pending.contains_key(&neighbor)
+            {
+                self.request_remesh(neighbor);
+            }
+        }
+
+        true
+    }
+
+    /// Finds the first block in a loaded chunk along the ray. Positions
+    /// and the hit are in world space.
+    pub fn raycast(self: &World, origin: &glm::Vec3, direction: &glm::Vec3, max_distance: f32) -> Option<RayHit>
+    {
+        raycast(origin, direction, max_distance, |x, y, z| self.blocks.is_solid(self.get_block(x, y, z)))
+    }
+
+    pub fn get_chunk(self: &World, coord: ChunkCoord) -> Option<&WorldChunk>
+    {
+        self.chunks.get(&coord).map(|chunk| &**chunk)
+    }
+
+    pub fn iter(self: &World) -> impl Iterator<Item = (&ChunkCoord, &WorldChunk)>
+    {
+        self.chunks.iter().map(|(coord, chunk)| (coord, &**chunk))
+    }
+
+    /// Iterates over every chunk that is ready to be drawn
+    pub fn chunk_buffers(self: &World) -> impl Iterator<Item = (&ChunkCoord, &ChunkBuffers)>
+    {
+        self.buffers.iter()
+    }
+
+    /// Sections that could be seen from the position (occlusion culling). Only sections
+    /// in_view returns true for are walked through. Returns None if the position isn't in
+    /// or above a chunk with geometry, everything should be drawn then.
+    pub fn visible_sections<V: Fn(SectionCoord) -> bool>(self: &World, position: &glm::Vec3, in_view: V) -> Option<HashSet<SectionCoord>>
+    {
+        // Blocks are centered on integer coordinates
+        let (x, y, z) = (position.x.round() as i32, position.y.round() as i32, position.z.round() as i32);
+        let (coord, _) = self.block_to_local(x, z);
+        if y < 0 || !self.connections.contains_key(&coord)
+        {
+            return None;
+        }
+
+        let num_sections = self.chunk_height.div_ceil(SECTION_HEIGHT);
+        let section = y as usize / SECTION_HEIGHT;
+
+        // Above the world every top section in view can be seen through its top face
+        let start: Vec<(SectionCoord, Option<usize>)> = if section >= num_sections
+        {
+            self.connections.keys().map(|c| ((*c, num_sections - 1), Some(2))).filter(|(s, _)| in_view(*s)).collect()
+        }
+        else
+        {
+            vec![((coord, section), None)]
+        };
+
+        Some(visible_sections(&start, num_sections, |(c, s)| self.connections.get(&c).and_then(|sections| sections.get(s).cloned()), in_view))
+    }
+
+    pub fn get_mesh_mode(self: &World) -> MeshMode
+    {
+        self.mesh_mode
+    }
+
+    /// Switches how chunk geometry is built and rebuilds every loaded chunk
+    pub fn set_mesh_mode(self: &mut World, mesh_mode: MeshMode)
+    {
+        if mesh_mode == self.mesh_mode
+        {
+            return;
+        }
+
+        self.mesh_mode = mesh_mode;
+
+        // Only the geometry needs rebuilding, the voxel data is still good
+        self.stamp = self.stamp.wrapping_add(1);
+        let coords: Vec<ChunkCoord> = self.chunks.keys().cloned().collect();
+        for coord in coords
+        {
+            if !self.pending.contains_key(&coord)
+            {
+                self.request_remesh(coord);
+            }
+        }
+    }
+
+    pub fn num_loaded(self: &World) -> usize
+    {
+        self.chunks.len()
+    }
+
+    /// Number of chunks waiting on the worker threads
+    pub fn num_pending(self: &World) -> usize
+    {
+        self.pending.len()
+    }
+
+    /// Adds a generator (or replaces the existing one) for the noise type.
+    /// Call regenerate_all afterwards if the noise type is in use.
+    pub fn register_generator<G: ChunkGenerator + 'static>(self: &mut World, noise_type: NoiseType, generator: G)
+    {
+        // Jobs already sent to the workers keep the old registry
+        Arc::make_mut(&mut self.generators).register(noise_type, generator);
+    }
+
+    /// Regenerates every loaded chunk with new settings. The old
+    /// chunks stay visible until the new ones are ready. Edited chunks
+    /// are saved first so chunks that come back from disk are up to date.
+    pub fn regenerate_all(self: &mut World, chunk_generation: &ChunkGeneration)
+    {
+        self.save_all();
+
+        self.settings = *chunk_generation;
+        self.stamp = self.stamp.wrapping_add(1);
+
+        let coords: Vec<ChunkCoord> = self.chunks.keys().cloned().collect();
+        for coord in coords
+        {
+            // Chunks already in progress get requeued when they come back
+            if !self.pending.contains_key(&coord)
+            {
+                self.request_chunk(coord);
+            }
+        }
+    }
+
+    /// Writes every loaded chunk that was edited to the region files (if saving is enabled)
+    pub fn save_all(self: &mut World)
+    {
+        let coords: Vec<ChunkCoord> = self.chunks.keys().cloned().collect();
+        self.save_chunks(&coords);
+    }
+
+    /// Queues the chunks on the save thread. Only chunks edited since they were generated
+    /// or loaded, and since they were last saved, are written.
+    fn save_chunks(self: &mut World, coords: &[ChunkCoord])
+    {
+        let saver = match &self.saver
+        {
+            Some(s) => s,
+            None => return
+        };
+
+        // Hand made chunks can't be matched to generation settings when loading, and
+        // animated chunks are replaced too often to be worth saving
+        let chunks: Vec<(ChunkCoord, Arc<WorldChunk>)> = coords.iter()
+            .filter_map(|coord| self.chunks.get(coord).map(|chunk| (*coord, Arc::clone(chunk))))
+            .filter(|(_, chunk)| chunk.generation.is_some_and(|g| g.noise_type != NoiseType::ANIMATED_3D))
+            .filter(|(coord, chunk)| chunk.revision > 0 && self.saved_revisions.get(coord) != Some(&chunk.revision))
+            .collect();
+
+        for (coord, chunk) in chunks.iter()
+        {
+            self.saved_revisions.insert(*coord, chunk.revision);
+        }
+
+        saver.save(chunks);
+    }
+
+    fn request_chunk(self: &mut World, coord: ChunkCoord)
+    {
+        self.submit_job(coord, None);
+    }
+
+    /// Rebuilds the geometry of a loaded chunk without generating it again
+    fn request_remesh(self: &mut World, coord: ChunkCoord)
+    {
+        let chunk = self.chunks.get(&coord).cloned();
+        self.submit_job(coord, chunk);
+    }
+
+    fn submit_job(self: &mut World, coord: ChunkCoord, chunk: Option<Arc<WorldChunk>>)
+    {
+        let lod = self.desired_lod(coord);
+
+        // Borders are only hidden between chunks with the same level of detail, LOD
+        // meshes keep their border faces so they close the gap to their neighbours
+        let mut neighbors: [Option<Arc<WorldChunk>>; 4] = [None, None, None, None];
+        let mut loaded_neighbors = [false; 4];
+        if self.cull_chunk_borders
+        {
+            for (i, offset) in NEIGHBOR_OFFSETS.iter().enumerate()
+            {
+                let neighbor = (coord.0 + offset.0, coord.1 + offset.1);
+                loaded_neighbors[i] = self.chunks.contains_key(&neighbor);
+                if lod == 0 && self.lods.get(&neighbor) == Some(&0)
+                {
+                    neighbors[i] = self.chunks.get(&neighbor).cloned();
+                }
+            }
+        }
+
+        let job = ChunkJob { coord, origin: self.chunk_origin(coord),
+                            dimensions: (self.chunk_width, self.chunk_height, self.chunk_depth),
+                            settings: self.settings, generators: Arc::clone(&self.generators), blocks: Arc::clone(&self.blocks),
+                            mesh_mode: self.mesh_mode, lod, chunk, neighbors, loaded_neighbors,
+                            compress: self.compress_chunks, regions: self.regions.clone(), stamp: self.stamp };
+
+        self.workers.submit(job);
+        self.pending.insert(coord, self.stamp);
+    }
+
+    /// Rebuilds the loaded chunks next to coord that were built before it was loaded,
+    /// so their border blocks can be hidden
+    fn remesh_neighbors_of(self: &mut World, coord: ChunkCoord)
+    {
+        if !self.cull_chunk_borders
+        {
+            return;
+        }
+
+        for (i, offset) in NEIGHBOR_OFFSETS.iter().enumerate()
+        {
+            let neighbor = (coord.0 + offset.0, coord.1 + offset.1);
+
+            // The neighbour's side facing coord is the opposite direction (+X <-> -X, +Z <-> -Z)
+            let facing = i ^ 1;
+            let needs_remesh = match self.built_with.get(&neighbor)
+            {
+                Some(built_with) => !built_with[facing],
+                None => false
+            };
+
+            if needs_remesh && !self.pending.contains_key(&neighbor)
+            {
+                self.request_remesh(neighbor);
+            }
+        }
+    }
+
+    /// Rebuilds every loaded chunk next to coord. Used when coord's voxels or level of
+    /// detail changed, the neighbours' borders were built against the old ones.
+    fn remesh_all_neighbors_of(self: &mut World, coord: ChunkCoord)
+    {
+        if !self.cull_chunk_borders
+        {
+            return;
+        }
+
+        for offset in NEIGHBOR_OFFSETS.iter()
+        {
+            let neighbor = (coord.0 + offset.0, coord.1 + offset.1);
+            if !self.chunks.contains_key(&neighbor)
+            {
+                continue;
+            }
+
+            // A job in flight may have copied coord's old voxels, rebuild it once it's back
+            if self.pending.contains_key(&neighbor)
+            {
+                self.stale_borders.insert(neighbor);
+            }
+            else
+            {
+                self.request_remesh(neighbor);
+            }
+        }
+    }
+
+    /// Level of detail for a chunk at this distance from the camera
+    pub fn lod_for_distance(self: &World, distance: f32) -> u32
+    {
+        (self.lod_distances.iter().filter(|d| distance >= **d).count() as u32).min(MAX_LOD)
+    }
+
+    /// Horizontal distance from the camera to the closest point of the chunk
+    fn distance_to_chunk(self: &World, coord: ChunkCoord) -> f32
+    {
+        let origin = self.chunk_origin(coord);
+        let min = (origin.0 as f32 - 0.5, origin.1 as f32 - 0.5);
+        let max = (min.0 + self.chunk_width as f32, min.1 + self.chunk_depth as f32);
+
+        let dx = (min.0 - self.camera_position.x).max(self.camera_position.x - max.0).max(0.0);
+        let dz = (min.1 - self.camera_position.z).max(self.camera_position.z - max.1).max(0.0);
+        (dx * dx + dz * dz).sqrt()
+    }
+
+    /// The level of detail a chunk should be built at. Chunks only switch to a lower
+    /// detail once they are LOD_HYSTERESIS past the distance so they don't flicker
+    /// between levels while the camera moves along the boundary.
+    fn desired_lod(self: &World, coord: ChunkCoord) -> u32
+    {
+        let distance = self.distance_to_chunk(coord);
+        let target = self.lod_for_distance(distance);
+        match self.lods.get(&coord)
+        {
+            Some(&current) if target > current => self.lod_for_distance(distance - LOD_HYSTERESIS).max(current),
+            _ => target
+        }
+    }
+
+    /// Number of chunks drawn at each level of detail
+    pub fn lod_counts(self: &World) -> [usize; MAX_LOD as usize + 1]
+    {
+        let mut counts = [0; MAX_LOD as usize + 1];
+        for lod in self.lods.values()
+        {
+            counts[*lod as usize] += 1;
+        }
+
+        counts
+    }
+
+    fn in_range(center: ChunkCoord, coord: ChunkCoord, radius: i32) -> bool
+    {
+        (coord.0 - center.0).abs() <= radius && (coord.1 - center.1).abs() <= radius
+    }
+
+    /// Requests chunks within load_radius of the camera, unloads the ones that
+    /// have moved out of range and uploads chunks the workers have finished.
+    /// Must be called before drawing begins.
+    pub fn update(self: &mut World, display: &Gl, camera_position: &glm::Vec3)
+    {
+        let center = self.chunk_coord_at(camera_position);
+        let radius = self.load_radius;
+        self.camera_position = *camera_position;
+
+        // Unload with one chunk of slack so moving back and forth over
+        // a chunk border does not keep regenerating the same chunks
+        let unloading: Vec<ChunkCoord> = self.chunks.keys().filter(|coord| !World::in_range(center, **coord, radius + 1))
+                                                        .cloned().collect();
+        self.save_chunks(&unloading);
+
+        self.chunks.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+        self.built_with.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+        self.lods.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+        self.stale_borders.retain(|coord| World::in_range(center, *coord, radius + 1));
+        self.saved_revisions.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+        self.buffers.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+        self.connections.retain(|coord, _| World::in_range(center, *coord, radius + 1));
+
+        // Request the missing chunks closest to the camera first
+        let mut missing: Vec<ChunkCoord> = vec![];
+        for x in (center.0 - radius)..(center.0 + radius + 1)
+        {
+            for z in (center.1 - radius)..(center.1 + radius + 1)
+            {
+                if !self.chunks.contains_key(&(x, z)) && !self.pending.contains_key(&(x, z))
+                {
+                    missing.push((x, z));
+                }
+            }
+        }
+
+        missing.sort_by_key(|c| (c.0 - center.0).pow(2) + (c.1 - center.1).pow(2));
+
+        for coord in missing
+        {
+            self.request_chunk(coord);
+        }
+
+        // Rebuild chunks that have moved into a different level of detail
+        let lod_changes: Vec<ChunkCoord> = self.lods.iter()
+            .filter(|(coord, lod)| !self.pending.contains_key(coord) && self.desired_lod(**coord) != **lod)
+            .map(|(coord, _)| *coord)
+            .collect();
+
+        for coord in lod_changes
+        {
+            self.request_remesh(coord);
+        }
+
+        // Upload finished chunks. Only a few per frame so a burst of
+        // results doesn't cause a hitch.
+        for _ in 0..self.max_uploads_per_frame
+        {
+            let result = match self.workers.try_recv()
+            {
+                Some(r) => r,
+                None => break
+            };
+
+            self.pending.remove(&result.coord);
+
+            if !World::in_range(center, result.coord, radius + 1)
+            {
+                self.stale_borders.remove(&result.coord);
+                continue;
+            }
+
+            let coord = result.coord;
+            let buffers = match self.accept_result(result)
+            {
+                ChunkGeometry::Instances(instances, sections) => 
+                    ChunkBuffers::Instanced(glium::VertexBuffer::dynamic(&**display, &instances).unwrap(), sections),
+
+                ChunkGeometry::Mesh(mesh) => 
+                    ChunkBuffers::Mesh(glium::VertexBuffer::new(&**display, &mesh.vertices).unwrap(),
+                                        glium::IndexBuffer::new(&**display, glium::index::PrimitiveType::TrianglesList, &mesh.indices).unwrap(),
+                                        mesh.sections),
+            };
+
+            self.buffers.insert(coord, buffers);
+        }
+    }
+
+    /// Stores a finished chunk and requests whatever has to be rebuilt because of it.
+    /// Returns the geometry to upload.
+    fn accept_result(self: &mut World, result: ChunkResult) -> ChunkGeometry
+    {
+        let ChunkResult { coord, chunk, geometry, stamp, lod, neighbors, connections } = result;
+
+        // The chunk was edited while it was being rebuilt. Keep the edited
+        // voxels, use the geometry for now and build it again.
+        let edited = match self.chunks.get(&coord)
+        {
+            Some(old) => old.generation == chunk.generation && old.revision > chunk.revision,
+            None => false
+        };
+
+        // The voxels are different from the ones the neighbours were built against
+        let replaced = match self.chunks.get(&coord)
+        {
+            Some(old) => !edited && (old.generation != chunk.generation || old.revision != chunk.revision),
+            None => false
+        };
+
+        self.connections.insert(coord, connections);
+        self.built_with.insert(coord, neighbors);
+        let lod_changed = match self.lods.insert(coord, lod)
+        {
+            Some(old) => old != lod,
+            None => false
+        };
+        if !edited
+        {
+            self.chunks.insert(coord, Arc::new(chunk));
+        }
+        if replaced
+        {
+            self.saved_revisions.remove(&coord);
+        }
+
+        // The settings changed while this one was being built. Keep it
+        // (it's better than nothing) but ask for a fresh one. If only
+        // the mesh mode changed the voxels can be reused.
+        if edited
+        {
+            self.request_remesh(coord);
+        }
+        else if stamp != self.stamp
+        {
+            if self.chunks[&coord].generation == Some(self.settings)
+            {
+                self.request_remesh(coord);
+            }
+            else
+            {
+                self.request_chunk(coord);
+            }
+        }
+
+        // A neighbour's voxels were replaced while this was being built
+        if self.stale_borders.remove(&coord) && !self.pending.contains_key(&coord)
+        {
+            self.request_remesh(coord);
+        }
+
+        self.remesh_neighbors_of(coord);
+
+        // Neighbours were built hiding (or keeping) their border against the old voxels or level of detail
+        if replaced || lod_changed
+        {
+            self.remesh_all_neighbors_of(coord);
+        }
+
+        geometry
+    }
+}
+
+impl Drop for World
+{
+    fn drop(&mut self)
+    {
+        self.save_all();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{ utils::{ FractalMode, NoiseAlgorithm }, game::ChunkNeighbors };
+
+    fn world(save_dir: Option<String>) -> World
+    {
+        let settings = WorldSettings { chunk_width: 8, chunk_height: 8, chunk_depth: 8, load_radius: 1,
+                                        max_uploads_per_frame: 2, cull_chunk_borders: true, compress_chunks: false,
+                                        save_dir, worker_threads: 2, mesh_mode: MeshMode::INSTANCED,
+                                        lod_distances: vec![], animation_speed: 0.25, animation_rate: 5.0 };
+
+        World::new(&settings, &generation(2.0), GeneratorRegistry::with_defaults(), BlockRegistry::load("assets/blocks.cfg").unwrap())
+    }
+
+    /// Random noise with a threshold of 0 fills every block, above 1 leaves the chunk empty
+    fn generation(threshold: f32) -> ChunkGeneration
+    {
+        ChunkGeneration { noise_type: NoiseType::RANDOM_3D, offset: (0.0, 0.0), zoom_factor: 0.01,
+                            sx_scale: 32.0, threshold, threshold_falloff: 20,
+                            octaves: 3, bias: 0.5, seed: Some([0; 32]),
+                            fractal_mode: FractalMode::FBM, lacunarity: 2.0, gain: 0.5,
+                            warp_strength: 1.0, warp_frequency: 1.0, double_warp: false,
+                            base_noise: NoiseAlgorithm::SIMPLEX, time: 0.0 }
+    }
+
+    /// Takes results from the workers until nothing is pending. Returns the
+    /// number of instances each chunk was last built with.
+    fn settle(world: &mut World, instances: &mut HashMap<ChunkCoord, usize>)
+    {
+        while world.num_pending() > 0
+        {
+            match world.workers.try_recv()
+            {
+                Some(result) =>
+                {
+                    world.pending.remove(&result.coord);
+                    let coord = result.coord;
+                    if let ChunkGeometry::Instances(built, _) = world.accept_result(result)
+                    {
+                        instances.insert(coord, built.len());
+                    }
+                },
+                None => std::thread::sleep(std::time::Duration::from_millis(1))
+            }
+        }
+    }
+
+    #[test]
+    fn regenerated_neighbors_rebuild_their_shared_border()
+    {
+        let mut world = world(None);
+        let mut instances = HashMap::new();
+        world.request_chunk((0, 0));
+        world.request_chunk((1, 0));
+        settle(&mut world, &mut instances);
+        assert_eq!(instances[&(0, 0)], 0);
+
+        // Both jobs are sent before either comes back, so each one copies the other's empty voxels
+        world.regenerate_all(&generation(0.0));
+        settle(&mut world, &mut instances);
+
+        let mut left = (**world.chunks.get(&(0, 0)).unwrap()).clone();
+        let mut right = (**world.chunks.get(&(1, 0)).unwrap()).clone();
+        let blocks = BlockRegistry::load("assets/blocks.cfg").unwrap();
+        let left_expected = left.gen_instance_data(&ChunkNeighbors { pos_x: Some(&right), ..ChunkNeighbors::none() }, &blocks, false).len();
+        let right_expected = right.gen_instance_data(&ChunkNeighbors { neg_x: Some(&left), ..ChunkNeighbors::none() }, &blocks, false).len();
+        let unculled = left.gen_instance_data(&ChunkNeighbors::none(), &blocks, false).len();
+
+        assert!(left_expected < unculled);
+        assert_eq!(instances[&(0, 0)], left_expected);
+        assert_eq!(instances[&(1, 0)], right_expected);
+    }
+
+    #[test]
+    fn only_edited_chunks_are_saved()
+    {
+        let dir = std::env::temp_dir().join(format!("block_world_world_saves_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut world = world(Some(dir.to_string_lossy().into_owned()));
+        world.request_chunk((0, 0));
+        world.request_chunk((1, 0));
+        settle(&mut world, &mut HashMap::new());
+
+        world.set_block(3, 2, 3, 1);
+        world.save_all();
+        assert_eq!(world.saved_revisions.get(&(0, 0)), Some(&1));
+        assert_eq!(world.saved_revisions.get(&(1, 0)), None);
+        drop(world);
+
+        let regions = RegionStore::new(&dir);
+        let saved = regions.load_chunk((0, 0), &generation(2.0)).unwrap().unwrap();
+        assert_eq!(saved.get(3, 2, 3), 1);
+        assert!(regions.load_chunk((1, 0), &generation(2.0)).unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,74 +1,71 @@
 
-use std::{fmt, rc::Rc};
+use std::fmt;
+use crate::game::{ ChunkGeneration, RayHit, BlockRegistry, raycast::raycast, chunk_mesher::FACES, chunk_storage::{ChunkStorage, PaletteStorage, SECTION_HEIGHT} };
 
 // use crate::graphics::Mesh;
 
-#[derive(Clone, Debug)]
-pub struct Voxel
-{
-    pub id: u16,
-    pub visible: bool
-}
-
 #[derive(Copy, Clone)]
 pub struct Attr 
 {
     offset: (f32, f32, f32),
-    texture: u32,
-}
 
-implement_vertex!(Attr, offset, texture);
-
-#[derive(Clone)]
-pub struct Layer
-{
-    pub layer: Vec<Vec<Voxel>>,
-   // combined: Mesh
+    // Texture slots for the top, bottom and sides, the vertex shader
+    // picks one for each face of the cube from its normal
+    textures: [u32; 3],
 }
 
-impl fmt::Display for Layer
+implement_vertex!(Attr, offset, textures);
+
+impl Attr
 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result 
+    /// The section (see chunk_storage::SECTION_HEIGHT) the block is in
+    pub fn section(self: &Attr) -> usize
     {
-        let mut output: String = format!("");
-        for row in self.layer.iter()
-        {
-            output = format!("{}\n{:?}", output, row);
-        }
-
-        write!(f, "{}", output)
+        self.offset.1 as usize / SECTION_HEIGHT
     }
 }
 
-impl Layer
+/// The chunks around a chunk, used to hide blocks on the chunk border that are
+/// covered by the next chunk. Missing neighbours are treated as empty space.
+#[derive(Clone, Copy, Default)]
+pub struct ChunkNeighbors<'a>
 {
-    pub fn new(width: usize, depth: usize) -> Layer
-    {
-        Layer { layer: vec![vec![Voxel { id: 0, visible: true }; width]; depth] }
-    }
+    pub pos_x: Option<&'a WorldChunk>,
+    pub neg_x: Option<&'a WorldChunk>,
+    pub pos_z: Option<&'a WorldChunk>,
+    pub neg_z: Option<&'a WorldChunk>,
+}
 
-    pub fn fill_with(self: &mut Layer, value: u16)
+impl ChunkNeighbors<'_>
+{
+    pub fn none() -> ChunkNeighbors<'static>
     {
-        for row in self.layer.iter_mut()
-        {
-            for i in 0..row.len()
-            {
-                row[i] = Voxel { id: value, visible: true };
-            } 
-        }
+        ChunkNeighbors { pos_x: None, neg_x: None, pos_z: None, neg_z: None }
     }
 }
 
+/// Block ids for a chunk stored in one flat array. Blocks in a layer (same y)
+/// are next to each other with z changing fastest, so looping y, x, z walks
+/// straight through memory. Chunks can be compressed (see chunk_storage.rs)
+/// to save memory while they are only being kept around, get and set still
+/// work but are slower.
+#[derive(Clone)]
 pub struct WorldChunk
 {
     pub width: usize,
     pub height: usize,
     pub depth: usize,
-    pub layers: Vec<Layer>,
-    pub instance_buff: Option<Rc<glium::VertexBuffer<Attr>>>,
+    blocks: ChunkStorage,
+
+    /// The settings the chunk was generated with, None for hand made chunks
+    pub generation: Option<ChunkGeneration>,
+
+    /// Goes up every time the chunk is edited after it's generated
+    pub revision: u32,
     pub total_blocks: u32,
     pub hidden_blocks: u32,
     pub rendered_blocks: u32,
+    pub triangles: u32,
 }
 
 impl fmt::Display for WorldChunk
@@ -76,11 +73,18 @@ impl fmt::Display for WorldChunk
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result 
     {
         let mut output: String = format!("WorldChunk ({}, {}, {})\n____________________________________________\n", 
-                                            self.layers.len(), self.layers[0].layer.len(), self.height);
+                                            self.width, self.height, self.depth);
 
-        for i in 0..self.layers.len()
+        for (i, layer) in self.layers().enumerate()
         {
-            output = format!("{}\nLayer #{}{}\n", output, i, self.layers[i]);
+            output = format!("{}\nLayer #{}", output, i);
+            let layer: Vec<u16> = layer.collect();
+            for row in layer.chunks(self.depth)
+            {
+                output = format!("{}\n{:?}", output, row);
+            }
+
+            output = format!("{}\n", output);
         }
 
         write!(f, "{}", output)
@@ -91,168 +95,667 @@ impl WorldChunk
 {
     pub fn new(width: usize, height: usize, depth: usize,) -> WorldChunk
     {
-        let layers = vec![Layer::new(width, depth); height];
+        WorldChunk { width, height, depth, blocks: ChunkStorage::Flat(vec![0; width * height * depth]), generation: None, revision: 0,
+                    total_blocks: 0, hidden_blocks: 0, rendered_blocks: 0, triangles: 0 }
+    }
 
-        WorldChunk { width, height, depth, layers, instance_buff: None, total_blocks: 0, hidden_blocks: 0, rendered_blocks: 0 }
+    fn index(self: &WorldChunk, x: usize, y: usize, z: usize) -> usize
+    {
+        (y * self.width + x) * self.depth + z
+    }
+
+    /// Block id at the position. Panics if the position is outside the chunk.
+    pub fn get(self: &WorldChunk, x: usize, y: usize, z: usize) -> u16
+    {
+        self.blocks.get(self.index(x, y, z))
+    }
+
+    pub fn set(self: &mut WorldChunk, x: usize, y: usize, z: usize, id: u16)
+    {
+        let i = self.index(x, y, z);
+        self.blocks.set(i, id);
     }
 
     pub fn make_empty(self: &mut WorldChunk)
     {
-        for l in 0..self.layers.len()
+        self.fill(0);
+    }
+
+    pub fn fill(self: &mut WorldChunk, id: u16)
+    {
+        self.blocks.fill(id);
+    }
+
+    pub fn fill_layer(self: &mut WorldChunk, y: usize, id: u16)
+    {
+        for x in 0..self.width
         {
-            self.layers[l].fill_with(0);
+            for z in 0..self.depth
+            {
+                self.set(x, y, z, id);
+            }
         }
     }
 
-    pub fn get_instance_buffer(self: &mut WorldChunk, display: &glium::Display, force_regen: bool) -> Rc<glium::VertexBuffer<Attr>>
+    /// The blocks at height y, ordered by x then z
+    pub fn layer(self: &WorldChunk, y: usize) -> impl Iterator<Item = u16> + '_
+    {
+        let start = self.index(0, y, 0);
+        (start..start + self.width * self.depth).map(move |i| self.blocks.get(i))
+    }
+
+    /// Iterates over the layers from the bottom up
+    pub fn layers(self: &WorldChunk) -> impl Iterator<Item = impl Iterator<Item = u16> + '_>
+    {
+        (0..self.height).map(move |y| self.layer(y))
+    }
+
+    /// Iterates over the blocks in the column at (x, z) from the bottom up
+    pub fn column(self: &WorldChunk, x: usize, z: usize) -> impl Iterator<Item = u16> + '_
+    {
+        (0..self.height).map(move |y| self.get(x, y, z))
+    }
+
+    /// Iterates over every column as (x, z, blocks from the bottom up)
+    pub fn columns(self: &WorldChunk) -> impl Iterator<Item = (usize, usize, impl Iterator<Item = u16> + '_)>
+    {
+        (0..self.width).flat_map(move |x| (0..self.depth).map(move |z| (x, z, self.column(x, z))))
+    }
+
+    /// Switches to palette storage with uniform sections collapsed. Calling
+    /// this on a compressed chunk re-collapses sections that set has filled in.
+    pub fn compress(self: &mut WorldChunk)
     {
-        if self.instance_buff.is_none() || force_regen
+        let ids = match &self.blocks
         {
-            self.gen_instance_buffer(display, false);
-        }
+            ChunkStorage::Flat(blocks) => blocks.clone(),
+            ChunkStorage::Palette(palette) => palette.to_ids()
+        };
+
+        let section_volume = self.width * self.depth * SECTION_HEIGHT;
+        self.blocks = ChunkStorage::Palette(PaletteStorage::from_ids(&ids, section_volume));
+    }
 
-        match &self.instance_buff
+    /// Switches back to one u16 per block for fast access
+    pub fn decompress(self: &mut WorldChunk)
+    {
+        if let ChunkStorage::Palette(palette) = &self.blocks
         {
-            Some(ib) => ib.clone(),
-            None => panic!("ERROR RETURNING INSTANCE BUFFER!!!")
+            self.blocks = ChunkStorage::Flat(palette.to_ids());
         }
     }
 
-    fn gen_instance_buffer(self: &mut WorldChunk, display: &glium::Display, debug_output: bool)
+    pub fn is_compressed(self: &WorldChunk) -> bool
     {
-        let mut total_blocks = 0;
-        let mut skipped_blocks = 0;
-        self.instance_buff = {
-            let mut data: Vec<Attr> = vec![];
-            let cube_size = 1.0;
-            for l in 0..self.layers.len()
+        matches!(self.blocks, ChunkStorage::Palette(_))
+    }
+
+    /// Bytes used by the block ids
+    pub fn memory_usage(self: &WorldChunk) -> usize
+    {
+        self.blocks.memory_usage()
+    }
+
+    /// Makes a lower resolution copy where each block covers factor^3 blocks of this
+    /// chunk. A block is solid if at least half of the blocks it covers are, and takes
+    /// the id of the highest of them so the surface keeps its look from above.
+    pub fn downsample(self: &WorldChunk, factor: usize) -> WorldChunk
+    {
+        let mut coarse = WorldChunk::new(self.width.div_ceil(factor), self.height.div_ceil(factor), self.depth.div_ceil(factor));
+        for cy in 0..coarse.height
+        {
+            for cx in 0..coarse.width
             {
-                for r in 0..self.layers[l].layer.len()
+                for cz in 0..coarse.depth
                 {
-                    for c in 0..self.layers[l].layer[r].len()
+                    let (mut solid, mut total, mut top_id) = (0, 0, 0);
+                    for y in (cy * factor..((cy + 1) * factor).min(self.height)).rev()
                     {
-                        //let block = &mut self.layers[l].layer[r][c];
-                        
-                        if self.layers[l].layer[r][c].id < 1
+                        for x in cx * factor..((cx + 1) * factor).min(self.width)
                         {
-                            continue;
+                            for z in cz * factor..((cz + 1) * factor).min(self.depth)
+                            {
+                                let id = self.get(x, y, z);
+                                total += 1;
+                                if id > 0
+                                {
+                                    solid += 1;
+                                    if top_id == 0
+                                    {
+                                        top_id = id;
+                                    }
+                                }
+                            }
                         }
+                    }
 
-                        total_blocks += 1;
-                        
-                        let mut skip = true;
+                    if solid * 2 >= total && solid > 0
+                    {
+                        coarse.set(cx, cy, cz, top_id);
+                    }
+                }
+            }
+        }
 
-                        if self.has_neighbor_gap(r, l, c)
-                        {
-                            skip = false;
-                        }                        
+        coarse
+    }
 
-                        if skip
-                        {
-                            skipped_blocks += 1;
-                            continue;
-                        }
+    /// Finds the first block along the ray. The origin is in chunk space (block
+    /// (x, y, z) is centered on (x, y, z)) and the hit is in block coordinates.
+    pub fn raycast(self: &WorldChunk, origin: &glm::Vec3, direction: &glm::Vec3, max_distance: f32, blocks: &BlockRegistry) -> Option<RayHit>
+    {
+        raycast(origin, direction, max_distance, |x, y, z| blocks.is_solid(self.get_id(x, y, z)))
+    }
 
-                        self.layers[l].layer[r][c].visible = !skip;
+    /// Returns the block id at the position, or 0 (empty) if it's outside the chunk
+    pub fn get_id(self: &WorldChunk, x: i32, y: i32, z: i32) -> u16
+    {
+        if x < 0 || y < 0 || z < 0
+            || x >= self.width as i32
+            || y >= self.height as i32
+            || z >= self.depth as i32
+        {
+            return 0;
+        }
 
-                        let x = (c as f32) * cube_size;
-                        let y = (l as f32) * cube_size;
-                        let z = (r as f32) * cube_size;
-                        let texture = self.layers[l].layer[r][c].id as u32;
+        self.get(x as usize, y as usize, z as usize)
+    }
 
-                        data.push(Attr { offset: (x, y, z), texture: texture });
+    /// Like get_id but positions just past the x/z edges are looked up in the
+    /// neighbouring chunks. Anything that still isn't in a chunk is empty.
+    pub fn get_id_with_neighbors(self: &WorldChunk, x: i32, y: i32, z: i32, neighbors: &ChunkNeighbors) -> u16
+    {
+        let (w, d) = (self.width as i32, self.depth as i32);
+        let in_x = x >= 0 && x < w;
+        let in_z = z >= 0 && z < d;
+
+        let neighbor = match (in_x, in_z)
+        {
+            (true, true) => return self.get_id(x, y, z),
+            (false, true) if x < 0 => neighbors.neg_x.map(|c| (c, x + w, z)),
+            (false, true) => neighbors.pos_x.map(|c| (c, x - w, z)),
+            (true, false) if z < 0 => neighbors.neg_z.map(|c| (c, x, z + d)),
+            (true, false) => neighbors.pos_z.map(|c| (c, x, z - d)),
+            (false, false) => None
+        };
+
+        match neighbor
+        {
+            Some((chunk, nx, nz)) => chunk.get_id(nx, y, nz),
+            None => 0
+        }
+    }
+
+    /// A block is visible if any of its six face neighbours is empty or transparent
+    pub fn is_block_visible(self: &WorldChunk, x: i32, y: i32, z: i32, neighbors: &ChunkNeighbors, blocks: &BlockRegistry) -> bool
+    {
+        let id = self.get_id(x, y, z);
+        if id < 1
+        {
+            return false;
+        }
+
+        FACES.iter().any(|face| !blocks.hides_face(id, self.get_id_with_neighbors(x + face.dir.0, y + face.dir.1, z + face.dir.2, neighbors)))
+    }
+
+    /// Updates the block counts
+    pub fn update_visibility(self: &mut WorldChunk, neighbors: &ChunkNeighbors, blocks: &BlockRegistry)
+    {
+        let mut total_blocks = 0;
+        let mut rendered_blocks = 0;
+
+        for y in 0..self.height
+        {
+            for x in 0..self.width
+            {
+                for z in 0..self.depth
+                {
+                    if self.get(x, y, z) < 1
+                    {
+                        continue;
+                    }
+
+                    total_blocks += 1;
+
+                    if self.is_block_visible(x as i32, y as i32, z as i32, neighbors, blocks)
+                    {
+                        rendered_blocks += 1;
                     }
                 }
             }
+        }
 
-            if debug_output
+        self.total_blocks = total_blocks;
+        self.rendered_blocks = rendered_blocks;
+        self.hidden_blocks = total_blocks - rendered_blocks;
+    }
+
+    /// Builds the per-instance data (one entry per visible block) and updates the
+    /// block counts. This only touches CPU memory so it can run on a worker thread, 
+    /// the result still needs to be uploaded into a VertexBuffer.
+    pub fn gen_instance_data(self: &mut WorldChunk, neighbors: &ChunkNeighbors, blocks: &BlockRegistry, debug_output: bool) -> Vec<Attr>
+    {
+        let mut data: Vec<Attr> = vec![];
+        let mut total_blocks = 0;
+        let cube_size = 1.0;
+        for l in 0..self.height
+        {
+            for r in 0..self.width
             {
-                println!("Chunk Dimensions ({}x{}x{})\ntotal visible blocks: {}\nskipped blocks: {}\nrendering {} blocks", 
-                        self.width, self.height, self.depth, total_blocks, skipped_blocks, data.len());
-            }
+                for c in 0..self.depth
+                {
+                    let id = self.get(r, l, c);
+                    if id < 1
+                    {
+                        continue;
+                    }
 
-            self.total_blocks = total_blocks;
-            self.hidden_blocks = skipped_blocks;
-            self.rendered_blocks = data.len() as u32;
+                    total_blocks += 1;
 
-            Some(Rc::new(glium::vertex::VertexBuffer::dynamic(display, &data).unwrap()))
-        };
-    }
+                    if !self.is_block_visible(r as i32, l as i32, c as i32, neighbors, blocks)
+                    {
+                        continue;
+                    }
 
-    fn has_neighbor_gap(self: &WorldChunk, x: usize, y: usize, z: usize) -> bool
-    {
+                    let x = (r as f32) * cube_size;
+                    let y = (l as f32) * cube_size;
+                    let z = (c as f32) * cube_size;
 
-        if x == 0 || y == 0 || z == 0
-            || x == self.width -1
-            || y == self.height -1
-            || z == self.depth -1
-            {
-                return true;
+                    data.push(Attr { offset: (x, y, z), textures: blocks.face_textures(id) });
+                }
             }
+        }
+
+        self.total_blocks = total_blocks;
+        self.rendered_blocks = data.len() as u32;
+        self.hidden_blocks = total_blocks - self.rendered_blocks;
 
-        // above
-        if self.layers[y + 1].layer[x][z].id < 1
+        if debug_output
         {
-            return true;
+            println!("Chunk Dimensions ({}x{}x{})\ntotal visible blocks: {}\nskipped blocks: {}\nrendering {} blocks", 
+                    self.width, self.height, self.depth, self.total_blocks, self.hidden_blocks, data.len());
         }
 
-        // below
-        if self.layers[y - 1].layer[x][z].id < 1
+        // BasicCube.obj is 12 triangles
+        self.triangles = self.rendered_blocks * 12;
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::time::Instant;
+    use crate::game::{ ChunkDemoScene, chunk_mesher::ChunkMesher };
+
+    fn blocks() -> BlockRegistry
+    {
+        BlockRegistry::load("assets/blocks.cfg").unwrap()
+    }
+
+    fn solid_chunk(size: usize) -> WorldChunk
+    {
+        let mut chunk = WorldChunk::new(size, size, size);
+        chunk.fill(1);
+        chunk
+    }
+
+    #[test]
+    fn test_one_renders_only_the_shell()
+    {
+        let mut chunk = WorldChunk::new(16, 16, 16);
+        ChunkDemoScene::make_test_one(&mut chunk);
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks());
+
+        // The hole in the top layer removes one shell block but uncovers the one below it
+        assert_eq!(chunk.total_blocks, 4095);
+        assert_eq!(chunk.rendered_blocks, 1352);
+        assert_eq!(chunk.hidden_blocks, 2743);
+    }
+
+    #[test]
+    fn single_block_is_visible()
+    {
+        let mut chunk = WorldChunk::new(3, 3, 3);
+        chunk.set(1, 1, 1, 1);
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks());
+
+        assert_eq!(chunk.rendered_blocks, 1);
+        assert!(chunk.is_block_visible(1, 1, 1, &ChunkNeighbors::none(), &blocks()));
+    }
+
+    #[test]
+    fn diagonal_gaps_do_not_make_blocks_visible()
+    {
+        // Only the corners of the middle layer are empty. The center block
+        // touches them diagonally but all six of its faces are covered.
+        let mut chunk = solid_chunk(3);
+        for &(x, z) in [(0, 0), (0, 2), (2, 0), (2, 2)].iter()
         {
-            return true;
+            chunk.set(x, 1, z, 0);
         }
 
-        // The rest of the comments are from the perspective
-        // of a face-on view of the layer.
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks());
+
+        assert!(!chunk.is_block_visible(1, 1, 1, &ChunkNeighbors::none(), &blocks()));
+        assert_eq!(chunk.hidden_blocks, 1);
+    }
+
+    #[test]
+    fn neighbouring_chunks_hide_border_blocks()
+    {
+        let mut chunk = solid_chunk(4);
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks());
+        assert_eq!(chunk.rendered_blocks, 56);
+
+        // With a solid chunk on the +X side the blocks against it are only
+        // visible if they are on another edge
+        let pos_x = solid_chunk(4);
+        let neighbors = ChunkNeighbors { pos_x: Some(&pos_x), ..ChunkNeighbors::none() };
+        chunk.update_visibility(&neighbors, &blocks());
+        assert_eq!(chunk.rendered_blocks, 52);
+
+        let all = solid_chunk(4);
+        let neighbors = ChunkNeighbors { pos_x: Some(&all), neg_x: Some(&all), pos_z: Some(&all), neg_z: Some(&all) };
+        chunk.update_visibility(&neighbors, &blocks());
+
+        // Only the top and bottom layers are left
+        assert_eq!(chunk.rendered_blocks, 32);
+    }
+
+    #[test]
+    fn transparent_blocks_do_not_hide_blocks()
+    {
+        let blocks = BlockRegistry::parse("[stone]\nid = 1\ntexture = a.png\n[glass]\nid = 2\ntexture = b.png\ntransparent = true").unwrap();
+
+        // A stone cube with a glass center layer
+        let mut chunk = solid_chunk(3);
+        chunk.fill_layer(1, 2);
+        chunk.set(1, 1, 1, 1);
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks);
+        assert!(chunk.is_block_visible(1, 1, 1, &ChunkNeighbors::none(), &blocks));
+
+        // Glass next to glass is hidden
+        chunk.fill(2);
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks);
+        assert!(!chunk.is_block_visible(1, 1, 1, &ChunkNeighbors::none(), &blocks));
+        assert_eq!(chunk.rendered_blocks, 26);
+    }
+
+    #[test]
+    fn instances_carry_a_texture_for_each_face()
+    {
+        let blocks = blocks();
+        let grass = blocks.id_of("grass").unwrap();
+        let mut chunk = WorldChunk::new(2, 2, 2);
+        chunk.set(1, 1, 1, grass);
+
+        let data = chunk.gen_instance_data(&ChunkNeighbors::none(), &blocks, false);
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].textures, blocks.face_textures(grass));
+        assert_ne!(data[0].textures[0], data[0].textures[2]);
+    }
 
-        // left
-        if self.layers[y].layer[x - 1][z].id < 1
+    #[test]
+    fn instance_counts_match_visibility()
+    {
+        let blocks = blocks();
+        let mut chunk = solid_chunk(4);
+        chunk.set(0, 3, 0, 0);
+        let pos_x = solid_chunk(4);
+        let neighbors = ChunkNeighbors { pos_x: Some(&pos_x), ..ChunkNeighbors::none() };
+
+        let data = chunk.gen_instance_data(&neighbors, &blocks, false);
+        let (total, rendered, hidden) = (chunk.total_blocks, chunk.rendered_blocks, chunk.hidden_blocks);
+        assert_eq!(rendered as usize, data.len());
+
+        chunk.update_visibility(&neighbors, &blocks);
+        assert_eq!((total, rendered, hidden), (chunk.total_blocks, chunk.rendered_blocks, chunk.hidden_blocks));
+        assert_eq!(total, 63);
+    }
+
+    #[test]
+    fn downsampling_keeps_the_surface()
+    {
+        // Stone up to y = 4 with grass on top, in a chunk that doesn't divide evenly
+        let mut chunk = WorldChunk::new(6, 8, 6);
+        for y in 0..4
         {
-            return true;
+            chunk.fill_layer(y, 3);
         }
-        
-        // left top
-        if self.layers[y].layer[x - 1][z - 1].id < 1
+        chunk.fill_layer(4, 1);
+
+        let coarse = chunk.downsample(2);
+        assert_eq!((coarse.width, coarse.height, coarse.depth), (3, 4, 3));
+        assert!(coarse.layer(0).all(|id| id == 3));
+
+        // Layer 2 covers y = 4 (grass) and y = 5 (air), half solid
+        assert!(coarse.layer(2).all(|id| id == 1));
+        assert!(coarse.layer(3).all(|id| id == 0));
+
+        let coarse = chunk.downsample(4);
+        assert_eq!((coarse.width, coarse.height, coarse.depth), (2, 2, 2));
+        assert!(coarse.layer(0).all(|id| id == 3));
+        assert!(coarse.layer(1).all(|id| id == 0));
+    }
+
+    #[test]
+    fn neighbor_lookup_wraps_into_the_next_chunk()
+    {
+        let chunk = WorldChunk::new(4, 4, 4);
+        let mut neg_z = WorldChunk::new(4, 4, 4);
+        neg_z.set(1, 2, 3, 5);
+
+        let neighbors = ChunkNeighbors { neg_z: Some(&neg_z), ..ChunkNeighbors::none() };
+        assert_eq!(chunk.get_id_with_neighbors(1, 2, -1, &neighbors), 5);
+        assert_eq!(chunk.get_id_with_neighbors(1, 2, 4, &neighbors), 0);
+        assert_eq!(chunk.get_id_with_neighbors(-1, 2, -1, &neighbors), 0);
+    }
+
+    #[test]
+    fn columns_and_layers_match_get()
+    {
+        let mut chunk = WorldChunk::new(3, 4, 5);
+        chunk.set(2, 3, 4, 7);
+        chunk.set(2, 0, 4, 1);
+        chunk.fill_layer(1, 2);
+
+        let column: Vec<u16> = chunk.column(2, 4).collect();
+        assert_eq!(column, vec![1, 2, 0, 7]);
+
+        assert_eq!(chunk.layers().count(), 4);
+        assert!(chunk.layer(1).all(|id| id == 2));
+        assert_eq!(chunk.layer(3).nth(2 * 5 + 4), Some(7));
+        assert_eq!(chunk.columns().count(), 15);
+    }
+
+    #[test]
+    fn compressing_keeps_blocks_and_visibility()
+    {
+        let mut chunk = WorldChunk::new(16, 32, 16);
+        ChunkDemoScene::make_test_one(&mut chunk);
+        let flat_memory = chunk.memory_usage();
+
+        chunk.compress();
+        assert!(chunk.is_compressed());
+        assert!(chunk.memory_usage() < flat_memory / 4);
+
+        chunk.update_visibility(&ChunkNeighbors::none(), &blocks());
+        assert_eq!(chunk.rendered_blocks, 1352);
+        assert_eq!(chunk.get(7, 15, 7), 0);
+        assert_eq!(chunk.get(7, 14, 7), 3);
+
+        // Setting a new id on the uniform (empty) top section
+        chunk.set(1, 20, 1, 9);
+        assert_eq!(chunk.get(1, 20, 1), 9);
+        assert_eq!(chunk.get(1, 20, 2), 0);
+
+        chunk.decompress();
+        assert!(!chunk.is_compressed());
+        assert_eq!(chunk.get(1, 20, 1), 9);
+        assert_eq!(chunk.get(0, 0, 0), 1);
+    }
+
+    #[test]
+    fn raycast_hits_blocks_in_the_chunk()
+    {
+        // Solid layer at y = 8
+        let mut chunk = WorldChunk::new(8, 10, 8);
+        ChunkDemoScene::make_chunk_single_layer(&mut chunk);
+        chunk.set(4, 9, 4, 2);
+
+        let hit = chunk.raycast(&glm::vec3(2.0, 9.0, 2.0), &glm::vec3(0.0, -1.0, 0.0), 8.0, &blocks()).unwrap();
+        assert_eq!(hit.block, (2, 8, 2));
+        assert_eq!(hit.face, (0, 1, 0));
+
+        let hit = chunk.raycast(&glm::vec3(0.0, 9.0, 4.0), &glm::vec3(1.0, 0.0, 0.0), 8.0, &blocks()).unwrap();
+        assert_eq!(hit.block, (4, 9, 4));
+        assert_eq!(hit.face, (-1, 0, 0));
+
+        // Leaves the chunk without hitting anything
+        assert!(chunk.raycast(&glm::vec3(2.0, 9.0, 2.0), &glm::vec3(0.0, 1.0, 0.0), 50.0, &blocks()).is_none());
+    }
+
+    /// The layout WorldChunk used to have: Vec<Layer> of Vec<Vec<Voxel>>
+    #[derive(Clone)]
+    struct NestedVoxel
+    {
+        id: u16,
+        _visible: bool
+    }
+
+    struct NestedChunk
+    {
+        width: usize,
+        height: usize,
+        depth: usize,
+        layers: Vec<Vec<Vec<NestedVoxel>>>,
+    }
+
+    impl NestedChunk
+    {
+        fn get_id(self: &NestedChunk, x: i32, y: i32, z: i32) -> u16
         {
-            return true;
+            if x < 0 || y < 0 || z < 0 || x >= self.width as i32 || y >= self.height as i32 || z >= self.depth as i32
+            {
+                return 0;
+            }
+
+            self.layers[y as usize][x as usize][z as usize].id
         }
+    }
+
+    fn bench_height(x: usize, z: usize) -> usize
+    {
+        8 + (x * 7 + z * 13) % 20
+    }
 
-        // top
-        if self.layers[y].layer[x][z - 1].id < 1
+    /// Counts exposed faces the same way the culled mesher decides which faces to build
+    fn count_faces(get_id: impl Fn(i32, i32, i32) -> u16, dims: (usize, usize, usize)) -> u32
+    {
+        let mut faces = 0;
+        for y in 0..dims.1 as i32
         {
-            return true;
+            for x in 0..dims.0 as i32
+            {
+                for z in 0..dims.2 as i32
+                {
+                    if get_id(x, y, z) < 1
+                    {
+                        continue;
+                    }
+
+                    for face in FACES.iter()
+                    {
+                        if get_id(x + face.dir.0, y + face.dir.1, z + face.dir.2) < 1
+                        {
+                            faces += 1;
+                        }
+                    }
+                }
+            }
         }
 
-        // top right
-        if self.layers[y].layer[x + 1][z - 1].id < 1
+        faces
+    }
+
+    /// Run with: cargo test --release bench_flat_vs_nested_layout -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_flat_vs_nested_layout()
+    {
+        let (w, h, d) = (128, 32, 128);
+        let runs = 20;
+
+        let start = Instant::now();
+        let mut nested = NestedChunk { width: w, height: h, depth: d, layers: vec![] };
+        for _ in 0..runs
         {
-            return true;
+            nested.layers = vec![vec![vec![NestedVoxel { id: 0, _visible: true }; d]; w]; h];
+            for x in 0..w
+            {
+                for z in 0..d
+                {
+                    for y in 0..bench_height(x, z)
+                    {
+                        nested.layers[y][x][z].id = 1;
+                    }
+                }
+            }
         }
+        let nested_gen = start.elapsed();
 
-        // right
-        if self.layers[y].layer[x + 1][z].id < 1
+        let start = Instant::now();
+        let mut flat = WorldChunk::new(w, h, d);
+        for _ in 0..runs
         {
-            return true;
+            flat = WorldChunk::new(w, h, d);
+            for x in 0..w
+            {
+                for z in 0..d
+                {
+                    for y in 0..bench_height(x, z)
+                    {
+                        flat.set(x, y, z, 1);
+                    }
+                }
+            }
         }
+        let flat_gen = start.elapsed();
 
-        // bottom right
-        if self.layers[y].layer[x + 1][z + 1].id < 1
+        let start = Instant::now();
+        let mut nested_faces = 0;
+        for _ in 0..runs
         {
-            return true;
+            nested_faces = count_faces(|x, y, z| nested.get_id(x, y, z), (w, h, d));
         }
-        
-        // bottom
-        if self.layers[y].layer[x][z + 1].id < 1
+        let nested_mesh = start.elapsed();
+
+        let start = Instant::now();
+        let mut flat_faces = 0;
+        for _ in 0..runs
         {
-            return true;
+            flat_faces = count_faces(|x, y, z| flat.get_id(x, y, z), (w, h, d));
         }
+        let flat_mesh = start.elapsed();
 
-        // bottom left
-        if self.layers[y].layer[x - 1][z + 1].id < 1
+        let blocks = blocks();
+        let start = Instant::now();
+        for _ in 0..runs
         {
-            return true;
+            ChunkMesher::build_culled(&mut flat, &ChunkNeighbors::none(), &blocks);
         }
+        let culled_mesh = start.elapsed();
+
+        assert_eq!(nested_faces, flat_faces);
 
-        return false;
+        println!("\n{}x{}x{} chunk, {} runs", w, h, d, runs);
+        println!("generation: nested {:?}, flat {:?} ({:.2}x)", nested_gen, flat_gen, 
+                    nested_gen.as_secs_f64() / flat_gen.as_secs_f64());
+        println!("face culling: nested {:?}, flat {:?} ({:.2}x)", nested_mesh, flat_mesh, 
+                    nested_mesh.as_secs_f64() / flat_mesh.as_secs_f64());
+        println!("full culled mesh (flat): {:?}", culled_mesh);
     }
-}
\ No newline at end of file
+}
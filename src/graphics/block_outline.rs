@@ -0,0 +1,91 @@
+
+extern crate nalgebra_glm as glm;
+
+use crate::utils;
+use crate::graphics::grid_plane::{LineVertex, line_program};
+
+/// Wireframe cube drawn around a block. Uses the same line shaders as GridPlane.
+#[derive(Debug)]
+pub struct BlockOutline
+{
+    vb: glium::VertexBuffer<LineVertex>,
+    indices: glium::index::NoIndices,
+    pub program: glium::Program,
+    pub view: glm::Mat4,
+    pub projection: glm::Mat4,
+}
+
+impl BlockOutline
+{
+    pub fn new(gl: &glium::Display, color: [f32; 3]) -> Result<BlockOutline, String>
+    {
+        // Slightly bigger than a block so the lines aren't hidden by its faces
+        let s = 0.505;
+        let corners = [[-s, -s, -s], [s, -s, -s], [s, -s, s], [-s, -s, s],
+                       [-s, s, -s], [s, s, -s], [s, s, s], [-s, s, s]];
+
+        // Bottom square, top square and the four sides
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 0),
+                     (4, 5), (5, 6), (6, 7), (7, 4),
+                     (0, 4), (1, 5), (2, 6), (3, 7)];
+
+        let mut vertices: Vec<LineVertex> = Vec::new();
+        for (a, b) in edges.iter()
+        {
+            vertices.push(LineVertex { pos: corners[*a], color });
+            vertices.push(LineVertex { pos: corners[*b], color });
+        }
+
+        let vertex_buffer = match glium::VertexBuffer::new(gl, &vertices)
+        {
+            Ok(vb) => vb,
+            Err(e) => return Err(format!("Error Creating Block Outline! Error: {:?}", e))
+        };
+
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+
+        let program = line_program(gl)?;
+
+        Ok (BlockOutline { vb: vertex_buffer, indices, program, 
+                            view: glm::Mat4::identity(), projection: glm::Mat4::identity() })
+    }
+
+    /// Draws the outline around the block centered on position
+    pub fn draw(self: &BlockOutline, target: &mut glium::Frame, position: &glm::Vec3)
+    {
+        let model = glm::translate(&glm::Mat4::identity(), position);
+        self.draw_model(target, &model, true);
+    }
+
+    /// Draws the outline stretched over a box. The lines are drawn on top
+    /// of everything so boxes behind terrain still show.
+    pub fn draw_box(self: &BlockOutline, target: &mut glium::Frame, min: &glm::Vec3, max: &glm::Vec3)
+    {
+        let model = glm::translate(&glm::Mat4::identity(), &((min + max) * 0.5));
+        let model = glm::scale(&model, &(max - min));
+        self.draw_model(target, &model, false);
+    }
+
+    fn draw_model(self: &BlockOutline, target: &mut glium::Frame, model: &glm::Mat4, depth_test: bool)
+    {
+        let uniforms = uniform! 
+        { 
+            model: utils::mat4_to_array(model), 
+            view: utils::mat4_to_array(&self.view), 
+            projection: utils::mat4_to_array(&self.projection)
+        };
+
+        let params = glium::DrawParameters {
+            depth: glium::Depth {
+                test: if depth_test { glium::draw_parameters::DepthTest::IfLess } else { glium::draw_parameters::DepthTest::Overwrite },
+                write: depth_test,
+                .. Default::default()
+            },
+            line_width: Some(2.0),
+            .. Default::default()
+        };
+
+        use glium::Surface;
+        target.draw(&self.vb, self.indices, &self.program, &uniforms, &params).unwrap();
+    }
+}
@@ -37,7 +37,7 @@ impl CameraFPS
             look_sensitivity: 0.1 };
 
         c.update_camera_vectors();
-        return c;
+        c
     }
 
     pub fn get_position(&self) -> glm::Vec3
@@ -45,12 +45,18 @@ impl CameraFPS
         self.position
     }
 
+    /// The direction the camera is looking
+    pub fn get_front(&self) -> glm::Vec3
+    {
+        self.front
+    }
+
     pub fn update_camera_vectors(&mut self)
     {
         //println!("pitch, yaw: {}, {}", self.pitch, self.yaw);
         self.front = glm::vec3(0.0, 0.0, 0.0);
 
-        let yp = glm::radians(&glm::vec2(self.yaw.clone(), self.pitch.clone()));
+        let yp = glm::radians(&glm::vec2(self.yaw, self.pitch));
         let yaw = yp.x;
         let pitch = yp.y;
 
@@ -71,13 +77,13 @@ impl CameraFPS
 
     pub fn move_forward(&mut self, velocity: f32)
     {
-        let velocity = velocity * -1.0;
+        let velocity = -velocity;
         self.position += self.front * velocity;
     }
 
     pub fn move_right(&mut self, velocity: f32)
     {
-        let velocity = velocity * -1.0;
+        let velocity = -velocity;
         self.position += self.right * velocity;
     }
 
@@ -95,15 +101,7 @@ impl CameraFPS
         self.yaw += xoff;
         self.pitch += yoff;
 
-        if self.pitch > 89.0
-        {
-            self.pitch = 89.0;
-        }
-
-        if self.pitch < -89.0
-        {
-            self.pitch = -89.0;
-        }
+        self.pitch = self.pitch.clamp(-89.0, 89.0);
 
         //println!("pitch, yaw: {}, {}", self.pitch, self.yaw);
         self.update_camera_vectors();
@@ -0,0 +1,65 @@
+
+extern crate nalgebra_glm as glm;
+
+use crate::utils;
+use crate::graphics::WindowInfo;
+use crate::graphics::grid_plane::{LineVertex, line_program};
+
+/// A + in the middle of the screen. Drawn straight in clip space
+/// with the GridPlane line shaders.
+#[derive(Debug)]
+pub struct Crosshair
+{
+    vb: glium::VertexBuffer<LineVertex>,
+    indices: glium::index::NoIndices,
+    pub program: glium::Program,
+
+    /// Length of each arm in pixels
+    pub size: f32,
+}
+
+impl Crosshair
+{
+    pub fn new(gl: &glium::Display, color: [f32; 3], size: f32) -> Result<Crosshair, String>
+    {
+        let vertices = vec![LineVertex { pos: [-1.0, 0.0, 0.0], color }, LineVertex { pos: [1.0, 0.0, 0.0], color },
+                            LineVertex { pos: [0.0, -1.0, 0.0], color }, LineVertex { pos: [0.0, 1.0, 0.0], color }];
+
+        let vertex_buffer = match glium::VertexBuffer::new(gl, &vertices)
+        {
+            Ok(vb) => vb,
+            Err(e) => return Err(format!("Error Creating Crosshair! Error: {:?}", e))
+        };
+
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+
+        let program = line_program(gl)?;
+
+        Ok (Crosshair { vb: vertex_buffer, indices, program, size })
+    }
+
+    /// Draws on top of everything, should be called after the scene
+    pub fn draw(self: &Crosshair, target: &mut glium::Frame, window_info: &WindowInfo)
+    {
+        // Scale the unit lines to size pixels (clip space is 2 units across the window)
+        let sx = self.size * 2.0 / window_info.size.width as f32;
+        let sy = self.size * 2.0 / window_info.size.height as f32;
+        let model = glm::scale(&glm::Mat4::identity(), &glm::vec3(sx, sy, 1.0));
+        let identity: glm::Mat4 = glm::identity();
+
+        let uniforms = uniform! 
+        { 
+            model: utils::mat4_to_array(&model), 
+            view: utils::mat4_to_array(&identity), 
+            projection: utils::mat4_to_array(&identity)
+        };
+
+        let params = glium::DrawParameters {
+            line_width: Some(2.0),
+            .. Default::default()
+        };
+
+        use glium::Surface;
+        target.draw(&self.vb, self.indices, &self.program, &uniforms, &params).unwrap();
+    }
+}
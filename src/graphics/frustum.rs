@@ -0,0 +1,103 @@
+
+/// The six planes of a view frustum, pulled out of a projection * view matrix
+/// (Gribb & Hartmann). Each plane is (a, b, c, d) with the normal pointing into
+/// the frustum so a point p is inside when a*x + b*y + c*z + d >= 0 for every plane.
+pub struct Frustum
+{
+    planes: [glm::Vec4; 6],
+}
+
+impl Frustum
+{
+    /// Works with OpenGL style clip space (-w <= z <= w), which is what
+    /// glm::perspective_lh gives
+    pub fn from_matrix(view_projection: &glm::Mat4) -> Frustum
+    {
+        let row = |i: usize| glm::vec4(view_projection[(i, 0)], view_projection[(i, 1)], view_projection[(i, 2)], view_projection[(i, 3)]);
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        let mut planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2];
+        for plane in planes.iter_mut()
+        {
+            let length = glm::length(&glm::vec3(plane.x, plane.y, plane.z));
+            if length > 0.0
+            {
+                *plane /= length;
+            }
+        }
+
+        Frustum { planes }
+    }
+
+    /// False if the box is completely outside the frustum. Boxes near the corners
+    /// of the frustum can be reported as visible when they aren't, which only
+    /// costs a draw call.
+    pub fn intersects_aabb(self: &Frustum, min: &glm::Vec3, max: &glm::Vec3) -> bool
+    {
+        for plane in self.planes.iter()
+        {
+            // The corner furthest along the plane normal
+            let x = if plane.x >= 0.0 { max.x } else { min.x };
+            let y = if plane.y >= 0.0 { max.y } else { min.y };
+            let z = if plane.z >= 0.0 { max.z } else { min.z };
+
+            if plane.x * x + plane.y * y + plane.z * z + plane.w < 0.0
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn frustum_looking_down_z() -> Frustum
+    {
+        let perspective = glm::perspective_lh(1.0, 60.0_f32.to_radians(), 0.1, 100.0);
+        let view = glm::look_at_lh(&glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 0.0, 1.0), &glm::vec3(0.0, 1.0, 0.0));
+
+        Frustum::from_matrix(&(perspective * view))
+    }
+
+    fn unit_box_at(x: f32, y: f32, z: f32) -> (glm::Vec3, glm::Vec3)
+    {
+        (glm::vec3(x - 0.5, y - 0.5, z - 0.5), glm::vec3(x + 0.5, y + 0.5, z + 0.5))
+    }
+
+    #[test]
+    fn boxes_in_front_are_visible()
+    {
+        let frustum = frustum_looking_down_z();
+
+        let (min, max) = unit_box_at(0.0, 0.0, 10.0);
+        assert!(frustum.intersects_aabb(&min, &max));
+
+        // Partly inside the left edge
+        let (min, max) = unit_box_at(-5.9, 0.0, 10.0);
+        assert!(frustum.intersects_aabb(&min, &max));
+    }
+
+    #[test]
+    fn boxes_outside_are_culled()
+    {
+        let frustum = frustum_looking_down_z();
+
+        for &(x, y, z) in [(0.0, 0.0, -10.0), (20.0, 0.0, 10.0), (-20.0, 0.0, 10.0), (0.0, 20.0, 10.0), (0.0, -20.0, 10.0), (0.0, 0.0, 200.0)].iter()
+        {
+            let (min, max) = unit_box_at(x, y, z);
+            assert!(!frustum.intersects_aabb(&min, &max), "({}, {}, {})", x, y, z);
+        }
+    }
+
+    #[test]
+    fn box_around_the_camera_is_visible()
+    {
+        let frustum = frustum_looking_down_z();
+        assert!(frustum.intersects_aabb(&glm::vec3(-50.0, -50.0, -50.0), &glm::vec3(50.0, 50.0, 50.0)));
+    }
+}
@@ -2,16 +2,24 @@
 extern crate nalgebra_glm as glm;
 
 use crate::utils;
+use crate::graphics::program::load_program;
 
-use std::fs::File;
-use std::io::prelude::*;
 use std::vec;
 
+/// Vertex for the coloured line shaders shared by GridPlane, BlockOutline and Crosshair
 #[derive(Copy, Clone, Debug)]
-struct Vertex
+pub struct LineVertex
 {
-    pos: [f32; 3],
-    color: [f32; 3],
+    pub pos: [f32; 3],
+    pub color: [f32; 3],
+}
+
+implement_vertex!(LineVertex, pos, color);
+
+/// Loads the line shaders
+pub fn line_program(gl: &glium::Display) -> Result<glium::Program, String>
+{
+    load_program(gl, "line", "assets/shaders/grid_plane.vert", "assets/shaders/grid_plane.frag")
 }
 
 #[derive(Debug)]
@@ -19,7 +27,7 @@ pub struct GridPlane
 {
     width: i32,
     length: i32,
-    vb: glium::VertexBuffer<Vertex>,
+    vb: glium::VertexBuffer<LineVertex>,
     indices: glium::index::NoIndices,
     pub program: glium::Program,
     pub view: glm::Mat4,
@@ -30,12 +38,10 @@ impl GridPlane
 {
     pub fn new(gl: &glium::Display, color: [f32; 3], cell_size: f32, width: i32, length: i32) -> Result<GridPlane, String>
     {
-        implement_vertex!(Vertex, pos, color);
-
         // Generate Vertices
-        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut vertices: Vec<LineVertex> = Vec::new();
         let half_width = width / 2;
-        let start = half_width * -1;
+        let start = -half_width;
 
         // Loop from -half_width to half_width
         for x in start..half_width + 1
@@ -51,20 +57,20 @@ impl GridPlane
 
             let xp = (x as f32) * cell_size;
             let z1 = ((length / 2) as f32) * cell_size;
-            let z2 = ((length / 2) as f32) * -1.0 * cell_size; 
-            vertices.push(Vertex { 
+            let z2 = -((length / 2) as f32) * cell_size; 
+            vertices.push(LineVertex { 
                     pos: [xp, 0.0, z1],
                     color: final_color,
                 });
 
-            vertices.push(Vertex { 
+            vertices.push(LineVertex { 
                 pos: [xp, 0.0, z2],
                 color: final_color2,
             });
         }
 
       let half_length = length / 2;
-      let start = half_length * -1;
+      let start = -half_length;
         for z in start..half_length + 1
         {
             let mut final_color = color;
@@ -78,32 +84,28 @@ impl GridPlane
 
             let zp = (z as f32) * cell_size;
             let x1 = ((width / 2) as f32) * cell_size;
-            let x2 = ((width / 2) as f32) * -1.0 * cell_size; 
-            vertices.push(Vertex { 
+            let x2 = -((width / 2) as f32) * cell_size; 
+            vertices.push(LineVertex { 
                     pos: [x1, 0.0, zp],
                     color: final_color,
                 });
 
-            vertices.push(Vertex { 
+            vertices.push(LineVertex { 
                 pos: [x2, 0.0, zp],
                 color: final_color2,
             });
         }
 
           // Create and Fill buffers
-        let vertex_buffer = glium::VertexBuffer::new(gl, &vertices).unwrap();
-        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+        let vertex_buffer = match glium::VertexBuffer::new(gl, &vertices)
+        {
+            Ok(vb) => vb,
+            Err(e) => return Err(format!("Error Creating Grid Plane! Error: {:?}", e))
+        };
 
-        // load shaders
-        let mut file = File::open("assets/shaders/grid_plane.vert").unwrap();
-        let mut vertex_shader_src = String::new();
-        file.read_to_string(&mut vertex_shader_src).unwrap();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
 
-        let mut file = File::open("assets/shaders/grid_plane.frag").unwrap();
-        let mut fragment_shader_src = String::new();
-        file.read_to_string(&mut fragment_shader_src).unwrap();
-        
-        let program = glium::Program::from_source(gl, &vertex_shader_src, &fragment_shader_src, None).unwrap();
+        let program = line_program(gl)?;
 
         Ok (GridPlane { width, length, program, vb: vertex_buffer, indices,
                     view: glm::Mat4::identity(), projection: glm::Mat4::identity() })
@@ -120,7 +122,7 @@ impl GridPlane
         };
 
         use glium::Surface;
-        target.draw(&self.vb, &self.indices, &self.program, &uniforms,
+        target.draw(&self.vb, self.indices, &self.program, &uniforms,
                         &Default::default()).unwrap();
     }
 }
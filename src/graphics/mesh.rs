@@ -15,7 +15,7 @@ impl Mesh
 {
     pub fn new(gl: &glium::Display, filename: &str) -> Result<Mesh, String>
     {
-        let obj_file = tobj::load_obj(&Path::new(filename));
+        let obj_file = tobj::load_obj(Path::new(filename));
 
         //assert!(obj_file.is_ok());
         if obj_file.is_err()
@@ -46,7 +46,7 @@ impl Mesh
         Ok(Mesh { vb, indices })
     }
 
-    pub fn new_from_verts(gl: &glium::Display, verts: &Vec<Vertex>, indices: &Vec<u32>) -> Mesh
+    pub fn new_from_verts(gl: &glium::Display, verts: &[Vertex], indices: &[u32]) -> Mesh
     {
         let vb = glium::VertexBuffer::new(gl, verts).unwrap();
         let indices = glium::IndexBuffer::new(gl, glium::index::PrimitiveType::TrianglesList,
@@ -7,26 +7,34 @@
 //pub use self::vertex::Vertex;
 pub use self::gl::Gl;
 pub use self::window_info::WindowInfo;
-pub use self::window_info::Point;
-pub use self::window_info::Size;
+#[allow(unused_imports)]
+pub use self::window_info::{Point, Size};
 pub use self::grid_plane::GridPlane;
+pub use self::block_outline::BlockOutline;
+pub use self::crosshair::Crosshair;
 pub use self::mesh::Mesh;
 pub use self::program::Program;
 pub use self::texture::Texture;
 pub use self::texture::Flip;
+pub use self::texture_array::TextureArray;
 pub use self::camera_fps::CameraFPS;
+pub use self::frustum::Frustum;
 // pub use self::text_renderer::TextRenderer;
 
 
 mod gl;
 mod window_info;
 mod camera_fps;
+mod frustum;
 //mod triangle;
 mod grid_plane;
+mod block_outline;
+mod crosshair;
 // mod block;
 mod vertex;
 mod program;
 mod texture;
+mod texture_array;
 mod mesh;
 // mod model;
 // mod text_renderer;
\ No newline at end of file
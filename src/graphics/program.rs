@@ -1,7 +1,6 @@
 
 
-use std::fs::File;
-use std::io::prelude::*;
+use std::fs;
 
 pub struct Program
 {
@@ -13,21 +12,30 @@ impl Program
 {
     pub fn new(gl: &glium::Display, program_name: &str, vert_source_file: &str, frag_source_file: &str) -> Result<Program, String>
     {
-        let mut file = File::open(vert_source_file).unwrap();
-        let mut vertex_shader_src = String::new();
-        file.read_to_string(&mut vertex_shader_src).unwrap();
-
-        let mut file = File::open(frag_source_file).unwrap();
-        let mut fragment_shader_src = String::new();
-        file.read_to_string(&mut fragment_shader_src).unwrap();
-        
-        let result = glium::Program::from_source(gl, &vertex_shader_src, &fragment_shader_src, None);
-
-        match result
-        {
-            Ok(program) => Ok(Program { name: String::from(program_name), program }),
-            Err(error) => Err(error_to_string(&error, program_name))
-        }
+        let program = load_program(gl, program_name, vert_source_file, frag_source_file)?;
+        Ok(Program { name: String::from(program_name), program })
+    }
+}
+
+/// Reads the vertex and fragment shader files and builds a program from them
+pub fn load_program(gl: &glium::Display, program_name: &str, vert_source_file: &str, frag_source_file: &str) -> Result<glium::Program, String>
+{
+    let vertex_shader_src = read_source(vert_source_file)?;
+    let fragment_shader_src = read_source(frag_source_file)?;
+
+    match glium::Program::from_source(gl, &vertex_shader_src, &fragment_shader_src, None)
+    {
+        Ok(program) => Ok(program),
+        Err(error) => Err(error_to_string(&error, program_name))
+    }
+}
+
+fn read_source(filename: &str) -> Result<String, String>
+{
+    match fs::read_to_string(filename)
+    {
+        Ok(source) => Ok(source),
+        Err(e) => Err(format!("Error Loading Shader! File: {}, Error: {:?}", filename, e))
     }
 }
 
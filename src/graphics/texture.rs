@@ -1,7 +1,7 @@
 
 extern crate image;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub enum Flip
 {
     NONE,
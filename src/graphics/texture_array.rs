@@ -0,0 +1,128 @@
+
+extern crate image;
+
+use crate::graphics::Flip;
+
+// RGBA pixels for each layer, all the same size
+struct Layers
+{
+    pixels: Vec<Vec<u8>>,
+    size: (u32, u32),
+}
+
+/// Several same sized textures in one Texture2dArray, sampled in a shader with a
+/// sampler2DArray and the layer index. Each layer gets its own mipmaps so unlike
+/// an atlas the textures never bleed into each other, and texture coordinates
+/// past 1.0 repeat the layer (which the greedy meshes rely on).
+pub struct TextureArray
+{
+    texture: glium::texture::Texture2dArray,
+    layers: usize,
+}
+
+impl TextureArray
+{
+    pub fn new(gl: &glium::Display, filenames: &[String], flip: Flip) -> Result<TextureArray, String>
+    {
+        let layers = TextureArray::load_layers(filenames, &flip)?;
+        let size = layers.size;
+
+        let images: Vec<glium::texture::RawImage2d<u8>> = layers.pixels.into_iter()
+            .map(|raw| glium::texture::RawImage2d::from_raw_rgba(raw, size))
+            .collect();
+
+        let texture = match glium::texture::Texture2dArray::with_mipmaps(gl, images, glium::texture::MipmapsOption::AutoGeneratedMipmaps)
+        {
+            Ok(t) => t,
+            Err(e) => return Err(format!("Error Creating Texture Array! Files: {:?}, Error: {:?}", filenames, e))
+        };
+
+        Ok( TextureArray { texture, layers: filenames.len() })
+    }
+
+    /// Loads every image as RGBA. Images that aren't the same size as the
+    /// first one are stretched to fit.
+    fn load_layers(filenames: &[String], flip: &Flip) -> Result<Layers, String>
+    {
+        if filenames.is_empty()
+        {
+            return Err(String::from("Error Creating Texture Array! No textures given"));
+        }
+
+        let mut layers = vec![];
+        let mut size = (0, 0);
+        for (i, filename) in filenames.iter().enumerate()
+        {
+            let img = match image::open(filename)
+            {
+                Ok(i) => i,
+                Err(e) => return Err(format!("Error Loading Texture! File: {}, Error: {:?}", filename, e))
+            };
+
+            let img = match flip
+            {
+                Flip::NONE => img,
+                Flip::VERTICAL => img.flipv(),
+                Flip::HORIZONTAL => img.fliph()
+            };
+
+            let mut img = img.to_rgba();
+            if i == 0
+            {
+                size = (img.width(), img.height());
+            }
+            else if (img.width(), img.height()) != size
+            {
+                img = image::imageops::resize(&img, size.0, size.1, image::FilterType::Triangle);
+            }
+
+            layers.push(img.into_raw());
+        }
+
+        Ok(Layers { pixels: layers, size })
+    }
+
+    pub fn num_layers(self: &TextureArray) -> usize
+    {
+        self.layers
+    }
+
+    /// The array with repeating texture coordinates and trilinear filtering
+    pub fn sampled(self: &TextureArray) -> glium::uniforms::Sampler<'_, glium::texture::Texture2dArray>
+    {
+        self.texture.sampled()
+            .wrap_function(glium::uniforms::SamplerWrapFunction::Repeat)
+            .minify_filter(glium::uniforms::MinifySamplerFilter::LinearMipmapLinear)
+            .magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear)
+    }
+
+    pub fn get_texture(self: &TextureArray) -> &glium::texture::Texture2dArray
+    {
+        &self.texture
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn loads_the_block_textures_as_layers()
+    {
+        let files = vec![String::from("assets/textures/Grass.png"), String::from("assets/textures/Stone.png")];
+        let layers = TextureArray::load_layers(&files, &Flip::NONE).unwrap();
+
+        // Stone.png isn't square, it's stretched to the size of Grass.png
+        assert_eq!(layers.pixels.len(), 2);
+        assert_eq!(layers.pixels[1].len(), (layers.size.0 * layers.size.1 * 4) as usize);
+    }
+
+    #[test]
+    fn missing_textures_are_errors()
+    {
+        let files = vec![String::from("assets/textures/Grass.png"), String::from("assets/textures/Missing.png")];
+        assert!(TextureArray::load_layers(&files, &Flip::NONE).is_err());
+        assert!(TextureArray::load_layers(&[], &Flip::NONE).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+
+use glium::glutin;
+use glutin::dpi::LogicalPosition;
+use crate::graphics::{Gl, WindowInfo};
+use crate::input::{InputBackend, KeyCode, MouseState, Coord};
+
+/// Input backend driven entirely by glutin window and device events.
+/// Works on every platform glutin supports.
+pub struct GlutinInput
+{
+    gl: Gl,
+    key_states: [bool; 256],
+    mouse_state: MouseState,
+    mouse_delta: (f64, f64),
+}
+
+impl GlutinInput
+{
+    pub fn new(gl: &Gl) -> GlutinInput
+    {
+        GlutinInput { gl: gl.clone(), key_states: [false; 256], mouse_state: MouseState::new(), mouse_delta: (0.0, 0.0) }
+    }
+
+    fn release_all(self: &mut GlutinInput)
+    {
+        self.key_states = [false; 256];
+        let coords = self.mouse_state.coords;
+        self.mouse_state = MouseState::new();
+        self.mouse_state.coords = coords;
+    }
+}
+
+impl InputBackend for GlutinInput
+{
+    fn process_event(self: &mut GlutinInput, event: &glutin::Event)
+    {
+        match event
+        {
+            glutin::Event::WindowEvent { event, .. } => match event
+            {
+                glutin::WindowEvent::KeyboardInput { input, .. } =>
+                {
+                    if let Some(key) = input.virtual_keycode.and_then(KeyCode::from_virtual_key)
+                    {
+                        self.key_states[key as usize] = input.state == glutin::ElementState::Pressed;
+                    }
+                },
+
+                glutin::WindowEvent::MouseInput { state, button, .. } =>
+                {
+                    let down = *state == glutin::ElementState::Pressed;
+                    match button
+                    {
+                        glutin::MouseButton::Left => self.mouse_state.left_button = down,
+                        glutin::MouseButton::Right => self.mouse_state.right_button = down,
+                        glutin::MouseButton::Middle => self.mouse_state.middle_button = down,
+                        glutin::MouseButton::Other(1) => self.mouse_state.button_one = down,
+                        glutin::MouseButton::Other(2) => self.mouse_state.button_two = down,
+                        _ => ()
+                    }
+                },
+
+                glutin::WindowEvent::CursorMoved { position, .. } =>
+                {
+                    self.mouse_state.coords = Coord { x: position.x as i32, y: position.y as i32 };
+                },
+
+                // Key up events are lost while the window is not focused
+                glutin::WindowEvent::Focused(false) => self.release_all(),
+                _ => (),
+            },
+
+            glutin::Event::DeviceEvent { event: glutin::DeviceEvent::MouseMotion { delta }, .. } =>
+            {
+                self.mouse_delta.0 += delta.0;
+                self.mouse_delta.1 += delta.1;
+            },
+
+            _ => (),
+        }
+    }
+
+    fn key_down(self: &GlutinInput, key: KeyCode) -> bool
+    {
+        self.key_states[key as usize]
+    }
+
+    fn get_mouse_state(self: &GlutinInput) -> MouseState
+    {
+        self.mouse_state
+    }
+
+    fn take_look_delta(self: &mut GlutinInput, window_info: &WindowInfo) -> (f32, f32)
+    {
+        // Moving the mouse right should turn the camera right, which is a
+        // negative offset for CameraFPS (same as the windows backend)
+        let delta = (-self.mouse_delta.0 as f32, -self.mouse_delta.1 as f32);
+        self.center_cursor(window_info);
+
+        delta
+    }
+
+    fn center_cursor(self: &mut GlutinInput, window_info: &WindowInfo)
+    {
+        self.mouse_delta = (0.0, 0.0);
+
+        let center = LogicalPosition::new(window_info.size.width as f64 / 2.0, window_info.size.height as f64 / 2.0);
+
+        // Not every platform allows moving the cursor. Mouse look still
+        // works from the raw motion events when this fails.
+        let _ = self.gl.gl_window().window().set_cursor_position(center);
+    }
+}
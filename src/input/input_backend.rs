@@ -0,0 +1,29 @@
+
+use glium::glutin;
+use crate::graphics::WindowInfo;
+use crate::input::{KeyCode, MouseState};
+
+/// A source of keyboard and mouse state. The InputManager
+/// talks to one of these so the game code does not care whether
+/// the state comes from polling the OS or from window events.
+pub trait InputBackend
+{
+    /// Gives the backend a chance to see every event from the events loop.
+    /// Backends that poll the OS directly can ignore this.
+    fn process_event(&mut self, event: &glutin::Event);
+
+    /// Returns true if the key is currently held down
+    fn key_down(&self, key: KeyCode) -> bool;
+
+    /// Returns the current cursor position and button states
+    fn get_mouse_state(&self) -> MouseState;
+
+    /// Returns how far the mouse has moved since the last call (in the
+    /// same direction convention as CameraFPS::apply_look_offset) and
+    /// moves the cursor back to the center of the window.
+    fn take_look_delta(&mut self, window_info: &WindowInfo) -> (f32, f32);
+
+    /// Moves the cursor to the center of the window and throws away any
+    /// mouse movement that has not been read yet.
+    fn center_cursor(&mut self, window_info: &WindowInfo);
+}
@@ -0,0 +1,153 @@
+
+use glium::glutin::VirtualKeyCode;
+
+// The values match the Windows virtual key codes so the winapi backend
+// can pass them straight to GetAsyncKeyState. Other backends only use
+// them as indices into their key state tables.
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyCode
+{
+    NUM0 = '0' as isize,
+    NUM1 = '1' as isize,
+    NUM2 = '2' as isize,
+    NUM3 = '3' as isize,
+    NUM4 = '4' as isize,
+    NUM5 = '5' as isize,
+    NUM6 = '6' as isize,
+    NUM7 = '7' as isize,
+    NUM8 = '8' as isize,
+    NUM9 = '9' as isize,
+    A = 'A' as isize,
+    B = 'B' as isize,
+    C = 'C' as isize,
+    D = 'D' as isize,
+    E = 'E' as isize,
+    F = 'F' as isize,
+    G = 'G' as isize,
+    H = 'H' as isize,
+    I = 'I' as isize,
+    J = 'J' as isize,
+    K = 'K' as isize,
+    L = 'L' as isize,
+    M = 'M' as isize,
+    N = 'N' as isize,
+    O = 'O' as isize,
+    P = 'P' as isize,
+    Q = 'Q' as isize,
+    R = 'R' as isize,
+    S = 'S' as isize,
+    T = 'T' as isize,
+    U = 'U' as isize,
+    V = 'V' as isize,
+    W = 'W' as isize,
+    X = 'X' as isize,
+    Y = 'Y' as isize,
+    Z = 'Z' as isize,
+
+    F1 = 0x70,
+    F2 = 0x71,
+    F3 = 0x72,
+    F4 = 0x73,
+    F5 = 0x74,
+    F6 = 0x75,
+    F7 = 0x76,
+    F8 = 0x77,
+    F9 = 0x78,
+    F10 = 0x79,
+    F11 = 0x7A,
+    F12 = 0x7B,
+    SPACE = 0x20,
+    LCONTROL = 0xA2,
+    RCONTROL = 0xA3,
+    LSHIFT = 0xA0,
+    RSHIFT = 0xA1,
+    LMENU = 0xA4,
+    RMENU = 0xA5,
+    ENTER = 0x0D,
+    Escape = 0x1B,
+
+    UP = 0x26,
+    DOWN = 0x28,
+    LEFT = 0x25,
+    RIGHT = 0x27,
+}
+
+impl KeyCode
+{
+    /// Maps a glutin virtual key to a KeyCode. Returns None for keys
+    /// the game does not use.
+    pub fn from_virtual_key(key: VirtualKeyCode) -> Option<KeyCode>
+    {
+        let code = match key
+        {
+            VirtualKeyCode::Key0 => KeyCode::NUM0,
+            VirtualKeyCode::Key1 => KeyCode::NUM1,
+            VirtualKeyCode::Key2 => KeyCode::NUM2,
+            VirtualKeyCode::Key3 => KeyCode::NUM3,
+            VirtualKeyCode::Key4 => KeyCode::NUM4,
+            VirtualKeyCode::Key5 => KeyCode::NUM5,
+            VirtualKeyCode::Key6 => KeyCode::NUM6,
+            VirtualKeyCode::Key7 => KeyCode::NUM7,
+            VirtualKeyCode::Key8 => KeyCode::NUM8,
+            VirtualKeyCode::Key9 => KeyCode::NUM9,
+            VirtualKeyCode::A => KeyCode::A,
+            VirtualKeyCode::B => KeyCode::B,
+            VirtualKeyCode::C => KeyCode::C,
+            VirtualKeyCode::D => KeyCode::D,
+            VirtualKeyCode::E => KeyCode::E,
+            VirtualKeyCode::F => KeyCode::F,
+            VirtualKeyCode::G => KeyCode::G,
+            VirtualKeyCode::H => KeyCode::H,
+            VirtualKeyCode::I => KeyCode::I,
+            VirtualKeyCode::J => KeyCode::J,
+            VirtualKeyCode::K => KeyCode::K,
+            VirtualKeyCode::L => KeyCode::L,
+            VirtualKeyCode::M => KeyCode::M,
+            VirtualKeyCode::N => KeyCode::N,
+            VirtualKeyCode::O => KeyCode::O,
+            VirtualKeyCode::P => KeyCode::P,
+            VirtualKeyCode::Q => KeyCode::Q,
+            VirtualKeyCode::R => KeyCode::R,
+            VirtualKeyCode::S => KeyCode::S,
+            VirtualKeyCode::T => KeyCode::T,
+            VirtualKeyCode::U => KeyCode::U,
+            VirtualKeyCode::V => KeyCode::V,
+            VirtualKeyCode::W => KeyCode::W,
+            VirtualKeyCode::X => KeyCode::X,
+            VirtualKeyCode::Y => KeyCode::Y,
+            VirtualKeyCode::Z => KeyCode::Z,
+
+            VirtualKeyCode::F1 => KeyCode::F1,
+            VirtualKeyCode::F2 => KeyCode::F2,
+            VirtualKeyCode::F3 => KeyCode::F3,
+            VirtualKeyCode::F4 => KeyCode::F4,
+            VirtualKeyCode::F5 => KeyCode::F5,
+            VirtualKeyCode::F6 => KeyCode::F6,
+            VirtualKeyCode::F7 => KeyCode::F7,
+            VirtualKeyCode::F8 => KeyCode::F8,
+            VirtualKeyCode::F9 => KeyCode::F9,
+            VirtualKeyCode::F10 => KeyCode::F10,
+            VirtualKeyCode::F11 => KeyCode::F11,
+            VirtualKeyCode::F12 => KeyCode::F12,
+            VirtualKeyCode::Space => KeyCode::SPACE,
+            VirtualKeyCode::LControl => KeyCode::LCONTROL,
+            VirtualKeyCode::RControl => KeyCode::RCONTROL,
+            VirtualKeyCode::LShift => KeyCode::LSHIFT,
+            VirtualKeyCode::RShift => KeyCode::RSHIFT,
+            VirtualKeyCode::LAlt => KeyCode::LMENU,
+            VirtualKeyCode::RAlt => KeyCode::RMENU,
+            VirtualKeyCode::Return => KeyCode::ENTER,
+            VirtualKeyCode::Escape => KeyCode::Escape,
+
+            VirtualKeyCode::Up => KeyCode::UP,
+            VirtualKeyCode::Down => KeyCode::DOWN,
+            VirtualKeyCode::Left => KeyCode::LEFT,
+            VirtualKeyCode::Right => KeyCode::RIGHT,
+
+            _ => return None
+        };
+
+        Some(code)
+    }
+}
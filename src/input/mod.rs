@@ -0,0 +1,18 @@
+
+pub use self::key_code::KeyCode;
+pub use self::mouse_state::MouseState;
+pub use self::mouse_state::Coord;
+pub use self::mouse_state::MouseButton;
+pub use self::input_backend::InputBackend;
+pub use self::glutin_input::GlutinInput;
+
+#[cfg(windows)]
+pub use self::win_input::WinInput;
+
+mod key_code;
+mod mouse_state;
+mod input_backend;
+mod glutin_input;
+
+#[cfg(windows)]
+mod win_input;
@@ -0,0 +1,33 @@
+
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone)]
+pub struct Coord { pub x: i32, pub y: i32 }
+
+#[derive(Debug, Copy, Clone)]
+pub struct MouseState
+{
+    pub coords: Coord,
+    pub left_button: bool,
+    pub right_button: bool,
+    pub middle_button: bool,
+    pub button_one: bool,
+    pub button_two: bool
+}
+
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MouseButton
+{
+    LEFT,
+    RIGHT,
+    MIDDLE,
+}
+
+impl MouseState
+{
+    pub fn new() -> MouseState
+    {
+        MouseState { coords: Coord { x: 0, y: 0 }, left_button: false, right_button: false, 
+                    middle_button: false, button_one: false, button_two: false }
+    }
+}
@@ -0,0 +1,43 @@
+
+
+use crate::input::KeyCode;
+
+pub struct KeyBoard
+{
+}
+
+#[allow(dead_code)]
+impl KeyBoard
+{
+    pub fn key_pressed(key: KeyCode) -> bool
+    {
+        use winapi::um::winuser::{ GetAsyncKeyState };
+        let mut state = false;
+
+        unsafe
+        {
+            if GetAsyncKeyState(key as i32) as u32 & 0x01 != 0
+            {
+                state = true;
+            }
+        }
+
+        return state;
+    }
+
+    pub fn key_down(key: KeyCode) -> bool
+    {
+        use winapi::um::winuser::{ GetAsyncKeyState };
+        let mut state = false;
+
+        unsafe
+        {
+            if GetAsyncKeyState(key as i32) as u32 & 0x8000 != 0
+            {
+                state = true;
+            }
+        }
+
+        return state;
+    }
+}
\ No newline at end of file
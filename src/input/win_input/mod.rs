@@ -0,0 +1,7 @@
+pub use self::key_board::KeyBoard;
+pub use self::mouse::Mouse;
+pub use self::win_backend::WinInput;
+
+mod key_board;
+mod mouse;
+mod win_backend;
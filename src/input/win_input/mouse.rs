@@ -1,18 +1,5 @@
 
-
-#[derive(Debug)]
-pub struct Coord { pub x: i32, pub y: i32 }
-
-#[derive(Debug)]
-pub struct MouseState
-{
-    pub coords: Coord,
-    pub left_button: bool,
-    pub right_button: bool,
-    pub middle_button: bool,
-    pub button_one: bool,
-    pub button_two: bool
-}
+use crate::input::{Coord, MouseState};
 
 pub struct Mouse
 {
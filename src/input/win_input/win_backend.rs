@@ -0,0 +1,53 @@
+
+use glium::glutin;
+use crate::graphics::WindowInfo;
+use crate::input::{InputBackend, KeyCode, MouseState};
+use crate::input::win_input::{KeyBoard, Mouse};
+
+/// Input backend that polls the keyboard and mouse through winapi.
+/// Cursor coordinates are in screen space.
+pub struct WinInput
+{
+}
+
+impl WinInput
+{
+    pub fn new() -> WinInput
+    {
+        WinInput { }
+    }
+}
+
+impl InputBackend for WinInput
+{
+    fn process_event(self: &mut WinInput, _event: &glutin::Event)
+    {
+        // Everything is polled from the OS
+    }
+
+    fn key_down(self: &WinInput, key: KeyCode) -> bool
+    {
+        KeyBoard::key_down(key)
+    }
+
+    fn get_mouse_state(self: &WinInput) -> MouseState
+    {
+        Mouse::get_state()
+    }
+
+    fn take_look_delta(self: &mut WinInput, window_info: &WindowInfo) -> (f32, f32)
+    {
+        let mouse_state = Mouse::get_state();
+        let delta_x = window_info.center.x - mouse_state.coords.x;
+        let delta_y = window_info.center.y - mouse_state.coords.y;
+
+        Mouse::set_position(window_info.center.x, window_info.center.y);
+
+        (delta_x as f32, delta_y as f32)
+    }
+
+    fn center_cursor(self: &mut WinInput, window_info: &WindowInfo)
+    {
+        Mouse::set_position(window_info.center.x, window_info.center.y);
+    }
+}
@@ -10,6 +10,7 @@
 8                   love to fix it, I think I'd rather just work on a new project.
 *                   So I'll leave this here for archive purposes.
 ******************************************************************************/
+
 use std::{thread, time, rc::Rc};
 
 #[macro_use]
@@ -20,24 +21,21 @@ extern crate glium_glyph;
 
 
 use glium::{glutin, Surface};
-use glutin::dpi::LogicalPosition;
+use glutin::dpi::{LogicalPosition, LogicalSize};
 
 // Local Modules
 pub mod utils;
-use utils::FrameTracker;
-
-#[cfg(windows)]
-mod win_input;
+use utils::{FrameTracker, FractalMode, NoiseAlgorithm};
 
-#[cfg(windows)]
-use win_input::{Mouse};
+mod input;
 
 mod graphics;
 use graphics::{Gl, WindowInfo, CameraFPS, GridPlane, Mesh, Program, Texture, Flip};
 
 mod game;
-use game::{GameData, DebugSettings, ChunkGeneration, NoiseType, AssetLib, 
-            InputManager, InputProcessor, /* ObjectDemoScene ,*/ ChunkDemoScene, WorldChunk};
+use game::{GameData, DebugSettings, ChunkGeneration, EditSettings, WorldSettings, DisplaySettings, MeshMode, NoiseType, AssetLib, 
+            InputManager, InputProcessor, /* ObjectDemoScene ,*/ ChunkDemoScene, WorldChunk, BlockRegistry, 
+            ExportSettings, noise_export, game_config, generators::GeneratorRegistry};
 //
 
 
@@ -46,9 +44,32 @@ use game::{GameData, DebugSettings, ChunkGeneration, NoiseType, AssetLib,
 ///////////////////////////////////////////////
 fn main() 
 {
+    // Headless mode, writes images of the generated terrain without opening a window
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| a.as_str()) == Some("export")
+    {
+        std::process::exit(run_export(&args[2..]));
+    }
+
+    if args.iter().any(|a| a == "--help")
+    {
+        game_config::print_usage();
+        return;
+    }
+
+    // Data for use with the game, from the config file and command line
+    let mut game_data = default_game_data();
+    if let Err(e) = game_data.configure(&args[1..])
+    {
+        println!("{}", e);
+        println!("Run with --help to list the options");
+        std::process::exit(1);
+    }
+
     // Window and OpenGL initialization
     let mut events_loop = glutin::EventsLoop::new();
-    let wb = glutin::WindowBuilder::new();
+    let wb = glutin::WindowBuilder::new()
+        .with_dimensions(LogicalSize::new(game_data.display.window_width as f64, game_data.display.window_height as f64));
     let cb = glutin::ContextBuilder::new().with_depth_buffer(24);
     let display = Gl { inner: Rc::new(glium::Display::new(wb, cb, &events_loop).unwrap()) } ;
 
@@ -58,7 +79,7 @@ fn main()
     // Window info setup
     let mut window_info = WindowInfo::calculate_window_info(&display);
     let perspective = glm::perspective_lh(window_info.size.width as f32 / window_info.size.height as f32, 
-                                            (60.0 as f32).to_radians(), 0.1, 1024.0);
+                                            game_data.display.fov.to_radians(), 0.1, game_data.display.view_distance);
     
     // Test font
     // let dejavu: &[u8] = include_bytes!("../assets/fonts/open-sans/OpenSans-Bold.ttf");
@@ -77,21 +98,14 @@ fn main()
     let mut asset_lib = AssetLib::new(&display);
 
     // Input manager
-    let mut input_manager = InputManager::new();
+    let mut input_manager = InputManager::new(&display);
 
-    // Data for use with the game
     // let mut game_data = GameData { print_help: true, print_chunk_info: true, remake_test_scene: false, noise_type: NoiseType::SIMPLEX_2D, 
     //                                 zoom_factor: 0.01, threshold: 0.3, threshold_falloff: 20, octaves: 3, bias: 0.5, seed: Some([0; 32]) };
-    let mut game_data = GameData { debug: DebugSettings { print_help: true, print_chunk_info: true, remake_test_scene: false }, 
-                                    chunk_generation: ChunkGeneration { noise_type: NoiseType::SIMPLEX_2D, offset:(0.0, 0.0), zoom_factor: 0.01, 
-                                                                        sx_scale: 32.0, threshold: 0.3, threshold_falloff: 20, 
-                                                                        octaves: 3, bias: 0.5, seed: Some([0; 32]) } };
 
     // Scenes for demoing/debugging game systems
     //let mut obj_demo_scene = ObjectDemoScene::new(&mut asset_lib, &display, &perspective).unwrap();
-    let mut chunk_test_scene = ChunkDemoScene::new(&mut asset_lib, display.clone(), &perspective).unwrap();
-
-    chunk_test_scene.make_simplex_noise2D(&game_data);
+    let mut chunk_test_scene = ChunkDemoScene::new(&mut asset_lib, display.clone(), &perspective, &game_data).unwrap();
     //
     
     ///////////////////////////////////////////////////////////
@@ -101,7 +115,7 @@ fn main()
     let mut window_focused = true;
     let mut closed = false;
     
-    Mouse::set_position(window_info.center.x, window_info.center.y);
+    input_manager.center_cursor(&window_info);
     while !closed 
     {
         //////////////////////
@@ -137,7 +151,7 @@ fn main()
         ////////////////////
         // Update Game
 
-        chunk_test_scene.update(&mut game_data, delta_time);
+        chunk_test_scene.update(&mut game_data, &camera, delta_time, total_time);
         //
 
         /////////////////////
@@ -147,7 +161,7 @@ fn main()
 
         // render objects
         // obj_demo_scene.render_scene(&mut asset_lib, &mut target, &camera.get_view());
-        chunk_test_scene.render_scene(&mut asset_lib, &game_data, &window_info, &(*display.inner), &mut target, &camera.get_view());
+        chunk_test_scene.render_scene(&mut asset_lib, &game_data, &window_info, &display.inner, &mut target, &camera.get_view());
 
         target.finish().unwrap();
         //
@@ -157,21 +171,22 @@ fn main()
         // listing the events produced by application and waiting to be received
         events_loop.poll_events(|ev| 
         {
-            match ev 
+            input_manager.process_event(&ev);
+
+            if let glutin::Event::WindowEvent { event, .. } = ev
             {
-                glutin::Event::WindowEvent { event, .. } => match event 
+                match event
                 {
                     glutin::WindowEvent::CloseRequested => closed = true,
                     glutin::WindowEvent::Focused(f) => 
                     {
                         window_focused = f;
-                        Mouse::set_position(window_info.center.x, window_info.center.y);
+                        input_manager.center_cursor(&window_info);
                     },
                     glutin::WindowEvent::Resized(_) => window_info = WindowInfo::calculate_window_info(&display),
                     glutin::WindowEvent::Moved(_) => window_info = WindowInfo::calculate_window_info(&display),
                     _ => (),
-                },
-                _ => (),
+                }
             }
         });
         //
@@ -183,6 +198,96 @@ fn main()
     }
 }
 
+///////////////////////////////////////////////
+//      Default Settings
+///////////////////////////////////////////////
+fn default_game_data() -> GameData
+{
+    GameData { debug: DebugSettings { print_help: true, print_chunk_info: true, remake_test_scene: false,
+                                        occlusion_culling: true, show_occluded_sections: false }, 
+                chunk_generation: ChunkGeneration { noise_type: NoiseType::SIMPLEX_2D, offset:(0.0, 0.0), zoom_factor: 0.01, 
+                                                    sx_scale: 32.0, threshold: 0.3, threshold_falloff: 20, 
+                                                    octaves: 3, bias: 0.5, seed: Some([0; 32]),
+                                                    fractal_mode: FractalMode::FBM, lacunarity: 2.0, gain: 0.5,
+                                                    warp_strength: 1.0, warp_frequency: 1.0, double_warp: false,
+                                                    base_noise: NoiseAlgorithm::SIMPLEX, time: 0.0 },
+                edit: EditSettings { selected_block: 1, reach: 8.0, break_block: false, place_block: false, next_block: false },
+                world: WorldSettings { chunk_width: 128, chunk_height: 32, chunk_depth: 128, 
+                                        load_radius: 1, max_uploads_per_frame: 2, cull_chunk_borders: true, 
+                                        compress_chunks: true, save_dir: Some(String::from("saves/default")), 
+                                        worker_threads: 0, 
+                                        mesh_mode: MeshMode::INSTANCED, 
+                                        lod_distances: vec![96.0, 192.0, 384.0],
+                                        animation_speed: 0.25, animation_rate: 5.0 },
+                display: DisplaySettings { window_width: 1024, window_height: 768, fov: 60.0, view_distance: 1024.0 } }
+}
+
+///////////////////////////////////////////////
+//      Export Mode
+///////////////////////////////////////////////
+fn run_export(args: &[String]) -> i32
+{
+    // Generation settings the export options don't override come from the config file
+    let mut game_data = default_game_data();
+    let config = game_config::take_config_arg(args).and_then(|(config, rest)| 
+    {
+        game_data.load_config_or_default(config.as_deref())?;
+        Ok(rest)
+    });
+
+    let args = match config
+    {
+        Ok(rest) => rest,
+        Err(e) => 
+        {
+            println!("{}", e);
+            return 1;
+        }
+    };
+
+    if args.iter().any(|a| a == "--help")
+    {
+        noise_export::print_export_usage();
+        return 0;
+    }
+
+    let chunk_size = (game_data.world.chunk_width, game_data.world.chunk_height, game_data.world.chunk_depth);
+    let mut settings = ExportSettings::new(game_data.chunk_generation, chunk_size);
+    if let Err(e) = settings.parse_args(&mut game_data, &args)
+    {
+        println!("{}", e);
+        noise_export::print_export_usage();
+        return 1;
+    }
+
+    let blocks = match BlockRegistry::load("assets/blocks.cfg")
+    {
+        Ok(blocks) => blocks,
+        Err(e) => 
+        {
+            println!("{}", e);
+            return 1;
+        }
+    };
+
+    match noise_export::run_export(&settings, &GeneratorRegistry::with_defaults(), &blocks)
+    {
+        Ok(files) => 
+        {
+            for file in files
+            {
+                println!("Wrote {}", file);
+            }
+            0
+        },
+        Err(e) => 
+        {
+            println!("{}", e);
+            1
+        }
+    }
+}
+
 ///////////////////////////////////////////////
 //      Check Input Function
 ///////////////////////////////////////////////
@@ -193,12 +298,6 @@ fn main()
 // }
 
 
-#[cfg(not(windows))]
-fn check_input(cam: &mut CameraFPS, mouse_lock_point: &Point) -> bool
-{
-    true
-}
-
 #[allow(dead_code)]
 fn print_controls()
 {
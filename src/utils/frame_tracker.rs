@@ -84,4 +84,12 @@ impl FrameTracker
     {
         (Instant::now() - self.start_time).as_micros() / self.total_frames
     }
-}
\ No newline at end of file
+}
+
+impl Default for FrameTracker
+{
+    fn default() -> FrameTracker
+    {
+        FrameTracker::new()
+    }
+}
@@ -4,9 +4,21 @@
 pub use self::frame_tracker::FrameTracker;
 pub use self::noise::OlcNoise;
 pub use self::noise::SimplexNoise;
+pub use self::noise::FractalNoise;
+pub use self::noise::FractalMode;
+pub use self::noise::DomainWarp;
+pub use self::noise::{Noise, Noise2D, Noise3D, NoiseAlgorithm};
+pub use self::perlin_noise::PerlinNoise;
+pub use self::open_simplex2::OpenSimplex2Noise;
+pub use self::value_noise::ValueNoise;
+pub use self::worley_noise::{WorleyNoise, WorleyDistance};
 
 mod frame_tracker;
 mod noise;
+mod perlin_noise;
+mod open_simplex2;
+mod value_noise;
+mod worley_noise;
 
 use std::{time::Instant};
 extern crate nalgebra_glm as glm;
@@ -17,99 +17,233 @@
 
 // Should remove unused_variables, unused_mut when module is complete
 #![allow(unused_variables, unused_mut, dead_code, non_snake_case)]
-use rand::{ /* prelude::*, */ Rng, rngs::StdRng, SeedableRng};
+use rand::{ /* prelude::*, */ Rng, rngs::StdRng, SeedableRng, seq::SliceRandom};
+use crate::utils::{ PerlinNoise, OpenSimplex2Noise, ValueNoise, WorleyNoise, WorleyDistance };
 
-pub struct OlcNoise
+/// 2D noise with values between -1 and 1
+pub trait Noise2D
 {
-    width: i32,
-    height: i32,
-    seed2D: Vec<f32>,
-    rng: StdRng,
+    fn sample_2D(&self, x: f32, y: f32) -> f32;
 }
 
-impl OlcNoise
+/// 3D noise with values between -1 and 1
+pub trait Noise3D
 {
-    pub fn new(width: i32, height: i32, seed: Option<[u8; 32]> ) -> OlcNoise
-    {
-        let seed = match seed
-        {
-            Some(s) => s,
-            None => get_system_seed()
-        };
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32;
+}
 
-        // Generate the 2D Noise Seed
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-        let size: usize = (width * height) as usize;
-        let mut seed2D: Vec<f32> = vec![0.0; size];
-        for i in 0..size
+/// Noise that can be sampled in 2D and 3D, so generators can hold any algorithm
+pub trait Noise: Noise2D + Noise3D + Send + Sync
+{
+}
+
+impl<T: Noise2D + Noise3D + Send + Sync> Noise for T
+{
+}
+
+/// The noise algorithms that implement Noise, for choosing one in the settings
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoiseAlgorithm
+{
+    SIMPLEX,
+    OPEN_SIMPLEX2,
+    PERLIN,
+    VALUE,
+    WORLEY_F1,
+    WORLEY_F2,
+    WORLEY_F2_MINUS_F1,
+}
+
+impl NoiseAlgorithm
+{
+    pub const ALL: [NoiseAlgorithm; 7] = [NoiseAlgorithm::SIMPLEX, NoiseAlgorithm::OPEN_SIMPLEX2, NoiseAlgorithm::PERLIN, 
+                                            NoiseAlgorithm::VALUE, NoiseAlgorithm::WORLEY_F1, NoiseAlgorithm::WORLEY_F2, 
+                                            NoiseAlgorithm::WORLEY_F2_MINUS_F1];
+
+    pub fn create(self: NoiseAlgorithm, seed: Option<[u8; 32]>) -> Box<dyn Noise>
+    {
+        match self
         {
-            // Generate a random value between 0 and 1
-            let value = rng.gen::<f32>();
-            seed2D[i as usize] = value;
+            NoiseAlgorithm::SIMPLEX => Box::new(SimplexNoise::new(seed)),
+            NoiseAlgorithm::OPEN_SIMPLEX2 => Box::new(OpenSimplex2Noise::new(seed)),
+            NoiseAlgorithm::PERLIN => Box::new(PerlinNoise::new(seed)),
+            NoiseAlgorithm::VALUE => Box::new(ValueNoise::new(seed)),
+            NoiseAlgorithm::WORLEY_F1 => Box::new(WorleyNoise::new(seed, WorleyDistance::F1)),
+            NoiseAlgorithm::WORLEY_F2 => Box::new(WorleyNoise::new(seed, WorleyDistance::F2)),
+            NoiseAlgorithm::WORLEY_F2_MINUS_F1 => Box::new(WorleyNoise::new(seed, WorleyDistance::F2_MINUS_F1)),
         }
+    }
 
-        OlcNoise { width, height, seed2D, rng  }
+    /// Lower case name without underscores ("opensimplex2", "worleyf2minusf1")
+    pub fn name(self: NoiseAlgorithm) -> String
+    {
+        format!("{:?}", self).replace('_', "").to_lowercase()
     }
 
+    pub fn from_name(name: &str) -> Option<NoiseAlgorithm>
+    {
+        let name = name.to_lowercase();
+        NoiseAlgorithm::ALL.iter().find(|a| a.name() == name).cloned()
+    }
 
+    /// The next algorithm, for cycling through them
+    pub fn next(self: NoiseAlgorithm) -> NoiseAlgorithm
+    {
+        let i = NoiseAlgorithm::ALL.iter().position(|a| *a == self).unwrap_or(0);
+        NoiseAlgorithm::ALL[(i + 1) % NoiseAlgorithm::ALL.len()]
+    }
+}
 
-    pub fn sample2D(self: &OlcNoise, x: i32, y: i32, octaves: i32, bias: f32) -> f32
+/// The seed, or a random one for None
+pub fn seed_or_random(seed: Option<[u8; 32]>) -> [u8; 32]
+{
+    match seed
     {
-        // Algorithm by OneLoneCoder
-        // https://github.com/OneLoneCoder/videos/blob/master/OneLoneCoder_PerlinNoise.cpp
+        Some(s) => s,
+        None => get_system_seed()
+    }
+}
 
-        let mut noise: f32 = 0.0;
-        let mut scale_accel: f32 = 0.0;
-        let mut scale: f32 = 1.0;
+/// A shuffled permutation of 0 - 255, repeated so perm[perm[x] + y] never needs wrapping
+pub fn permutation_table(seed: [u8; 32]) -> [usize; 512]
+{
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let mut values: Vec<usize> = (0..256).collect();
+    values.shuffle(&mut rng);
 
-        let width = self.width;
+    let mut perm = [0; 512];
+    for i in 0..512
+    {
+        perm[i] = values[i & 255];
+    }
 
-        let x = x % self.width;
-        let y = y % self.height;
+    perm
+}
 
-        for o in 0..octaves
-        {
-            let pitch: i32 = width >> o;
-            let sample_x1: i32 = (x / pitch) * pitch;
-            let sample_y1: i32 = (y / pitch) * pitch;
+/// Random values from the seed, for the noise that needs them on top of the permutation
+pub fn random_table(seed: [u8; 32], len: usize) -> Vec<f32>
+{
+    // Use different random numbers from the permutation table
+    let mut seed = seed;
+    seed[31] ^= 0x5A;
 
-           // println!("x: {}, y: {}, width: {}, pitch: {}, octave: {}", x, y, width, pitch, o);
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    (0..len).map(|_| rng.gen::<f32>()).collect()
+}
 
-            // if sample_y1 > 0 || sample_x1 > 0
-            // {
-            //     println!("pitch: {}, octave: {}, sample_x1: {}, sample_y1: {}", pitch, o, sample_x1, sample_y1);
-            // }
-            
-            let sample_x2: i32 = (sample_x1 + pitch) % width;
-            let sample_y2: i32 = (sample_y1 + pitch) % width;
+/// Quintic smoothing used to blend between lattice points
+pub fn fade(t: f32) -> f32
+{
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
 
-            // if sample_y2 > 0 || sample_x2 > 0
-            // {
-            //     println!("pitch: {}, octave: {}, sample_x2: {}, sample_y2: {}", pitch, o, sample_x2, sample_y2);
-            // }
+pub fn lerp(a: f32, b: f32, t: f32) -> f32
+{
+    a + t * (b - a)
+}
 
-            let blend_x: f32 = ((x - sample_x1) as f32) / (pitch as f32);
-            let blend_y: f32 = ((y - sample_y1) as f32) / (pitch as f32);
+fn gcd(a: i64, b: i64) -> i64
+{
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Value noise with octaves of halving pitch (One Lone Coder's algorithm). The random
+/// values come from hashing the lattice coordinates, so any i64 position can be sampled
+/// and the noise never repeats unless it's made tileable.
+pub struct OlcNoise
+{
+    // Distance between samples in the first octave, halved each octave
+    pitch: i64,
+
+    // The noise repeats every period blocks on both axes
+    period: Option<i64>,
+
+    seed: u64,
+}
+
+impl OlcNoise
+{
+    /// The pitch is the size of the largest features, a power of 2 gives the most octaves
+    pub fn new(pitch: i64, seed: Option<[u8; 32]>) -> OlcNoise
+    {
+        let mut rng: StdRng = SeedableRng::from_seed(seed_or_random(seed));
+        OlcNoise { pitch: pitch.max(1), period: None, seed: rng.gen() }
+    }
+
+    /// Noise that repeats every period blocks, for seamless textures. The period is
+    /// rounded up to a multiple of every octave's pitch so they all line up at the edges.
+    pub fn new_tileable(pitch: i64, period: i64, seed: Option<[u8; 32]>) -> OlcNoise
+    {
+        let mut noise = OlcNoise::new(pitch, seed);
+
+        // Pitches that aren't a power of 2 don't divide evenly when halved (24, 12, 6, 3, 1)
+        let step = (0..noise.max_octaves()).map(|o| noise.pitch >> o).fold(1, |l, p| l / gcd(l, p) * p);
+
+        let period = period.max(1);
+        noise.period = Some((period + step - 1) / step * step);
+        noise
+    }
 
+    pub fn period(self: &OlcNoise) -> Option<i64>
+    {
+        self.period
+    }
+
+    /// Octaves past this would have a pitch smaller than one block
+    pub fn max_octaves(self: &OlcNoise) -> i32
+    {
+        64 - self.pitch.leading_zeros() as i32
+    }
+
+    /// Random value between 0 and 1 for a lattice point
+    fn value(self: &OlcNoise, x: i64, y: i64) -> f32
+    {
+        let (x, y) = match self.period
+        {
+            Some(period) => (x.rem_euclid(period), y.rem_euclid(period)),
+            None => (x, y)
+        };
 
-            //println!("pitch: {}, sample_y1: {}, width: {}", pitch, sample_y1, width);
-            let idx1 = (sample_y1 * width + sample_x1) as usize;
-            let idx2 = (sample_y1 * width + sample_x2) as usize;
+        // splitmix64 finalizer over the seeded coordinates
+        let mut h = self.seed ^ (x as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (y as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+        h = (h ^ (h >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        h = (h ^ (h >> 27)).wrapping_mul(0x94D049BB133111EB);
+        h ^= h >> 31;
 
-           // println!("Idx1: {}, Idx2: {}", idx1, idx2);
+        (h >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Value between 0 and 1. Octaves are clamped between 1 and max_octaves.
+    pub fn sample2D(self: &OlcNoise, x: i64, y: i64, octaves: i32, bias: f32) -> f32
+    {
+        // Algorithm by OneLoneCoder
+        // https://github.com/OneLoneCoder/videos/blob/master/OneLoneCoder_PerlinNoise.cpp
+
+        let mut noise: f32 = 0.0;
+        let mut scale_accel: f32 = 0.0;
+        let mut scale: f32 = 1.0;
 
-            let sample_t: f32 = (1.0 - blend_x) * self.seed2D[idx1] + blend_x * self.seed2D[idx2];
+        for o in 0..octaves.max(1).min(self.max_octaves())
+        {
+            let pitch = self.pitch >> o;
 
-            let idx3 = (sample_y2 * width + sample_x1) as usize;
-            let idx4 = (sample_y2 * width + sample_x2) as usize;
+            // div_euclid so negative positions use the cell below them
+            let sample_x1 = x.div_euclid(pitch) * pitch;
+            let sample_y1 = y.div_euclid(pitch) * pitch;
+            // The lattice wraps at the ends of the i64 range
+            let sample_x2 = sample_x1.wrapping_add(pitch);
+            let sample_y2 = sample_y1.wrapping_add(pitch);
 
-            //println!("Idx3: {}, Idx4: {}", idx3, idx4);
+            let blend_x: f32 = ((x - sample_x1) as f32) / (pitch as f32);
+            let blend_y: f32 = ((y - sample_y1) as f32) / (pitch as f32);
 
-            let sample_b: f32 = (1.0 - blend_x) * self.seed2D[idx3] + blend_x * self.seed2D[idx4];
+            let sample_t: f32 = (1.0 - blend_x) * self.value(sample_x1, sample_y1) + blend_x * self.value(sample_x2, sample_y1);
+            let sample_b: f32 = (1.0 - blend_x) * self.value(sample_x1, sample_y2) + blend_x * self.value(sample_x2, sample_y2);
 
             scale_accel += scale;
             noise += (blend_y * (sample_b - sample_t) + sample_t) * scale;
-            scale = scale / bias;
+            scale /= bias;
         }
         noise / scale_accel
     }
@@ -178,9 +312,7 @@ impl SimplexNoise
                 continue;
             }
 
-            let temp = perm[i];
-            perm[i] = perm[j];
-            perm[j] = temp;
+            perm.swap(i, j);
         }
     }
 
@@ -221,7 +353,7 @@ float  SimplexNoise1234::grad( int hash, float x, float y ) {
             return -u + f;
         }
 
-        return u + f;
+        u + f
     }
 
     // Note: C implementation by Stefan Gustavson (stegu@itn.liu.se)
@@ -264,8 +396,8 @@ float  SimplexNoise1234::grad( int hash, float x, float y ) {
     #[allow(unused_assignments)]
     pub fn noise_2D(self: &SimplexNoise, x: f32, y: f32, scale_factor: f32) -> f32
     {
-         let F2: f32 = 0.366025403;
-        let G2: f32 = 0.211324865;
+         let F2: f32 = 0.366_025_4;
+        let G2: f32 = 0.211_324_87;
 
         let mut n0 = 0.0;
         let mut n1 = 0.0;
@@ -351,8 +483,8 @@ float  SimplexNoise1234::grad( int hash, float x, float y ) {
     {        
 
     // // Simple skewing factors for the 3D case
-        let F3 = 0.333333333;
-        let G3 = 0.166666667;
+        let F3 = 0.333_333_34;
+        let G3 = 0.166_666_67;
 
     // Noise contributions from the four corners
         let mut n0 = 0.0;
@@ -526,15 +658,514 @@ float  SimplexNoise1234::grad( int hash, float x, float y ) {
         //32.0 * (n0 + n1 + n2 + n3)
         scale_factor * (n0 + n1 + n2 + n3)
     }
+
+    // Note: C implementation by Stefan Gustavson (stegu@itn.liu.se)
+    // 32 gradients, the midpoints of the edges of a 4D hypercube
+    fn grad_4d(hash: i32, x: f32, y: f32, z: f32, w: f32) -> f32
+    {
+        let h = hash & 31;
+        let u = if h < 24 { x } else { y };
+        let v = if h < 16 { y } else { z };
+        let t = if h < 8 { z } else { w };
+
+        (if h & 1 > 0 { -u } else { u }) + (if h & 2 > 0 { -v } else { v }) + (if h & 4 > 0 { -t } else { t })
+    }
+
+    /// 4D simplex noise. Use 27.0 as the scale factor to keep the result between -1 and 1.
+    // Note: C implementation by Stefan Gustavson (stegu@itn.liu.se)
+    pub fn noise_4D(self: &SimplexNoise, x: f32, y: f32, z: f32, w: f32, scale_factor: f32) -> f32
+    {
+        // Skewing factors for 4D: (sqrt(5) - 1) / 4 and (5 - sqrt(5)) / 20
+        let F4 = 0.309_017;
+        let G4 = 0.138_196_6;
+
+        // Skew the input space to find the hypercube cell we're in
+        let s = (x + y + z + w) * F4;
+        let i = (x + s).floor() as i32;
+        let j = (y + s).floor() as i32;
+        let k = (z + s).floor() as i32;
+        let l = (w + s).floor() as i32;
+
+        // Unskew the cell origin back to (x, y, z, w) space
+        let t = (i + j + k + l) as f32 * G4;
+        let x0 = x - (i as f32 - t);
+        let y0 = y - (j as f32 - t);
+        let z0 = z - (k as f32 - t);
+        let w0 = w - (l as f32 - t);
+
+        // The cell is split into 24 simplices. Ranking the coordinates by size gives the
+        // order the simplex's corners step along each axis.
+        let mut rank = [0; 4];
+        let offsets = [x0, y0, z0, w0];
+        for a in 0..4
+        {
+            for b in a + 1..4
+            {
+                if offsets[a] > offsets[b]
+                {
+                    rank[a] += 1;
+                }
+                else
+                {
+                    rank[b] += 1;
+                }
+            }
+        }
+
+        let ii = (i & 0xff) as usize;
+        let jj = (j & 0xff) as usize;
+        let kk = (k & 0xff) as usize;
+        let ll = (l & 0xff) as usize;
+        let perm = &self.perm;
+
+        // Corner c steps along the axes ranked higher than 3 - c, the last corner is (1, 1, 1, 1)
+        let mut n = 0.0;
+        for c in 0..5
+        {
+            let step = |r: i32| if c > 0 && r >= 4 - c { 1 } else { 0 };
+            let (i1, j1, k1, l1) = (step(rank[0]), step(rank[1]), step(rank[2]), step(rank[3]));
+
+            let offset = c as f32 * G4;
+            let x1 = x0 - i1 as f32 + offset;
+            let y1 = y0 - j1 as f32 + offset;
+            let z1 = z0 - k1 as f32 + offset;
+            let w1 = w0 - l1 as f32 + offset;
+
+            let mut t1 = 0.6 - x1 * x1 - y1 * y1 - z1 * z1 - w1 * w1;
+            if t1 > 0.0
+            {
+                let hash = perm[ii + i1 + perm[jj + j1 + perm[kk + k1 + perm[ll + l1] as usize] as usize] as usize];
+                t1 *= t1;
+                n += t1 * t1 * SimplexNoise::grad_4d(hash, x1, y1, z1, w1);
+            }
+        }
+
+        scale_factor * n
+    }
+}
+
+// The scale factors Gustavson's implementation uses to keep the noise between -1 and 1
+impl Noise2D for SimplexNoise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        self.noise_2D(x, y, 40.0).clamp(-1.0, 1.0)
+    }
+}
+
+impl Noise3D for SimplexNoise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        self.noise_3D(x, y, z, 32.0).clamp(-1.0, 1.0)
+    }
+}
+
+/// How the octaves of a FractalNoise are combined
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FractalMode
+{
+    /// Fractal Brownian motion, octaves are summed as they are
+    FBM,
+
+    /// 1 - |noise| squared, each octave weighted by the one before it. Gives sharp ridges.
+    RIDGED,
+
+    /// |noise|, rounded hills with creases between them
+    BILLOW,
+
+    /// Sum of |noise|, like BILLOW but without remapping each octave
+    TURBULENCE
+}
+
+impl FractalMode
+{
+    pub fn from_name(name: &str) -> Option<FractalMode>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "fbm" => Some(FractalMode::FBM),
+            "ridged" => Some(FractalMode::RIDGED),
+            "billow" => Some(FractalMode::BILLOW),
+            "turbulence" => Some(FractalMode::TURBULENCE),
+            _ => None
+        }
+    }
+
+    /// The next mode, for cycling through them
+    pub fn next(self: FractalMode) -> FractalMode
+    {
+        match self
+        {
+            FractalMode::FBM => FractalMode::RIDGED,
+            FractalMode::RIDGED => FractalMode::BILLOW,
+            FractalMode::BILLOW => FractalMode::TURBULENCE,
+            FractalMode::TURBULENCE => FractalMode::FBM,
+        }
+    }
+}
+
+/// Layers octaves of any noise function. Each octave samples the noise at lacunarity
+/// times the frequency and gain times the amplitude of the one before it.
+///
+/// The noise functions passed to sample_2D and sample_3D should return values
+/// between -1 and 1, and so does the result.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FractalNoise
+{
+    pub mode: FractalMode,
+    pub octaves: i32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl FractalNoise
+{
+    pub fn new(mode: FractalMode, octaves: i32, lacunarity: f32, gain: f32) -> FractalNoise
+    {
+        FractalNoise { mode, octaves, lacunarity, gain }
+    }
+
+    pub fn sample_2D<F: Fn(f32, f32) -> f32>(self: &FractalNoise, x: f32, y: f32, noise: F) -> f32
+    {
+        self.combine(|frequency| noise(x * frequency, y * frequency))
+    }
+
+    pub fn sample_3D<F: Fn(f32, f32, f32) -> f32>(self: &FractalNoise, x: f32, y: f32, z: f32, noise: F) -> f32
+    {
+        self.combine(|frequency| noise(x * frequency, y * frequency, z * frequency))
+    }
+
+    /// Sums the octaves, octave(frequency) samples the noise at that frequency
+    fn combine<F: Fn(f32) -> f32>(self: &FractalNoise, octave: F) -> f32
+    {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut amplitude_sum = 0.0;
+        let mut frequency = 1.0;
+
+        // Ridged octaves are weighted by the previous octave so ridges stay sharp
+        // and the valleys between them stay smooth
+        let mut weight = 1.0;
+
+        for _ in 0..self.octaves.max(1)
+        {
+            let n = octave(frequency).clamp(-1.0, 1.0);
+            let value = match self.mode
+            {
+                FractalMode::FBM => n,
+                FractalMode::BILLOW => n.abs() * 2.0 - 1.0,
+                FractalMode::TURBULENCE => n.abs(),
+                FractalMode::RIDGED =>
+                {
+                    let ridge = (1.0 - n.abs()).powi(2) * weight;
+                    weight = ridge.clamp(0.0, 1.0);
+                    ridge
+                }
+            };
+
+            total += value * amplitude;
+            amplitude_sum += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        let total = total / amplitude_sum;
+        match self.mode
+        {
+            // These are between 0 and 1
+            FractalMode::RIDGED | FractalMode::TURBULENCE => total * 2.0 - 1.0,
+            _ => total
+        }
+    }
+}
+
+/// Offsets sample positions by another noise field before they are used ("domain warping").
+/// Each axis is pushed by up to strength, using noise sampled at frequency times the
+/// position. A double warp warps the position used to compute the warp as well, which
+/// gives more folded, river-like shapes.
+///
+/// The noise functions should return values between -1 and 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DomainWarp
+{
+    pub strength: f32,
+    pub frequency: f32,
+    pub double: bool,
+}
+
+// Keep the noise for each axis (and each pass) uncorrelated
+const WARP_OFFSETS: [[f32; 3]; 6] = [[0.0, 0.0, 0.0], [5.2, 1.3, 7.1], [3.7, 8.9, 2.4],
+                                    [1.7, 9.2, 4.6], [8.3, 2.8, 6.5], [6.1, 4.4, 9.9]];
+
+impl DomainWarp
+{
+    pub fn new(strength: f32, frequency: f32, double: bool) -> DomainWarp
+    {
+        DomainWarp { strength, frequency, double }
+    }
+
+    pub fn warp_2D<F: Fn(f32, f32) -> f32>(self: &DomainWarp, x: f32, y: f32, noise: F) -> (f32, f32)
+    {
+        let pass = |px: f32, py: f32, offsets: &[[f32; 3]]|
+        {
+            let (fx, fy) = (px * self.frequency, py * self.frequency);
+            (noise(fx + offsets[0][0], fy + offsets[0][1]) * self.strength,
+             noise(fx + offsets[1][0], fy + offsets[1][1]) * self.strength)
+        };
+
+        let (mut qx, mut qy) = (x, y);
+        if self.double
+        {
+            let (wx, wy) = pass(x, y, &WARP_OFFSETS[3..]);
+            qx += wx;
+            qy += wy;
+        }
+
+        let (wx, wy) = pass(qx, qy, &WARP_OFFSETS[..3]);
+        (x + wx, y + wy)
+    }
+
+    pub fn warp_3D<F: Fn(f32, f32, f32) -> f32>(self: &DomainWarp, x: f32, y: f32, z: f32, noise: F) -> (f32, f32, f32)
+    {
+        let pass = |px: f32, py: f32, pz: f32, offsets: &[[f32; 3]]|
+        {
+            let (fx, fy, fz) = (px * self.frequency, py * self.frequency, pz * self.frequency);
+            let axis = |o: &[f32; 3]| noise(fx + o[0], fy + o[1], fz + o[2]) * self.strength;
+            (axis(&offsets[0]), axis(&offsets[1]), axis(&offsets[2]))
+        };
+
+        let (mut qx, mut qy, mut qz) = (x, y, z);
+        if self.double
+        {
+            let (wx, wy, wz) = pass(x, y, z, &WARP_OFFSETS[3..]);
+            qx += wx;
+            qy += wy;
+            qz += wz;
+        }
+
+        let (wx, wy, wz) = pass(qx, qy, qz, &WARP_OFFSETS[..3]);
+        (x + wx, y + wy, z + wz)
+    }
 }
 
 fn get_system_seed() -> [u8; 32]
 {
     let mut seed: [u8; 32] = [0; 32];
-    for i in 0..32
+    for byte in seed.iter_mut()
+    {
+        *byte = rand::random::<u8>();
+    }
+
+    seed
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const MODES: [FractalMode; 4] = [FractalMode::FBM, FractalMode::RIDGED, FractalMode::BILLOW, FractalMode::TURBULENCE];
+
+    #[test]
+    fn fractal_noise_stays_in_range()
+    {
+        let simplex = SimplexNoise::new(Some([3; 32]));
+        for mode in MODES.iter()
+        {
+            let fractal = FractalNoise::new(*mode, 5, 2.0, 0.5);
+            for i in 0..500
+            {
+                let (x, y) = (i as f32 * 0.37, i as f32 * 0.11 - 20.0);
+                let value = fractal.sample_2D(x, y, |x, y| simplex.noise_2D(x, y, 40.0));
+                assert!((-1.0..=1.0).contains(&value), "{:?} gave {}", mode, value);
+
+                let value = fractal.sample_3D(x, y, x - y, |x, y, z| simplex.noise_3D(x, y, z, 32.0));
+                assert!((-1.0..=1.0).contains(&value), "{:?} gave {}", mode, value);
+            }
+        }
+    }
+
+    #[test]
+    fn single_octave_fbm_is_the_noise()
+    {
+        let simplex = SimplexNoise::new(Some([3; 32]));
+        let fractal = FractalNoise::new(FractalMode::FBM, 1, 2.0, 0.5);
+        let noise = |x, y| simplex.noise_2D(x, y, 40.0);
+        assert_eq!(fractal.sample_2D(1.3, 4.7, noise), noise(1.3, 4.7));
+    }
+
+    #[test]
+    fn warp_moves_samples_by_at_most_the_strength()
+    {
+        let simplex = SimplexNoise::new(Some([3; 32]));
+        let noise = |x, y| simplex.noise_2D(x, y, 40.0).clamp(-1.0, 1.0);
+        for double in [false, true].iter()
+        {
+            let warp = DomainWarp::new(0.5, 2.0, *double);
+            let mut moved = false;
+            for i in 0..200
+            {
+                let (x, y) = (i as f32 * 0.13, i as f32 * -0.07);
+                let (wx, wy) = warp.warp_2D(x, y, noise);
+                assert!((wx - x).abs() <= 0.5 && (wy - y).abs() <= 0.5);
+                moved |= wx != x || wy != y;
+
+                let (wx, wy, wz) = warp.warp_3D(x, y, 1.0, |x, y, z| simplex.noise_3D(x, y, z, 32.0).clamp(-1.0, 1.0));
+                assert!((wx - x).abs() <= 0.5 && (wy - y).abs() <= 0.5 && (wz - 1.0).abs() <= 0.5);
+            }
+            assert!(moved);
+        }
+
+        // No strength, no warp
+        assert_eq!(DomainWarp::new(0.0, 2.0, true).warp_2D(1.5, 2.5, noise), (1.5, 2.5));
+    }
+
+    #[test]
+    fn octaves_use_lacunarity_and_gain()
+    {
+        // Noise that returns its x coordinate shows the frequency and weight of each octave
+        let fractal = FractalNoise::new(FractalMode::FBM, 3, 3.0, 0.25);
+        let value = fractal.sample_2D(0.1, 0.0, |x, _| x);
+        let expected = (0.1 + 0.3 * 0.25 + 0.9 * 0.0625) / (1.0 + 0.25 + 0.0625);
+        assert!((value - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn noise_algorithms_stay_in_range()
+    {
+        for algorithm in NoiseAlgorithm::ALL.iter()
+        {
+            let noise = algorithm.create(Some([5; 32]));
+            let (mut min, mut max) = (0.0f32, 0.0f32);
+            for i in 0..2000
+            {
+                let (x, y, z) = (i as f32 * 0.173 - 100.0, i as f32 * 0.061, (i % 37) as f32 * 0.29);
+                for value in [noise.sample_2D(x, y), noise.sample_3D(x, y, z)].iter()
+                {
+                    assert!(*value >= -1.0 && *value <= 1.0, "{:?} gave {}", algorithm, value);
+                    min = min.min(*value);
+                    max = max.max(*value);
+                }
+            }
+
+            // Not stuck at one value
+            assert!(max - min > 0.5, "{:?} only covers {} to {}", algorithm, min, max);
+        }
+    }
+
+    #[test]
+    fn noise_algorithms_follow_the_seed()
+    {
+        for algorithm in NoiseAlgorithm::ALL.iter()
+        {
+            let (a, b, c) = (algorithm.create(Some([1; 32])), algorithm.create(Some([1; 32])), algorithm.create(Some([2; 32])));
+            let points: Vec<(f32, f32, f32)> = (0..50).map(|i| (i as f32 * 0.53, i as f32 * 0.21 + 3.0, i as f32 * 0.37)).collect();
+
+            for &(x, y, z) in points.iter()
+            {
+                assert_eq!(a.sample_2D(x, y), b.sample_2D(x, y));
+                assert_eq!(a.sample_3D(x, y, z), b.sample_3D(x, y, z));
+            }
+
+            assert!(points.iter().any(|&(x, y, _)| a.sample_2D(x, y) != c.sample_2D(x, y)), "{:?}", algorithm);
+            assert!(points.iter().any(|&(x, y, z)| a.sample_3D(x, y, z) != c.sample_3D(x, y, z)), "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn noise_algorithm_names_round_trip()
+    {
+        for algorithm in NoiseAlgorithm::ALL.iter()
+        {
+            assert_eq!(NoiseAlgorithm::from_name(&algorithm.name()), Some(*algorithm));
+        }
+
+        assert_eq!(NoiseAlgorithm::from_name("OpenSimplex2"), Some(NoiseAlgorithm::OPEN_SIMPLEX2));
+        assert_eq!(NoiseAlgorithm::from_name("open_simplex2"), None);
+    }
+
+    #[test]
+    fn olc_noise_does_not_repeat()
+    {
+        let noise = OlcNoise::new(32, Some([4; 32]));
+        assert!((0..64).any(|x| noise.sample2D(x, 7, 4, 2.0) != noise.sample2D(x + 32, 7, 4, 2.0)));
+
+        // Far from the origin is still deterministic and in range
+        let far = i64::MAX / 2;
+        let value = noise.sample2D(far, -far, 6, 2.0);
+        assert_eq!(value, OlcNoise::new(32, Some([4; 32])).sample2D(far, -far, 6, 2.0));
+        assert!((0.0..=1.0).contains(&value));
+
+        // The last cells before the ends of the range don't overflow
+        for (x, y) in [(i64::MAX, i64::MAX), (i64::MIN, i64::MIN), (i64::MAX, i64::MIN)].iter()
+        {
+            assert!((0.0..=1.0).contains(&noise.sample2D(*x, *y, 6, 2.0)));
+        }
+    }
+
+    #[test]
+    fn tileable_olc_noise_repeats()
+    {
+        // 40 rounds up to a multiple of the pitch
+        let noise = OlcNoise::new_tileable(16, 40, Some([4; 32]));
+        assert_eq!(noise.period(), Some(48));
+
+        for i in -60..60
+        {
+            let (x, y) = (i * 3, i * 5 + 1);
+            assert_eq!(noise.sample2D(x, y, 5, 2.0), noise.sample2D(x + 48, y, 5, 2.0));
+            assert_eq!(noise.sample2D(x, y, 5, 2.0), noise.sample2D(x, y - 96, 5, 2.0));
+        }
+    }
+
+    #[test]
+    fn tileable_olc_noise_repeats_with_any_pitch()
     {
-        seed[i] = rand::random::<u8>();
+        // Octave pitches 13, 6, 3 and 1, the period has to be a multiple of 78
+        let noise = OlcNoise::new_tileable(13, 100, Some([4; 32]));
+        assert_eq!(noise.period(), Some(156));
+
+        for i in -60..60
+        {
+            let (x, y) = (i * 3, i * 5 + 1);
+            assert_eq!(noise.sample2D(x, y, 4, 2.0), noise.sample2D(x + 156, y, 4, 2.0));
+            assert_eq!(noise.sample2D(x, y, 4, 2.0), noise.sample2D(x, y - 156, 4, 2.0));
+        }
     }
 
-    return seed;
+    #[test]
+    fn olc_octaves_are_clamped()
+    {
+        let noise = OlcNoise::new(32, Some([4; 32]));
+        assert_eq!(noise.max_octaves(), 6);
+
+        for x in -20..20
+        {
+            assert_eq!(noise.sample2D(x, 3, 20, 2.0), noise.sample2D(x, 3, 6, 2.0));
+            assert_eq!(noise.sample2D(x, 3, 0, 2.0), noise.sample2D(x, 3, 1, 2.0));
+        }
+    }
+
+    #[test]
+    fn simplex_4d_stays_in_range_and_is_smooth()
+    {
+        let simplex = SimplexNoise::new(Some([3; 32]));
+        let (mut min, mut max) = (0.0f32, 0.0f32);
+        for i in 0..5000
+        {
+            let (x, y, z, w) = (i as f32 * 0.173 - 100.0, i as f32 * 0.061, (i % 37) as f32 * 0.29, (i % 53) as f32 * 0.31);
+            let value = simplex.noise_4D(x, y, z, w, 27.0);
+            assert!((-1.0..=1.0).contains(&value), "{}", value);
+            min = min.min(value);
+            max = max.max(value);
+
+            // A small step through time only changes the value a little
+            assert!((simplex.noise_4D(x, y, z, w + 0.001, 27.0) - value).abs() < 0.05);
+        }
+
+        assert!(max - min > 1.0, "only covers {} to {}", min, max);
+        assert_ne!(simplex.noise_4D(1.3, 2.7, 0.4, 0.0, 27.0), simplex.noise_4D(1.3, 2.7, 0.4, 5.5, 27.0));
+    }
 }
@@ -0,0 +1,228 @@
+
+#![allow(non_snake_case)]
+use rand::{ Rng, rngs::StdRng, SeedableRng };
+use crate::utils::noise::{ Noise2D, Noise3D, seed_or_random };
+
+// Port of the "fast" variant of OpenSimplex2 by K.jpg (public domain):
+// https://github.com/KdotJPG/OpenSimplex2
+
+const PRIME_X: i64 = 0x5205402B9270C86F;
+const PRIME_Y: i64 = 0x598CD327003817B5;
+const PRIME_Z: i64 = 0x5BCC226E9FA0BACB;
+const HASH_MULTIPLIER: i64 = 0x53A3F72DEEC546F5;
+const SEED_FLIP_3D: i64 = -0x52D547B2E96ED629;
+
+const SKEW_2D: f64 = 0.366025403784439;
+const UNSKEW_2D: f64 = -0.21132486540518713;
+const RSQUARED_2D: f32 = 0.5;
+const RSQUARED_3D: f32 = 0.6;
+
+// Scale the gradients so the noise is between -1 and 1
+const NORMALIZER_2D: f32 = 0.010_016_341;
+const NORMALIZER_3D: f32 = 0.079_698_38;
+
+/// OpenSimplex2 noise. Unlike the original simplex noise the 3D version uses a
+/// rotated body-centered cubic lattice, so it has no axis aligned artefacts.
+pub struct OpenSimplex2Noise
+{
+    seed: i64,
+    gradients_2d: Vec<[f32; 2]>,
+    gradients_3d: Vec<[f32; 3]>,
+}
+
+impl OpenSimplex2Noise
+{
+    pub fn new(seed: Option<[u8; 32]>) -> OpenSimplex2Noise
+    {
+        // The hash uses a 64 bit seed
+        let mut rng: StdRng = SeedableRng::from_seed(seed_or_random(seed));
+        let seed: i64 = rng.gen();
+
+        // 24 directions, 15 degrees apart
+        let gradients_2d = (0..24).map(|i|
+        {
+            let angle = (7.5 + 15.0 * i as f32).to_radians();
+            [angle.cos() / NORMALIZER_2D, angle.sin() / NORMALIZER_2D]
+        }).collect();
+
+        // For each pair of axes and each sign: two directions between the pair and one
+        // tilted each way towards the third axis. 48 directions in total.
+        let (a, b, c) = (2.224_744_8, 3.086_266_5, 1.172_151_3);
+        let mut gradients_3d = vec![];
+        for &(i, j, k) in [(0, 1, 2), (0, 2, 1), (1, 2, 0)].iter()
+        {
+            for &(si, sj) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)].iter()
+            {
+                for &(gi, gj, gk) in [(a, a, -1.0), (a, a, 1.0), (b, c, 0.0), (c, b, 0.0)].iter()
+                {
+                    let mut g = [0.0; 3];
+                    g[i] = si * gi / NORMALIZER_3D;
+                    g[j] = sj * gj / NORMALIZER_3D;
+                    g[k] = gk / NORMALIZER_3D;
+                    gradients_3d.push(g);
+                }
+            }
+        }
+
+        OpenSimplex2Noise { seed, gradients_2d, gradients_3d }
+    }
+
+    fn grad_2d(self: &OpenSimplex2Noise, xsvp: i64, ysvp: i64, dx: f32, dy: f32) -> f32
+    {
+        let mut hash = (self.seed ^ xsvp ^ ysvp).wrapping_mul(HASH_MULTIPLIER);
+        hash ^= hash >> 32;
+        let g = self.gradients_2d[(hash as u64 % self.gradients_2d.len() as u64) as usize];
+        g[0] * dx + g[1] * dy
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn grad_3d(self: &OpenSimplex2Noise, seed: i64, xrvp: i64, yrvp: i64, zrvp: i64, dx: f32, dy: f32, dz: f32) -> f32
+    {
+        let mut hash = ((seed ^ xrvp) ^ (yrvp ^ zrvp)).wrapping_mul(HASH_MULTIPLIER);
+        hash ^= hash >> 32;
+        let g = self.gradients_3d[(hash as u64 % self.gradients_3d.len() as u64) as usize];
+        g[0] * dx + g[1] * dy + g[2] * dz
+    }
+}
+
+impl Noise2D for OpenSimplex2Noise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        // Skew onto the triangular lattice
+        let s = SKEW_2D * (x as f64 + y as f64);
+        let (xs, ys) = (x as f64 + s, y as f64 + s);
+
+        let (xsb, ysb) = (xs.floor(), ys.floor());
+        let (xi, yi) = ((xs - xsb) as f32, (ys - ysb) as f32);
+        let xsbp = (xsb as i64).wrapping_mul(PRIME_X);
+        let ysbp = (ysb as i64).wrapping_mul(PRIME_Y);
+
+        // Unskew to get the offset from the base vertex
+        let t = (xi + yi) * UNSKEW_2D as f32;
+        let (dx0, dy0) = (xi + t, yi + t);
+
+        let mut value = 0.0;
+        let mut add = |a: f32, xsvp: i64, ysvp: i64, dx: f32, dy: f32|
+        {
+            if a > 0.0
+            {
+                value += (a * a) * (a * a) * self.grad_2d(xsvp, ysvp, dx, dy);
+            }
+        };
+
+        add(RSQUARED_2D - dx0 * dx0 - dy0 * dy0, xsbp, ysbp, dx0, dy0);
+
+        let diagonal = 1.0 + 2.0 * UNSKEW_2D as f32;
+        let (dx1, dy1) = (dx0 - diagonal, dy0 - diagonal);
+        add(RSQUARED_2D - dx1 * dx1 - dy1 * dy1, xsbp.wrapping_add(PRIME_X), ysbp.wrapping_add(PRIME_Y), dx1, dy1);
+
+        // The third vertex depends on which triangle of the cell we're in
+        let unskew = UNSKEW_2D as f32;
+        if dy0 > dx0
+        {
+            let (dx2, dy2) = (dx0 - unskew, dy0 - (unskew + 1.0));
+            add(RSQUARED_2D - dx2 * dx2 - dy2 * dy2, xsbp, ysbp.wrapping_add(PRIME_Y), dx2, dy2);
+        }
+        else
+        {
+            let (dx2, dy2) = (dx0 - (unskew + 1.0), dy0 - unskew);
+            add(RSQUARED_2D - dx2 * dx2 - dy2 * dy2, xsbp.wrapping_add(PRIME_X), ysbp, dx2, dy2);
+        }
+
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+impl Noise3D for OpenSimplex2Noise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        // Rotate so the lattice's main diagonal points along y, this hides the lattice
+        // better than sampling the cubic grids directly
+        let r = (2.0 / 3.0) * (x as f64 + y as f64 + z as f64);
+        let (xr, yr, zr) = (r - x as f64, r - y as f64, r - z as f64);
+
+        // The BCC lattice is two cubic lattices, the second offset by half a cell
+        let (xrb, yrb, zrb) = (xr.round(), yr.round(), zr.round());
+        let (mut xri, mut yri, mut zri) = ((xr - xrb) as f32, (yr - yrb) as f32, (zr - zrb) as f32);
+
+        // -1 if the offset is positive, 1 if it's negative. Points to the far side of the cell.
+        let sign = |v: f32| if v > 0.0 { -1 } else { 1 };
+        let (mut x_sign, mut y_sign, mut z_sign) = (sign(xri), sign(yri), sign(zri));
+        let (mut ax0, mut ay0, mut az0) = (xri.abs(), yri.abs(), zri.abs());
+
+        let mut xrbp = (xrb as i64).wrapping_mul(PRIME_X);
+        let mut yrbp = (yrb as i64).wrapping_mul(PRIME_Y);
+        let mut zrbp = (zrb as i64).wrapping_mul(PRIME_Z);
+
+        let mut seed = self.seed;
+        let mut value = 0.0;
+        let mut a = RSQUARED_3D - xri * xri - yri * yri - zri * zri;
+        for lattice in 0..2
+        {
+            // Closest vertex
+            if a > 0.0
+            {
+                value += (a * a) * (a * a) * self.grad_3d(seed, xrbp, yrbp, zrbp, xri, yri, zri);
+            }
+
+            // Next closest, across the face we're closest to
+            if ax0 >= ay0 && ax0 >= az0
+            {
+                let b = a + ax0 + ax0 - 1.0;
+                if b > 0.0
+                {
+                    value += (b * b) * (b * b) * self.grad_3d(seed, xrbp.wrapping_sub(x_sign * PRIME_X), yrbp, zrbp,
+                                                            xri + x_sign as f32, yri, zri);
+                }
+            }
+            else if ay0 > ax0 && ay0 >= az0
+            {
+                let b = a + ay0 + ay0 - 1.0;
+                if b > 0.0
+                {
+                    value += (b * b) * (b * b) * self.grad_3d(seed, xrbp, yrbp.wrapping_sub(y_sign * PRIME_Y), zrbp,
+                                                            xri, yri + y_sign as f32, zri);
+                }
+            }
+            else
+            {
+                let b = a + az0 + az0 - 1.0;
+                if b > 0.0
+                {
+                    value += (b * b) * (b * b) * self.grad_3d(seed, xrbp, yrbp, zrbp.wrapping_sub(z_sign * PRIME_Z),
+                                                            xri, yri, zri + z_sign as f32);
+                }
+            }
+
+            if lattice == 1
+            {
+                break;
+            }
+
+            // Move to the offset lattice
+            ax0 = 0.5 - ax0;
+            ay0 = 0.5 - ay0;
+            az0 = 0.5 - az0;
+
+            xri = x_sign as f32 * ax0;
+            yri = y_sign as f32 * ay0;
+            zri = z_sign as f32 * az0;
+
+            a += (0.75 - ax0) - (ay0 + az0);
+
+            if x_sign < 0 { xrbp = xrbp.wrapping_add(PRIME_X); }
+            if y_sign < 0 { yrbp = yrbp.wrapping_add(PRIME_Y); }
+            if z_sign < 0 { zrbp = zrbp.wrapping_add(PRIME_Z); }
+
+            x_sign = -x_sign;
+            y_sign = -y_sign;
+            z_sign = -z_sign;
+
+            seed ^= SEED_FLIP_3D;
+        }
+
+        value.clamp(-1.0, 1.0)
+    }
+}
@@ -0,0 +1,86 @@
+
+#![allow(non_snake_case)]
+use crate::utils::noise::{ Noise2D, Noise3D, seed_or_random, permutation_table, fade, lerp };
+
+/// Ken Perlin's improved gradient noise (2002)
+pub struct PerlinNoise
+{
+    perm: [usize; 512]
+}
+
+impl PerlinNoise
+{
+    pub fn new(seed: Option<[u8; 32]>) -> PerlinNoise
+    {
+        PerlinNoise { perm: permutation_table(seed_or_random(seed)) }
+    }
+
+    // 8 directions, the diagonals and the axes
+    fn grad_2d(hash: usize, x: f32, y: f32) -> f32
+    {
+        match hash & 7
+        {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y
+        }
+    }
+
+    // The 12 cube edge directions (4 repeated to make 16)
+    fn grad_3d(hash: usize, x: f32, y: f32, z: f32) -> f32
+    {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+}
+
+impl Noise2D for PerlinNoise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        let (xf, yf) = (x.floor(), y.floor());
+        let (xi, yi) = ((xf as i32 & 255) as usize, (yf as i32 & 255) as usize);
+        let (x, y) = (x - xf, y - yf);
+        let (u, v) = (fade(x), fade(y));
+
+        let perm = &self.perm;
+        let (a, b) = (perm[xi] + yi, perm[xi + 1] + yi);
+
+        let value = lerp(lerp(PerlinNoise::grad_2d(perm[a], x, y), PerlinNoise::grad_2d(perm[b], x - 1.0, y), u),
+                        lerp(PerlinNoise::grad_2d(perm[a + 1], x, y - 1.0), PerlinNoise::grad_2d(perm[b + 1], x - 1.0, y - 1.0), u), v);
+
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+impl Noise3D for PerlinNoise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = ((xf as i32 & 255) as usize, (yf as i32 & 255) as usize, (zf as i32 & 255) as usize);
+        let (x, y, z) = (x - xf, y - yf, z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let perm = &self.perm;
+        let a = perm[xi] + yi;
+        let (aa, ab) = (perm[a] + zi, perm[a + 1] + zi);
+        let b = perm[xi + 1] + yi;
+        let (ba, bb) = (perm[b] + zi, perm[b + 1] + zi);
+
+        let grad = PerlinNoise::grad_3d;
+        let value = lerp(lerp(lerp(grad(perm[aa], x, y, z), grad(perm[ba], x - 1.0, y, z), u),
+                              lerp(grad(perm[ab], x, y - 1.0, z), grad(perm[bb], x - 1.0, y - 1.0, z), u), v),
+                         lerp(lerp(grad(perm[aa + 1], x, y, z - 1.0), grad(perm[ba + 1], x - 1.0, y, z - 1.0), u),
+                              lerp(grad(perm[ab + 1], x, y - 1.0, z - 1.0), grad(perm[bb + 1], x - 1.0, y - 1.0, z - 1.0), u), v), w);
+
+        value.clamp(-1.0, 1.0)
+    }
+}
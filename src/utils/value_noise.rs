@@ -0,0 +1,60 @@
+
+#![allow(non_snake_case)]
+use crate::utils::noise::{ Noise2D, Noise3D, seed_or_random, permutation_table, random_table, fade, lerp };
+
+/// Random values on an integer lattice blended with quintic smoothing
+pub struct ValueNoise
+{
+    perm: [usize; 512],
+
+    // Between -1 and 1
+    values: Vec<f32>,
+}
+
+impl ValueNoise
+{
+    pub fn new(seed: Option<[u8; 32]>) -> ValueNoise
+    {
+        let seed = seed_or_random(seed);
+        let values = random_table(seed, 256).iter().map(|v| v * 2.0 - 1.0).collect();
+        ValueNoise { perm: permutation_table(seed), values }
+    }
+
+    fn value_2d(self: &ValueNoise, x: usize, y: usize) -> f32
+    {
+        self.values[self.perm[self.perm[x & 255] + (y & 255)]]
+    }
+
+    fn value_3d(self: &ValueNoise, x: usize, y: usize, z: usize) -> f32
+    {
+        self.values[self.perm[self.perm[self.perm[x & 255] + (y & 255)] + (z & 255)]]
+    }
+}
+
+impl Noise2D for ValueNoise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        let (xf, yf) = (x.floor(), y.floor());
+        let (xi, yi) = ((xf as i32 & 255) as usize, (yf as i32 & 255) as usize);
+        let (u, v) = (fade(x - xf), fade(y - yf));
+
+        lerp(lerp(self.value_2d(xi, yi), self.value_2d(xi + 1, yi), u),
+             lerp(self.value_2d(xi, yi + 1), self.value_2d(xi + 1, yi + 1), u), v)
+    }
+}
+
+impl Noise3D for ValueNoise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = ((xf as i32 & 255) as usize, (yf as i32 & 255) as usize, (zf as i32 & 255) as usize);
+        let (u, v, w) = (fade(x - xf), fade(y - yf), fade(z - zf));
+
+        let layer = |z: usize| lerp(lerp(self.value_3d(xi, yi, z), self.value_3d(xi + 1, yi, z), u),
+                                    lerp(self.value_3d(xi, yi + 1, z), self.value_3d(xi + 1, yi + 1, z), u), v);
+
+        lerp(layer(zi), layer(zi + 1), w)
+    }
+}
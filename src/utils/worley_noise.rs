@@ -0,0 +1,125 @@
+
+#![allow(non_snake_case)]
+use crate::utils::noise::{ Noise2D, Noise3D, seed_or_random, permutation_table, random_table };
+
+/// Which distance WorleyNoise returns
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WorleyDistance
+{
+    /// Distance to the closest feature point, round cells
+    F1,
+
+    /// Distance to the second closest feature point
+    F2,
+
+    /// F2 - F1, zero along the edges between cells
+    F2_MINUS_F1
+}
+
+/// Cellular noise: one randomly placed feature point per lattice cell, the value
+/// comes from the distances to the closest points. Distances are scaled so the usual
+/// values cover -1 to 1 (-1 is close to a point, or on an edge for F2_MINUS_F1).
+pub struct WorleyNoise
+{
+    perm: [usize; 512],
+
+    // Feature point offsets inside each cell, 3 per hash
+    points: Vec<f32>,
+
+    distance: WorleyDistance,
+}
+
+impl WorleyNoise
+{
+    pub fn new(seed: Option<[u8; 32]>, distance: WorleyDistance) -> WorleyNoise
+    {
+        let seed = seed_or_random(seed);
+        WorleyNoise { perm: permutation_table(seed), points: random_table(seed, 256 * 3), distance }
+    }
+
+    fn hash(self: &WorleyNoise, x: i32, y: i32, z: i32) -> usize
+    {
+        let perm = &self.perm;
+        perm[perm[perm[(x & 255) as usize] + (y & 255) as usize] + (z & 255) as usize]
+    }
+
+    /// F1 and F2 squared to the value for the distance mode
+    fn value(self: &WorleyNoise, f1: f32, f2: f32, scales: [f32; 3]) -> f32
+    {
+        let (f1, f2) = (f1.sqrt(), f2.sqrt());
+        let value = match self.distance
+        {
+            WorleyDistance::F1 => f1 * scales[0],
+            WorleyDistance::F2 => f2 * scales[1],
+            WorleyDistance::F2_MINUS_F1 => (f2 - f1) * scales[2]
+        };
+
+        (value * 2.0 - 1.0).clamp(-1.0, 1.0)
+    }
+}
+
+/// Keeps the two smallest values
+fn closest(f1: &mut f32, f2: &mut f32, d: f32)
+{
+    if d < *f1
+    {
+        *f2 = *f1;
+        *f1 = d;
+    }
+    else if d < *f2
+    {
+        *f2 = d;
+    }
+}
+
+// One over roughly the largest F1, F2 and F2 - F1 distances, measured from samples.
+// The rare larger distances get clamped.
+const SCALES_2D: [f32; 3] = [1.15, 0.9, 1.2];
+const SCALES_3D: [f32; 3] = [1.1, 1.0, 1.5];
+
+impl Noise2D for WorleyNoise
+{
+    fn sample_2D(&self, x: f32, y: f32) -> f32
+    {
+        let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+        let (mut f1, mut f2) = (f32::MAX, f32::MAX);
+
+        for i in cx - 1..=cx + 1
+        {
+            for j in cy - 1..=cy + 1
+            {
+                let h = self.hash(i, j, 0) * 3;
+                let (dx, dy) = (i as f32 + self.points[h] - x, j as f32 + self.points[h + 1] - y);
+                closest(&mut f1, &mut f2, dx * dx + dy * dy);
+            }
+        }
+
+        self.value(f1, f2, SCALES_2D)
+    }
+}
+
+impl Noise3D for WorleyNoise
+{
+    fn sample_3D(&self, x: f32, y: f32, z: f32) -> f32
+    {
+        let (cx, cy, cz) = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let (mut f1, mut f2) = (f32::MAX, f32::MAX);
+
+        for i in cx - 1..=cx + 1
+        {
+            for j in cy - 1..=cy + 1
+            {
+                for k in cz - 1..=cz + 1
+                {
+                    let h = self.hash(i, j, k) * 3;
+                    let (dx, dy, dz) = (i as f32 + self.points[h] - x, j as f32 + self.points[h + 1] - y,
+                                        k as f32 + self.points[h + 2] - z);
+                    closest(&mut f1, &mut f2, dx * dx + dy * dy + dz * dz);
+                }
+            }
+        }
+
+        self.value(f1, f2, SCALES_3D)
+    }
+}
@@ -1,9 +0,0 @@
-
-pub use self::key_board::KeyBoard;
-pub use self::key_board::KeyCode;
-pub use self::mouse::Mouse;
-pub use self::mouse::MouseState;
-pub use self::mouse::Coord;
-
-mod key_board;
-mod mouse;
\ No newline at end of file